@@ -1,23 +1,48 @@
-use crate::models::{CategoryStats, Config, ProjectStats, RepoInfo, RepoStats};
-use crate::parser::{group_repos_by_vanity, parse_repo_string};
+use crate::authors::{split_author, AuthorFilter};
+use crate::cache::ResultCache;
+use crate::clones::update_clones;
+use crate::config::{parse_repos_from_config, schedules_from_config};
+use crate::journal;
+use crate::language::{FileTypeOrder, TestPatterns};
+#[cfg(feature = "libgit2")]
+use crate::libgit2;
+use crate::models::{
+    CategoryStats, Config, ForgeConfig, ProjectStats, RepoInfo, RepoStats, DAY_END_HOUR,
+};
+use crate::parser::{group_repos_by_vanity, parse_repo_entry};
+use crate::progress;
+use crate::remote::{read_remote_log, RemoteRepo};
+use crate::report::{build_report, Report};
+use crate::schedule::Schedules;
+use crate::state;
+use crate::timing::{self, Phase};
 use crate::utils::{
-    aggregate_stats, count_files_and_lines, debug, debug_git_command, is_repo_active, log,
+    aggregate_stats, binary_files_detail, commit_intervals, count_files_and_lines, debug,
+    debug_git_command, file_type_detail, format_minutes, is_repo_active, line_kinds_detail, log,
+    tag_commit_date, tests_detail, top_file_types,
 };
-use chrono::NaiveDate;
+use chrono::{DateTime, Datelike, Duration, Local, NaiveDate, NaiveDateTime, NaiveTime, Timelike};
 use colored::*;
-use std::collections::HashMap;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
+use std::ops::RangeInclusive;
 use std::process::Command;
 
 /// Analyzes a single repository and returns its statistics.
 ///
 /// # Arguments
-/// * `repo` - Path to the Git repository
-/// * `author` - Optional author name to filter commits
+/// * `repo` - Path to the Git repository, or a remote one such as `github:org/repo`
+/// * `authors` - Authors whose commits are counted
 /// * `since` - Start date for commit analysis (YYYY-MM-DD format)
 /// * `until` - End date for commit analysis (YYYY-MM-DD format)
 /// * `show_details` - Whether to print detailed information about the repository
-/// * `show_filetypes` - Whether to analyze and show file type statistics
-/// * `working_hours` - Optional working hours to track out-of-hours commits
+/// * `filetypes` - How to order the file type statistics, None not to show them
+/// * `files` - Which files are counted for the file and line statistics, if any
+/// * `history` - Which part of the history is analyzed
+/// * `working_hours` - Optional schedules to track out-of-hours commits, looked up per author
+/// * `session_gap` - Maximum gap in minutes between two commits of the same work session
+/// * `test_patterns` - Path patterns of the test files
+/// * `cache` - Optional cache the statistics are reused from and saved to
 ///
 /// # Returns
 /// * `RepoStats` - Statistics about the repository's commits, files, and lines
@@ -28,211 +53,290 @@ use std::process::Command;
 /// - Count files and lines in the repository
 /// - Analyze file types if requested
 /// - Track out-of-hours commits
+/// - Estimate the time worked outside working hours out of the work sessions
+#[allow(clippy::too_many_arguments)]
 pub fn analyze_repo(
     repo: &str,
-    author: &Option<String>,
+    authors: &AuthorFilter,
     since: &str,
     until: &str,
     show_details: bool,
-    show_filetypes: bool,
-    working_hours: Option<(u32, u32, u32, u32)>,
+    filetypes: Option<FileTypeOrder>,
+    files: FileScope,
+    history: &HistoryFilter,
+    working_hours: Option<&Schedules>,
+    session_gap: i64,
+    test_patterns: &TestPatterns,
+    cache: Option<&ResultCache>,
 ) -> RepoStats {
     let mut stats = RepoStats::default();
-
-    // Get commit history
-    let mut cmd = Command::new("git");
-    cmd.args([
-        "-C",
-        repo,
-        "log",
-        "--pretty=format:%h %ad %s",
-        "--date=iso-strict",
-    ]);
-
-    if let Some(a) = author {
-        cmd.arg(format!("--author={}", a));
+    // Remote repositories have no files to count nor submodules
+    let remote = RemoteRepo::parse(repo);
+
+    // Tags anchor the period to the releases of this very repository
+    let since_tag_date = history.since_tag.as_deref().and_then(|tag| {
+        let date = timing::time(repo, Phase::Log, || {
+            tag_date(repo, tag, true, history.backend)
+        });
+        if date.is_none() {
+            debug(&format!("Tag {} not found in {}, using --since", tag, repo));
+        }
+        date
+    });
+    let until_tag_date = history.until_tag.as_deref().and_then(|tag| {
+        let date = timing::time(repo, Phase::Log, || {
+            tag_date(repo, tag, false, history.backend)
+        });
+        if date.is_none() {
+            debug(&format!("Tag {} not found in {}, using --until", tag, repo));
+        }
+        date
+    });
+    let since = since_tag_date.as_deref().unwrap_or(since);
+    let until = until_tag_date.as_deref().unwrap_or(until);
+
+    // A previous run over the same history with the same options already has the
+    // statistics, unless the commits are listed
+    let cached = cache.filter(|_| !show_details).and_then(|cache| {
+        let schedules = working_hours.map(|schedules| {
+            (
+                &schedules.global,
+                schedules.repos.get(repo),
+                &schedules.team,
+            )
+        });
+        // Commits of the journal count too, as it was when the statistics were saved
+        let journal = history.journal.then(journal::journal_digest);
+        let options = format!(
+            "{:?}",
+            (
+                since,
+                journal,
+                authors,
+                files,
+                history,
+                schedules,
+                session_gap,
+                test_patterns
+            )
+        );
+        cache
+            .key(repo, history.backend, until, &options)
+            .map(|key| (cache, key))
+    });
+    if let Some(stats) = cached.as_ref().and_then(|(cache, key)| cache.load(key)) {
+        debug(&format!("Using the cached statistics of {}", repo));
+        return stats;
     }
 
-    cmd.arg(format!("--since={}", since));
-    cmd.arg(format!("--until={}", until));
-
-    debug(&format!("Executing git command on repo: {}", repo));
-
-    let output = match cmd.output() {
-        Ok(out) => {
-            debug_git_command(repo, &cmd, &out);
-            out
-        }
-        Err(e) => {
-            debug(&format!("Error executing git command: {}", e));
-            return stats;
+    // Get commit history
+    let commits = timing::time(repo, Phase::Log, || {
+        if let Some(remote) = &remote {
+            read_remote_log(remote, authors, since, until, history)
+        } else if history.incremental {
+            state::read_log(repo, authors, since, until, history)
+        } else {
+            read_log(repo, authors, LogRange::Period { since, until }, history)
         }
+    });
+    let Some(mut commits) = commits else {
+        return stats;
     };
-
-    if !output.status.success() {
-        debug(&format!(
-            "Git command failed with status: {}",
-            output.status
-        ));
+    if history.journal {
+        let lost = journal::lost_commits(repo, authors, since, until, history, &commits);
         debug(&format!(
-            "Error: {}",
-            String::from_utf8_lossy(&output.stderr)
+            "Found {} commits of the journal gone from {}",
+            lost.len(),
+            repo
         ));
+        commits.extend(lost);
     }
 
-    let stdout = String::from_utf8_lossy(&output.stdout);
-    let mut commits: Vec<String> = stdout.lines().map(String::from).collect();
+    // Files of the repository touched in the period, submodules excluded
+    let touched: BTreeSet<String> = commits
+        .iter()
+        .flat_map(|commit| commit.paths.iter().cloned())
+        .collect();
 
-    // Check for submodules automatically
-    debug(&format!("Checking for submodules in repository: {}", repo));
+    if remote.is_none() {
+        commits.extend(timing::time(repo, Phase::Submodules, || {
+            submodule_commits(repo, authors, since, until, history)
+        }));
+    }
 
-    // Get submodule status
-    let mut submodule_cmd = Command::new("git");
-    submodule_cmd.args(["-C", repo, "submodule", "status"]);
+    stats.commit_count = commits.len();
 
-    let submodule_output = match submodule_cmd.output() {
-        Ok(out) => {
-            debug_git_command(repo, &submodule_cmd, &out);
-            out
+    // Display information about found commits
+    debug(&format!(
+        "Found {} commits in repository {}",
+        commits.len(),
+        repo
+    ));
+
+    // Timestamps of the commits of every author, used to cluster them into work sessions
+    let mut timestamps: HashMap<&str, Vec<NaiveDateTime>> = HashMap::new();
+
+    // Parse commits by date and check for out-of-hours commits
+    for commit in &commits {
+        let author = &commit.author;
+        stats.lines_added += commit.lines_added;
+        stats.lines_removed += commit.lines_removed;
+        if commit.merge {
+            stats.merge_commits += 1;
         }
-        Err(e) => {
-            debug(&format!("Error executing git submodule command: {}", e));
-            // Continue without submodule info
-            stats.commit_count = commits.len();
-            return stats;
+        let commit_size = commit.lines_added + commit.lines_removed;
+        stats.commit_sizes.push(commit_size);
+        let touches_tests = commit.paths.iter().any(|path| test_patterns.is_test(path));
+        if touches_tests {
+            stats.test_commits += 1;
         }
-    };
-
-    // Process submodules only if the command was successful and returned output
-    let has_submodules = submodule_output.status.success() && !submodule_output.stdout.is_empty();
+        stats
+            .authors
+            .entry(author.clone())
+            .or_default()
+            .commit_count += 1;
 
-    if has_submodules {
-        let submodule_stdout = String::from_utf8_lossy(&submodule_output.stdout);
-        let submodule_lines: Vec<&str> = submodule_stdout.lines().collect();
+        // Team members are evaluated against their own schedule
+        let schedule = working_hours.map(|schedules| schedules.for_commit(repo, author));
 
-        debug(&format!("Found {} submodules", submodule_lines.len()));
+        if let Some(date_part) = commit.line.split_whitespace().nth(1) {
+            debug(&format!("Processing commit date: {}", date_part));
 
-        for line in submodule_lines {
-            let parts: Vec<&str> = line.trim().split_whitespace().collect();
-            if parts.len() >= 2 {
-                // Extract submodule path (2nd element)
-                let submodule_path = parts[1];
-                let full_path = format!("{}/{}", repo, submodule_path);
+            // Move the timestamp into the timezone the commit is evaluated in
+            let timezone = schedule
+                .map(|schedule| schedule.timezone)
+                .unwrap_or_default();
+            let instant = DateTime::parse_from_rfc3339(date_part).ok();
+            if let Some(instant) = instant {
+                stats.commit_times.push(instant.timestamp());
+            }
+            let timestamp = instant.map(|timestamp| timezone.convert(&timestamp));
+            match timestamp {
+                Some(timestamp) => timestamps.entry(author).or_default().push(timestamp),
+                None => debug(&format!("Failed to parse commit date: {}", date_part)),
+            }
 
-                debug(&format!("Found submodule: {}", full_path));
+            // Extract just the date part (YYYY-MM-DD)
+            let date = match timestamp {
+                Some(timestamp) => timestamp.format("%Y-%m-%d").to_string(),
+                None => date_part.split('T').next().unwrap_or(date_part).to_string(),
+            };
+            *stats.commits_by_date.entry(date.clone()).or_insert(0) += 1;
+            *stats.lines_added_by_date.entry(date.clone()).or_insert(0) += commit.lines_added;
+            *stats.lines_removed_by_date.entry(date.clone()).or_insert(0) += commit.lines_removed;
+
+            // Extract the time of day, when available
+            let commit_time = timestamp.map(|timestamp| (timestamp.hour(), timestamp.minute()));
+
+            // First and last commit of the day, late nights counting for the day before
+            if let Some(timestamp) = timestamp {
+                let day = (timestamp - Duration::hours(DAY_END_HOUR)).date();
+                let minute = (timestamp - day.and_time(NaiveTime::MIN)).num_minutes() as u32;
+                let day = day.format("%Y-%m-%d").to_string();
+                let first = stats
+                    .first_commit_by_date
+                    .entry(day.clone())
+                    .or_insert(minute);
+                *first = (*first).min(minute);
+                let last = stats.last_commit_by_date.entry(day).or_insert(minute);
+                *last = (*last).max(minute);
+            }
 
-                // Get commit history for this submodule
-                let mut sub_cmd = Command::new("git");
-                sub_cmd.args([
-                    "-C",
-                    &full_path,
-                    "log",
-                    "--pretty=format:[SUBMODULE %s] %h %ad %s",
-                    "--date=short",
-                ]);
+            // Weekday of the commit, Monday first
+            let weekday = NaiveDate::parse_from_str(&date, "%Y-%m-%d")
+                .ok()
+                .map(|day| day.weekday().num_days_from_monday() as usize);
 
-                if let Some(a) = author {
-                    sub_cmd.arg(format!("--author={}", a));
+            if let Some((hour, _)) = commit_time {
+                if let Some(slot) = stats.commits_by_hour.get_mut(hour as usize) {
+                    *slot += 1;
                 }
+                if let Some(weekday) = weekday {
+                    if let Some(slot) = stats.punch_card[weekday].get_mut(hour as usize) {
+                        *slot += 1;
+                    }
+                }
+            }
 
-                sub_cmd.arg(format!("--since={}", since));
-                sub_cmd.arg(format!("--until={}", until));
-
+            // Check if commit is outside the working hours of its weekday
+            if let (Some(schedule), Some((hour, minute)), Some(weekday)) =
+                (schedule, commit_time, weekday)
+            {
                 debug(&format!(
-                    "Executing git command on submodule: {}",
-                    full_path
+                    "Parsed commit time: {:02}:{:02} (working hours: {})",
+                    hour,
+                    minute,
+                    schedule.hours_for(weekday)
                 ));
-
-                let sub_output = match sub_cmd.output() {
-                    Ok(out) => {
-                        debug_git_command(&full_path, &sub_cmd, &out);
-                        out
-                    }
-                    Err(e) => {
-                        debug(&format!("Error executing git command on submodule: {}", e));
-                        continue;
+                if !schedule.contains(weekday, hour, minute) {
+                    stats.out_of_hours_commits += 1;
+                    stats.out_of_hours_commit_sizes.push(commit_size);
+                    if touches_tests {
+                        stats.out_of_hours_test_commits += 1;
                     }
-                };
-
-                if !sub_output.status.success() {
-                    debug(&format!(
-                        "Git command failed on submodule with status: {}",
-                        sub_output.status
-                    ));
-                } else {
-                    let sub_stdout = String::from_utf8_lossy(&sub_output.stdout);
-                    let sub_commit_count = sub_stdout.lines().count();
-
-                    // Add submodule commits to the list (convert to owned Strings)
-                    for commit in sub_stdout.lines() {
-                        commits.push(format!("{}", commit));
-                    }
-
+                    *stats.out_of_hours_by_date.entry(date.clone()).or_insert(0) += 1;
+                    stats
+                        .authors
+                        .entry(author.clone())
+                        .or_default()
+                        .out_of_hours_commits += 1;
                     debug(&format!(
-                        "Added {} commits from submodule {}",
-                        sub_commit_count, submodule_path
+                        "Found out-of-hours commit at {:02}:{:02}",
+                        hour, minute
                     ));
                 }
             }
         }
     }
 
-    stats.commit_count = commits.len();
-
-    // Display information about found commits
-    debug(&format!(
-        "Found {} commits in repository {}",
-        commits.len(),
-        repo
-    ));
-
-    // Parse commits by date and check for out-of-hours commits
-    for commit in &commits {
-        if let Some(date_part) = commit.split_whitespace().nth(1) {
-            debug(&format!("Processing commit date: {}", date_part));
-
-            // Extract just the date part from ISO format (YYYY-MM-DD)
-            let date = date_part.split('T').next().unwrap_or(date_part);
-            *stats.commits_by_date.entry(date.to_string()).or_insert(0) += 1;
-
-            // Check if commit is outside working hours
-            if let Some((start_hour, start_min, end_hour, end_min)) = working_hours {
-                if let Some(time_part) = date_part.split('T').nth(1) {
-                    debug(&format!("Found time part: {}", time_part));
-                    if let Some((hour, minute)) = parse_commit_time(time_part) {
-                        debug(&format!(
-                            "Parsed commit time: {:02}:{:02} (working hours: {:02}:{:02}-{:02}:{:02})",
-                            hour, minute, start_hour, start_min, end_hour, end_min
-                        ));
-                        if !is_within_working_hours(
-                            hour, minute, start_hour, start_min, end_hour, end_min,
-                        ) {
-                            stats.out_of_hours_commits += 1;
-                            debug(&format!(
-                                "Found out-of-hours commit at {:02}:{:02}",
-                                hour, minute
-                            ));
-                        }
-                    } else {
-                        debug(&format!("Failed to parse time: {}", time_part));
-                    }
-                } else {
-                    debug("No time part found in commit date");
-                }
-            }
+    // Estimate the time spent working outside working hours
+    if let Some(working_hours) = working_hours {
+        for (author, author_timestamps) in &mut timestamps {
+            let schedule = working_hours.for_commit(repo, author);
+            let minutes: usize = work_sessions(author_timestamps, session_gap)
+                .into_iter()
+                .map(|(start, end)| schedule.minutes_outside(start, end))
+                .sum();
+            stats.out_of_hours_minutes += minutes;
+            stats
+                .authors
+                .entry(author.to_string())
+                .or_default()
+                .out_of_hours_minutes += minutes;
         }
+        debug(&format!(
+            "Estimated {} minutes worked outside working hours",
+            stats.out_of_hours_minutes
+        ));
     }
 
-    // Count files and lines
-    let (file_count, line_count, file_types) = count_files_and_lines(repo);
-    stats.file_count = file_count;
-    stats.line_count = line_count;
-    stats.file_types = file_types;
+    // Count files and lines, unless they are not shown
+    if files != FileScope::Skip && remote.is_none() {
+        let counts = timing::time(repo, Phase::Files, || {
+            count_files_and_lines(
+                repo,
+                (files == FileScope::Touched).then_some(&touched),
+                &history.pathspecs,
+                test_patterns,
+                history.backend,
+            )
+        });
+        stats.files_counted = true;
+        stats.file_count = counts.file_count;
+        stats.line_count = counts.lines.total();
+        stats.line_kinds = counts.lines;
+        stats.file_types = counts.file_types;
+        stats.line_kinds_by_type = counts.lines_by_type;
+        stats.binary_files = counts.binary_files;
+        stats.binary_size = counts.binary_size;
+        stats.test_files = counts.test_files;
+        stats.test_lines = counts.test_lines;
 
-    debug(&format!(
-        "Counted {} files, {} lines in repository {}",
-        file_count, line_count, repo
-    ));
+        debug(&format!(
+            "Counted {} files, {} lines in repository {}",
+            stats.file_count, stats.line_count, repo
+        ));
+    }
 
     if show_details {
         // Print repo stats with colors
@@ -242,29 +346,53 @@ pub fn analyze_repo(
             "Commits".yellow(),
             stats.commit_count.to_string().cyan()
         ));
-        if let Some(_) = working_hours {
+        if working_hours.is_some() {
             log(&format!(
                 "{}: {}",
                 "Out-of-hours commits".yellow(),
                 stats.out_of_hours_commits.to_string().cyan()
             ));
         }
+        if stats.merge_commits > 0 {
+            log(&format!(
+                "{}: {}",
+                "Merge commits".yellow(),
+                stats.merge_commits.to_string().cyan()
+            ));
+        }
         log(&format!(
-            "{}: {}",
+            "{}: {}{}",
             "Files".yellow(),
-            stats.file_count.to_string().cyan()
+            stats.file_count.to_string().cyan(),
+            binary_files_detail(&stats)
         ));
         log(&format!(
-            "{}: {}",
+            "{}: {} {}",
             "Lines of code".yellow(),
-            stats.line_count.to_string().cyan()
+            stats.line_kinds.code.to_string().cyan(),
+            line_kinds_detail(&stats.line_kinds)
+        ));
+        log(&format!("{}: {}", "Tests".yellow(), tests_detail(&stats)));
+        log(&format!(
+            "{}: {} {}",
+            "Lines changed".yellow(),
+            format!("+{}", stats.lines_added).green(),
+            format!("-{}", stats.lines_removed).red()
         ));
+        if let Some((mean, median)) = commit_intervals(&stats.commit_times) {
+            log(&format!(
+                "{}: mean {}, median {}",
+                "Time between commits".yellow(),
+                format_minutes(mean).cyan(),
+                format_minutes(median).cyan()
+            ));
+        }
 
         // Show commit history
         if !commits.is_empty() {
             log(&format!("\n{}", "Commit history:".bright_magenta()));
             for commit in commits {
-                log(&format!("{}", commit));
+                log(&commit.line);
             }
 
             // Show commits by date (sorted)
@@ -295,129 +423,638 @@ pub fn analyze_repo(
             }
 
             // Show file types
-            if show_filetypes {
-                if !stats.file_types.is_empty() {
-                    log(&format!("\n{}", "File types:".bright_magenta()));
-                    let mut types: Vec<(String, usize)> = stats
-                        .file_types
-                        .iter()
-                        .map(|(k, v)| (k.clone(), *v))
-                        .collect();
-
-                    // Sort by count (descending)
-                    types.sort_by(|a, b| b.1.cmp(&a.1));
-
-                    for (ext, count) in types {
-                        log(&format!(
-                            "  {} - {} {}",
-                            ext.bright_yellow(),
-                            count,
-                            "files".green()
-                        ));
-                    }
+            if let (Some(order), false) = (filetypes, stats.file_types.is_empty()) {
+                log(&format!("\n{}", "File types:".bright_magenta()));
+                for (language, files, lines) in top_file_types(&stats, order, usize::MAX) {
+                    log(&format!(
+                        "  {} - {}",
+                        language.bright_yellow(),
+                        file_type_detail(files, lines)
+                    ));
                 }
             }
         }
     }
 
+    if let Some((cache, key)) = cached {
+        cache.store(&key, &stats);
+    }
+
     stats
 }
 
-/// Parses a time string in ISO format (HH:MM:SS+HHMM) and returns the hour and minute
-fn parse_commit_time(time: &str) -> Option<(u32, u32)> {
-    // Split on the timezone offset
-    let time_part = time.split('+').next()?;
-    let parts: Vec<&str> = time_part.split(':').collect();
-    if parts.len() < 2 {
+/// Looks up the committer date of the commit a tag points to with the chosen
+/// backend, see `tag_commit_date`.
+fn tag_date(repo: &str, tag: &str, after: bool, backend: Backend) -> Option<String> {
+    match backend {
+        Backend::Git => tag_commit_date(repo, tag, after),
+        #[cfg(feature = "libgit2")]
+        Backend::Libgit2 => libgit2::tag_commit_date(repo, tag, after),
+    }
+}
+
+/// Which commits of the history are read.
+#[derive(Debug, Clone, Copy)]
+pub enum LogRange<'a> {
+    /// The commits committed in a period, between two dates
+    Period { since: &'a str, until: &'a str },
+    /// The commits of HEAD that a commit does not have, the whole history for None
+    After(Option<&'a str>),
+}
+
+/// Reads the commits of a repository with the chosen backend.
+///
+/// # Arguments
+/// * `repo` - Path to the Git repository
+/// * `authors` - Authors whose commits are counted
+/// * `range` - Which commits are read
+/// * `history` - Which part of the history is analyzed
+///
+/// # Returns
+/// * `Option<Vec<LoggedCommit>>` - The commits, newest first, None if the history could not be read
+pub fn read_log(
+    repo: &str,
+    authors: &AuthorFilter,
+    range: LogRange,
+    history: &HistoryFilter,
+) -> Option<Vec<LoggedCommit>> {
+    match history.backend {
+        Backend::Git => git_log(repo, authors, range, history),
+        #[cfg(feature = "libgit2")]
+        Backend::Libgit2 => libgit2::read_log(repo, authors, range, history)
+            .map_err(|err| debug(&format!("Error reading the history of {}: {}", repo, err)))
+            .ok(),
+    }
+}
+
+/// Reads the commits of a repository by running `git log`.
+fn git_log(
+    repo: &str,
+    authors: &AuthorFilter,
+    range: LogRange,
+    history: &HistoryFilter,
+) -> Option<Vec<LoggedCommit>> {
+    let mut cmd = Command::new("git");
+    cmd.args([
+        "-C",
+        repo,
+        "log",
+        &format!(
+            "--pretty=format:{}{}{}%h {} %s",
+            authors.source.identity_format(),
+            PARENTS_FORMAT,
+            COMMITTED_FORMAT,
+            authors.source.date_placeholder()
+        ),
+        "--date=iso-strict",
+        // Consolidate historical names/emails through .mailmap before filtering by author
+        "--use-mailmap",
+        // Lines added and removed by every commit
+        "--numstat",
+    ]);
+
+    // git ORs repeated --author patterns
+    for a in &authors.include {
+        cmd.arg(authors.source.filter_arg(a));
+    }
+    cmd.args(history.git_args());
+
+    match range {
+        LogRange::Period { since, until } => {
+            cmd.arg(format!("--since={}", since));
+            cmd.arg(format!("--until={}", until));
+        }
+        LogRange::After(Some(commit)) => {
+            cmd.arg(format!("{}..HEAD", commit));
+        }
+        LogRange::After(None) => {}
+    }
+
+    // Only the commits touching the pathspecs, e.g. the part of a monorepo we own
+    if !history.pathspecs.is_empty() {
+        cmd.arg("--");
+        cmd.args(&history.pathspecs);
+    }
+
+    debug(&format!("Executing git command on repo: {}", repo));
+
+    let output = match cmd.output() {
+        Ok(out) => {
+            debug_git_command(repo, &cmd, &out);
+            out
+        }
+        Err(e) => {
+            debug(&format!("Error executing git command: {}", e));
+            return None;
+        }
+    };
+
+    if !output.status.success() {
+        debug(&format!(
+            "Git command failed with status: {}",
+            output.status
+        ));
+        debug(&format!(
+            "Error: {}",
+            String::from_utf8_lossy(&output.stderr)
+        ));
+    }
+
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    Some(parse_log(&stdout, authors))
+}
+
+/// Reads the commits of the submodules of a repository in the period.
+///
+/// # Returns
+/// * `Vec<LoggedCommit>` - The commits of all the submodules inside the pathspecs
+fn submodule_commits(
+    repo: &str,
+    authors: &AuthorFilter,
+    since: &str,
+    until: &str,
+    history: &HistoryFilter,
+) -> Vec<LoggedCommit> {
+    debug(&format!("Checking for submodules in repository: {}", repo));
+
+    let submodule_paths = match history.backend {
+        Backend::Git => git_submodule_paths(repo),
+        #[cfg(feature = "libgit2")]
+        Backend::Libgit2 => libgit2::submodule_paths(repo).unwrap_or_else(|err| {
+            debug(&format!("Error listing submodules: {}", err));
+            Vec::new()
+        }),
+    };
+    if !submodule_paths.is_empty() {
+        debug(&format!("Found {} submodules", submodule_paths.len()));
+    }
+
+    let mut commits = Vec::new();
+    for submodule_path in submodule_paths {
+        let full_path = format!("{}/{}", repo, submodule_path);
+
+        if !history.pathspecs.is_empty()
+            && !history
+                .pathspecs
+                .iter()
+                .any(|pathspec| submodule_path.starts_with(pathspec.trim_end_matches('/')))
+        {
+            debug(&format!(
+                "Skipping submodule outside the pathspecs: {}",
+                full_path
+            ));
+            continue;
+        }
+
+        debug(&format!("Found submodule: {}", full_path));
+
+        // Get commit history for this submodule
+        let sub_commits = match history.backend {
+            Backend::Git => git_submodule_log(&full_path, authors, since, until, history),
+            #[cfg(feature = "libgit2")]
+            Backend::Libgit2 => {
+                // The pathspecs are relative to the repository, not to its submodules
+                let history = HistoryFilter {
+                    pathspecs: Vec::new(),
+                    ..history.clone()
+                };
+                libgit2::read_log(
+                    &full_path,
+                    authors,
+                    LogRange::Period { since, until },
+                    &history,
+                )
+                .map_err(|err| debug(&format!("Error reading submodule history: {}", err)))
+                .ok()
+            }
+        };
+
+        if let Some(sub_commits) = sub_commits {
+            debug(&format!(
+                "Added {} commits from submodule {}",
+                sub_commits.len(),
+                submodule_path
+            ));
+            // Add submodule commits to the list
+            commits.extend(sub_commits);
+        }
+    }
+
+    commits
+}
+
+/// Lists the submodules of a repository with `git submodule status`.
+fn git_submodule_paths(repo: &str) -> Vec<String> {
+    let mut submodule_cmd = Command::new("git");
+    submodule_cmd.args(["-C", repo, "submodule", "status"]);
+
+    let submodule_output = match submodule_cmd.output() {
+        Ok(out) => {
+            debug_git_command(repo, &submodule_cmd, &out);
+            out
+        }
+        Err(e) => {
+            debug(&format!("Error executing git submodule command: {}", e));
+            // Continue without submodule info
+            return Vec::new();
+        }
+    };
+
+    // Process submodules only if the command was successful
+    if !submodule_output.status.success() {
+        return Vec::new();
+    }
+
+    // Extract submodule path (2nd element)
+    String::from_utf8_lossy(&submodule_output.stdout)
+        .lines()
+        .filter_map(|line| line.split_whitespace().nth(1))
+        .map(|path| path.to_string())
+        .collect()
+}
+
+/// Reads the commits of a submodule in the period by running `git log`.
+fn git_submodule_log(
+    full_path: &str,
+    authors: &AuthorFilter,
+    since: &str,
+    until: &str,
+    history: &HistoryFilter,
+) -> Option<Vec<LoggedCommit>> {
+    let mut sub_cmd = Command::new("git");
+    sub_cmd.args([
+        "-C",
+        full_path,
+        "log",
+        &format!(
+            "--pretty=format:{}{}{}[SUBMODULE %s] %h {} %s",
+            authors.source.identity_format(),
+            PARENTS_FORMAT,
+            COMMITTED_FORMAT,
+            authors.source.date_placeholder()
+        ),
+        "--date=short",
+        "--use-mailmap",
+        "--numstat",
+    ]);
+
+    for a in &authors.include {
+        sub_cmd.arg(authors.source.filter_arg(a));
+    }
+    sub_cmd.args(history.git_args());
+
+    sub_cmd.arg(format!("--since={}", since));
+    sub_cmd.arg(format!("--until={}", until));
+
+    debug(&format!(
+        "Executing git command on submodule: {}",
+        full_path
+    ));
+
+    let sub_output = match sub_cmd.output() {
+        Ok(out) => {
+            debug_git_command(full_path, &sub_cmd, &out);
+            out
+        }
+        Err(e) => {
+            debug(&format!("Error executing git command on submodule: {}", e));
+            return None;
+        }
+    };
+
+    if !sub_output.status.success() {
+        debug(&format!(
+            "Git command failed on submodule with status: {}",
+            sub_output.status
+        ));
         return None;
     }
 
-    let hour: u32 = parts[0].parse().ok()?;
-    let minute: u32 = parts[1].parse().ok()?;
+    let sub_stdout = String::from_utf8_lossy(&sub_output.stdout);
+    Some(parse_log(&sub_stdout, authors))
+}
 
-    debug(&format!("Parsed commit time: {:02}:{:02}", hour, minute));
+/// Format of the abbreviated parent hashes, put right after the author in every `git log` line.
+const PARENTS_FORMAT: &str = "%p%x1f";
+
+/// Format of the committer date as a Unix timestamp, put right after the parent hashes.
+const COMMITTED_FORMAT: &str = "%ct%x1f";
+
+/// Separator between the parent hashes, the committer date and the rest of a `git log` line.
+const PARENTS_SEPARATOR: char = '\u{1f}';
+
+/// Which commits are counted depending on whether they are merges, as chosen by
+/// `--no-merges` and `--merges-only`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum MergeFilter {
+    /// Every commit
+    #[default]
+    Include,
+    /// Commits with a single parent
+    Exclude,
+    /// Commits with more than one parent
+    Only,
+}
+
+/// How the history of the repositories is read, as chosen by `--backend`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Backend {
+    /// Running the `git` binary
+    #[default]
+    Git,
+    /// The libgit2 library, without spawning any process
+    #[cfg(feature = "libgit2")]
+    Libgit2,
+}
+
+impl Backend {
+    /// Names accepted by `--backend`, including the ones this build lacks.
+    pub const NAMES: [&'static str; 2] = ["git", "libgit2"];
+
+    /// Parses a backend name as accepted by `--backend`.
+    ///
+    /// # Returns
+    /// * `Option<Backend>` - The backend, None if it is unknown or gitnapped was built without it
+    pub fn from_name(name: &str) -> Option<Backend> {
+        match name {
+            "git" => Some(Backend::Git),
+            #[cfg(feature = "libgit2")]
+            "libgit2" => Some(Backend::Libgit2),
+            _ => None,
+        }
+    }
+}
 
-    Some((hour, minute))
+/// Which files are counted for the file and line statistics. Reading every
+/// tracked file is the slowest part of the analysis, so it is skipped when
+/// nothing shows the counts.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum FileScope {
+    /// No file is read
+    Skip,
+    /// Every tracked file
+    #[default]
+    All,
+    /// The files touched by the commits of the period, with `--touched-only`
+    Touched,
 }
 
-/// Checks if a given time is within working hours
-fn is_within_working_hours(
-    hour: u32,
-    minute: u32,
-    start_hour: u32,
-    start_min: u32,
-    end_hour: u32,
-    end_min: u32,
-) -> bool {
-    let commit_time = hour * 60 + minute;
-    let start_time = start_hour * 60 + start_min;
-    let end_time = end_hour * 60 + end_min;
+/// Which part of the history is analyzed, as chosen by the merge, `--first-parent`
+/// and tag flags, and how it is read.
+#[derive(Debug, Clone, Default)]
+pub struct HistoryFilter {
+    /// Whether merge commits are counted
+    pub merges: MergeFilter,
+    /// Whether only the first parent of merges is followed, so a merged
+    /// branch counts as its merge commit alone
+    pub first_parent: bool,
+    /// Optional tag whose commit starts the period, looked up in every repository
+    pub since_tag: Option<String>,
+    /// Optional tag whose commit ends the period, looked up in every repository
+    pub until_tag: Option<String>,
+    /// Pathspecs restricting the analysis to parts of the repositories, empty for all of them
+    pub pathspecs: Vec<String>,
+    /// How the history is read
+    pub backend: Backend,
+    /// Whether the commits are kept from one run to the next, so only the new
+    /// ones are read
+    pub incremental: bool,
+    /// Whether the commits of the journal gone from the history, e.g. squashed
+    /// ones, are counted too
+    pub journal: bool,
+    /// How the forges of remote repositories are reached, by host
+    pub forges: BTreeMap<String, ForgeConfig>,
+    /// Whether the lines and files changed by commits are shown, which the
+    /// GitHub API only tells commit by commit
+    pub changes: bool,
+}
 
-    // If commit time is before start time, it's out of hours
-    if commit_time < start_time {
-        return false;
+impl HistoryFilter {
+    /// Returns the filter of a repository: its own pathspecs from the config,
+    /// when it has some, replace the `--pathspec` ones.
+    pub fn for_repo(&self, pathspecs: &[String]) -> HistoryFilter {
+        if pathspecs.is_empty() {
+            return self.clone();
+        }
+        HistoryFilter {
+            pathspecs: pathspecs.to_vec(),
+            ..self.clone()
+        }
     }
 
-    // If commit time is after end time, it's out of hours
-    if commit_time > end_time {
-        return false;
+    /// Returns the `git log` options selecting the commits.
+    fn git_args(&self) -> Vec<&'static str> {
+        let mut args = match self.merges {
+            MergeFilter::Include => vec![],
+            MergeFilter::Exclude => vec!["--no-merges"],
+            MergeFilter::Only => vec!["--merges"],
+        };
+        if self.first_parent {
+            // Merges then show the changes of the whole merged branch in --numstat
+            args.push("--first-parent");
+        }
+        args
     }
+}
 
-    // Otherwise it's within working hours
-    true
+/// A commit read from the history, with the lines it changed.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LoggedCommit {
+    /// Author of the commit, as `Name <email>`
+    pub author: String,
+    /// The commit line: hash, date and subject
+    pub line: String,
+    /// Unix timestamp of the committer date, which periods are filtered on
+    pub committed: i64,
+    /// Lines added by the commit
+    pub lines_added: usize,
+    /// Lines removed by the commit
+    pub lines_removed: usize,
+    /// Paths of the files changed by the commit
+    pub paths: Vec<String>,
+    /// Whether the commit has more than one parent
+    pub merge: bool,
 }
 
-/// Creates a mapping between original repository paths from the config file
-/// and their cleaned versions.
+/// Parses the output of `git log --numstat`, dropping the commits of excluded authors.
 ///
 /// # Arguments
-/// * `config` - The configuration structure
+/// * `output` - Output of `git log`, every commit line starting with its author
+/// * `authors` - Authors whose commits are counted
+///
+/// # Returns
+/// * `Vec<LoggedCommit>` - The counted commits with their added and removed lines
+fn parse_log(output: &str, authors: &AuthorFilter) -> Vec<LoggedCommit> {
+    let mut commits: Vec<LoggedCommit> = Vec::new();
+    let mut excluded = false;
+
+    for line in output.lines() {
+        match split_author(line) {
+            Some((author, commit)) => {
+                excluded = authors.is_excluded(author);
+                if excluded {
+                    debug(&format!(
+                        "Skipping commit of excluded author {}: {}",
+                        author, commit
+                    ));
+                    continue;
+                }
+                let (parents, commit) =
+                    commit.split_once(PARENTS_SEPARATOR).unwrap_or(("", commit));
+                let (committed, commit) =
+                    commit.split_once(PARENTS_SEPARATOR).unwrap_or(("", commit));
+                commits.push(LoggedCommit {
+                    author: author.to_string(),
+                    line: commit.to_string(),
+                    committed: committed.parse().unwrap_or_default(),
+                    merge: parents.split_whitespace().count() > 1,
+                    lines_added: 0,
+                    lines_removed: 0,
+                    paths: Vec::new(),
+                });
+            }
+            // A `--numstat` line of the previous commit: added, removed and path
+            None => {
+                let Some(commit) = commits.last_mut().filter(|_| !excluded) else {
+                    continue;
+                };
+                let mut fields = line.splitn(3, '\t');
+                if let (Some(added), Some(removed), Some(path)) =
+                    (fields.next(), fields.next(), fields.next())
+                {
+                    // Binary files are listed with `-` instead of line counts
+                    commit.lines_added += added.parse::<usize>().unwrap_or(0);
+                    commit.lines_removed += removed.parse::<usize>().unwrap_or(0);
+                    commit.paths.push(renamed_path(path));
+                }
+            }
+        }
+    }
+
+    commits
+}
+
+/// Returns the path a `--numstat` entry ends up at, resolving renames written as
+/// `old => new` or `dir/{old => new}/file`.
+fn renamed_path(path: &str) -> String {
+    match (path.find('{'), path.find(" => "), path.find('}')) {
+        (Some(open), Some(arrow), Some(close)) if open < arrow && arrow < close => {
+            let new = format!(
+                "{}{}{}",
+                &path[..open],
+                &path[arrow + 4..close],
+                &path[close + 1..]
+            );
+            // `dir/{ => sub}/file` leaves a doubled slash behind
+            new.replace("//", "/")
+        }
+        (_, Some(arrow), _) => path[arrow + 4..].to_string(),
+        _ => path.to_string(),
+    }
+}
+
+/// Groups commit timestamps into work sessions: a commit less than `max_gap`
+/// minutes after the previous one continues its session. As the work before
+/// the first commit of a session is not recorded, each session starts half a
+/// gap before it.
+///
+/// # Arguments
+/// * `timestamps` - The commit timestamps, sorted in place
+/// * `max_gap` - Maximum gap in minutes between two commits of the same session
 ///
 /// # Returns
-/// * `HashMap<String, String>` - Map of original paths to cleaned paths
-pub fn create_repo_path_map(config: &Config) -> HashMap<String, String> {
-    let mut repo_path_map: HashMap<String, String> = HashMap::new();
-
-    for (_, repos) in &config.repos {
-        for repo_str in repos {
-            let repo_info = parse_repo_string(repo_str);
-            repo_path_map.insert(repo_str.clone(), repo_info.path);
+/// * `Vec<(NaiveDateTime, NaiveDateTime)>` - Start and end of every session
+pub fn work_sessions(
+    timestamps: &mut [NaiveDateTime],
+    max_gap: i64,
+) -> Vec<(NaiveDateTime, NaiveDateTime)> {
+    timestamps.sort();
+
+    let lead_in = Duration::minutes(max_gap / 2);
+    let mut sessions: Vec<(NaiveDateTime, NaiveDateTime)> = Vec::new();
+
+    for timestamp in timestamps.iter() {
+        match sessions.last_mut() {
+            Some((_, end)) if *timestamp - *end <= Duration::minutes(max_gap) => {
+                *end = *timestamp;
+            }
+            _ => sessions.push((*timestamp - lead_in, *timestamp)),
         }
     }
 
-    repo_path_map
+    sessions
+}
+
+/// Estimates the minutes worked every day out of the commits, grouped into
+/// work sessions like for the hours gitnapped, a session counting for the
+/// day it starts on in the timezone of the machine.
+///
+/// # Arguments
+/// * `commit_times` - Unix timestamps of the commits, in any order
+/// * `max_gap` - Maximum gap in minutes between two commits of the same session
+///
+/// # Returns
+/// * `BTreeMap<NaiveDate, usize>` - The minutes of every day with a commit
+pub fn session_minutes_by_day(commit_times: &[i64], max_gap: i64) -> BTreeMap<NaiveDate, usize> {
+    let mut timestamps: Vec<NaiveDateTime> = commit_times
+        .iter()
+        .filter_map(|timestamp| DateTime::from_timestamp(*timestamp, 0))
+        .map(|timestamp| timestamp.with_timezone(&Local).naive_local())
+        .collect();
+
+    let mut minutes: BTreeMap<NaiveDate, usize> = BTreeMap::new();
+    for (start, end) in work_sessions(&mut timestamps, max_gap) {
+        *minutes.entry(start.date()).or_default() += (end - start).num_minutes() as usize;
+    }
+    minutes
 }
 
 /// Analyzes all categories defined in the configuration and returns their statistics.
 ///
+/// A repository listed in several categories is analyzed once: every category
+/// counts it, while the statistics of all repositories only hold it once.
+///
 /// # Arguments
 /// * `config` - The configuration structure
-/// * `repo_path_map` - Mapping of repository paths
-/// * `author_filter` - Optional author name to filter commits
+/// * `author_filter` - Authors whose commits are counted
 /// * `since` - Start date for analysis (YYYY-MM-DD format)
 /// * `until` - End date for analysis (YYYY-MM-DD format)
 /// * `active_only` - Whether to include only repositories with commits
 /// * `show_repo_details` - Whether to show detailed repository information
-/// * `show_filetypes` - Whether to analyze and show file type statistics
-/// * `working_hours` - Optional working hours to filter out-of-hours commits
+/// * `filetypes` - How to order the file type statistics, None not to show them
+/// * `files` - Which files are counted for the file and line statistics, if any
+/// * `history` - Which part of the history is analyzed
+/// * `working_hours` - Optional schedules to filter out-of-hours commits, looked up per repository and author
+/// * `session_gap` - Maximum gap in minutes between two commits of the same work session
+/// * `test_patterns` - Path patterns of the test files
+/// * `cache` - Optional cache the statistics are reused from and saved to
+/// * `on_repo_analyzed` - Called with each repository's statistics as soon as it is analyzed
 ///
 /// # Returns
 /// * `(Vec<CategoryStats>, Vec<(String, RepoStats)>)` - Tuple containing:
 ///   - Vector of category statistics
 ///   - Vector of all repository statistics
+#[allow(clippy::too_many_arguments)]
 pub fn analyze_all_categories(
     config: &Config,
-    repo_path_map: &HashMap<String, String>,
-    author_filter: &Option<String>,
+    author_filter: &AuthorFilter,
     since: &str,
     until: &str,
     active_only: bool,
     show_repo_details: bool,
-    show_filetypes: bool,
-    working_hours: Option<(u32, u32, u32, u32)>,
+    filetypes: Option<FileTypeOrder>,
+    files: FileScope,
+    history: &HistoryFilter,
+    working_hours: Option<&Schedules>,
+    session_gap: i64,
+    test_patterns: &TestPatterns,
+    cache: Option<&ResultCache>,
+    on_repo_analyzed: &mut dyn FnMut(&str, &RepoStats),
 ) -> (Vec<CategoryStats>, Vec<(String, RepoStats)>) {
     let mut categories = Vec::new();
     let mut all_repo_stats = Vec::new();
+    // Statistics of the repositories already analyzed, by canonical key
+    let mut analyzed: HashMap<String, RepoStats> = HashMap::new();
 
     for (category_name, repos) in &config.repos {
         let mut category_stats = CategoryStats {
@@ -428,20 +1065,45 @@ pub fn analyze_all_categories(
 
         let mut category_repo_stats = Vec::new();
 
-        for repo_str in repos {
-            // Get the parsed path for this repository
-            let repo_path = repo_path_map.get(repo_str).unwrap_or(repo_str);
+        for entry in repos {
+            let repo_info = parse_repo_entry(entry);
+            let repo_path = &repo_info.path;
+            let repo_history = history.for_repo(&repo_info.pathspecs);
+            // Parts of a monorepo share its path, so they are told apart by their key
+            let repo_key = repo_info.key();
+            let canonical_key = repo_info.canonical_key();
 
-            // Check if we've already analyzed this repo
-            let repo_stats = analyze_repo(
-                repo_path,
-                author_filter,
-                since,
-                until,
-                show_repo_details,
-                show_filetypes,
-                working_hours,
-            );
+            let (repo_stats, first_listing) = match analyzed.get(&canonical_key) {
+                Some(stats) => {
+                    debug(&format!(
+                        "Repository {} already analyzed in another category",
+                        repo_key
+                    ));
+                    (stats.clone(), false)
+                }
+                None => {
+                    progress::analyzing(repo_path);
+                    let stats = timing::time(repo_path, Phase::Total, || {
+                        analyze_repo(
+                            repo_path,
+                            author_filter,
+                            since,
+                            until,
+                            show_repo_details,
+                            filetypes,
+                            files,
+                            &repo_history,
+                            working_hours,
+                            session_gap,
+                            test_patterns,
+                            cache,
+                        )
+                    });
+                    progress::analyzed();
+                    analyzed.insert(canonical_key, stats.clone());
+                    (stats, true)
+                }
+            };
 
             // Skip inactive repositories if active-only flag is set
             if active_only && !is_repo_active(&repo_stats) {
@@ -450,9 +1112,14 @@ pub fn analyze_all_categories(
 
             category_stats
                 .repos
-                .push((repo_path.clone(), repo_stats.clone()));
+                .push((repo_key.clone(), repo_stats.clone()));
             category_repo_stats.push(repo_stats.clone());
-            all_repo_stats.push((repo_path.clone(), repo_stats));
+
+            // Totals count every repository once, whatever the categories listing it
+            if first_listing {
+                on_repo_analyzed(&repo_key, &repo_stats);
+                all_repo_stats.push((repo_key, repo_stats));
+            }
         }
 
         // Aggregate statistics for this category
@@ -470,33 +1137,57 @@ pub fn analyze_all_categories(
 
 /// Analyzes all projects by grouping repositories with the same vanity name.
 ///
+/// A repository already analyzed, under any of the ways its path is written,
+/// is not analyzed again, and is counted once in a project listing it twice.
+///
 /// # Arguments
 /// * `repo_infos` - Vector of repository information
 /// * `repo_stats_map` - Map of repository paths to their statistics
-/// * `author_filter` - Optional author name to filter commits
+/// * `author_filter` - Authors whose commits are counted
 /// * `since` - Start date for analysis (YYYY-MM-DD format)
 /// * `until` - End date for analysis (YYYY-MM-DD format)
 /// * `active_only` - Whether to include only repositories with commits
 /// * `show_repo_details` - Whether to show detailed repository information
-/// * `show_filetypes` - Whether to analyze and show file type statistics
-/// * `working_hours` - Optional working hours to filter out-of-hours commits
+/// * `filetypes` - How to order the file type statistics, None not to show them
+/// * `files` - Which files are counted for the file and line statistics, if any
+/// * `history` - Which part of the history is analyzed
+/// * `working_hours` - Optional schedules to filter out-of-hours commits, looked up per repository and author
+/// * `session_gap` - Maximum gap in minutes between two commits of the same work session
+/// * `test_patterns` - Path patterns of the test files
+/// * `cache` - Optional cache the statistics are reused from and saved to
 ///
 /// # Returns
 /// * `Vec<ProjectStats>` - Vector of project statistics
+#[allow(clippy::too_many_arguments)]
 pub fn analyze_all_projects(
     repo_infos: &[RepoInfo],
     repo_stats_map: &HashMap<String, RepoStats>,
-    author_filter: &Option<String>,
+    author_filter: &AuthorFilter,
     since: &str,
     until: &str,
     active_only: bool,
     show_repo_details: bool,
-    show_filetypes: bool,
-    working_hours: Option<(u32, u32, u32, u32)>,
+    filetypes: Option<FileTypeOrder>,
+    files: FileScope,
+    history: &HistoryFilter,
+    working_hours: Option<&Schedules>,
+    session_gap: i64,
+    test_patterns: &TestPatterns,
+    cache: Option<&ResultCache>,
 ) -> Vec<ProjectStats> {
     let grouped_repos = group_repos_by_vanity(repo_infos);
     let mut project_list = Vec::new();
 
+    // Statistics of the repositories already analyzed, by canonical key
+    let mut analyzed: HashMap<String, RepoStats> = repo_infos
+        .iter()
+        .filter_map(|repo_info| {
+            repo_stats_map
+                .get(&repo_info.key())
+                .map(|stats| (repo_info.canonical_key(), stats.clone()))
+        })
+        .collect();
+
     for (vanity_name, repo_group) in grouped_repos {
         debug(&format!("\nProcessing project: {}", vanity_name));
         let mut project_stats = ProjectStats {
@@ -508,24 +1199,45 @@ pub fn analyze_all_projects(
 
         let mut project_repo_stats = Vec::new();
         let mut active_repos_in_project = 0;
+        let mut project_repos = HashSet::new();
 
         for repo_info in repo_group {
             let repo_path = &repo_info.path;
-            project_stats.repos.push(repo_path.clone());
+            let repo_key = repo_info.key();
+            let canonical_key = repo_info.canonical_key();
+            if !project_repos.insert(canonical_key.clone()) {
+                debug(&format!(
+                    "  Repository {} listed twice, counted once",
+                    repo_key
+                ));
+                continue;
+            }
+            project_stats.repos.push(repo_key.clone());
 
             // Use already calculated statistics for this repo or analyze it
-            let repo_stats = if let Some(stats) = repo_stats_map.get(repo_path) {
+            let repo_stats = if let Some(stats) = analyzed.get(&canonical_key) {
                 stats.clone()
             } else {
-                analyze_repo(
-                    repo_path,
-                    author_filter,
-                    since,
-                    until,
-                    show_repo_details,
-                    show_filetypes,
-                    working_hours,
-                )
+                progress::analyzing(repo_path);
+                let stats = timing::time(repo_path, Phase::Total, || {
+                    analyze_repo(
+                        repo_path,
+                        author_filter,
+                        since,
+                        until,
+                        show_repo_details,
+                        filetypes,
+                        files,
+                        &history.for_repo(&repo_info.pathspecs),
+                        working_hours,
+                        session_gap,
+                        test_patterns,
+                        cache,
+                    )
+                });
+                progress::analyzed();
+                analyzed.insert(canonical_key, stats.clone());
+                stats
             };
 
             debug(&format!(
@@ -557,3 +1269,147 @@ pub fn analyze_all_projects(
 
     project_list
 }
+
+/// Maximum gap in minutes between two commits of a work session, as `--session-gap` defaults to.
+const SESSION_GAP: i64 = 45;
+
+/// Hour before which a start after a late night is a short sleep, as `--short-sleep-before` defaults to.
+const SHORT_SLEEP_BEFORE: u32 = 9;
+
+/// Minimum length in days of an inactivity gap, as `--min-gap` defaults to.
+const MIN_GAP_DAYS: usize = 7;
+
+/// Analyzes the repositories of a config the way `gitnapped --json` does
+/// with its default options, for tools embedding gitnapped.
+pub struct Analyzer {
+    /// The config of the repositories, authors and working hours
+    config: Config,
+    /// Which files of the repositories are counted
+    files: FileScope,
+    /// Whether the statistics of unchanged repositories are reused across runs
+    cached: bool,
+    /// Whether the commits of every repository are kept between runs
+    incremental: bool,
+}
+
+impl Analyzer {
+    /// Builds an analyzer of the repositories of a config, e.g. read with `load_config`.
+    pub fn new(config: Config) -> Analyzer {
+        Analyzer {
+            config,
+            files: FileScope::All,
+            cached: false,
+            incremental: false,
+        }
+    }
+
+    /// Leaves the files and lines of the repositories out of the report, for
+    /// a quicker analysis of the commits alone.
+    pub fn without_files(mut self) -> Analyzer {
+        self.files = FileScope::Skip;
+        self
+    }
+
+    /// Reuses the statistics of the repositories unchanged since a previous
+    /// run with the same options, like `gitnapped` does unless `--no-cache`.
+    pub fn cached(mut self) -> Analyzer {
+        self.cached = true;
+        self
+    }
+
+    /// Keeps the commits of every repository between runs, so only the new
+    /// ones are read, like `--incremental`.
+    pub fn incremental(mut self) -> Analyzer {
+        self.incremental = true;
+        self
+    }
+
+    /// Analyzes the commits of the authors of the config over a range of days
+    /// against its working hours, along with the files and lines of every
+    /// repository unless left out, its categories and its projects.
+    ///
+    /// # Arguments
+    /// * `range` - The first and last days analyzed, both included
+    ///
+    /// # Returns
+    /// * `Result<Report, String>` - The report, or an error naming the invalid setting of the config
+    pub fn run(&self, range: RangeInclusive<NaiveDate>) -> Result<Report, String> {
+        let since = format!("{} 00:00:00", range.start().format("%Y-%m-%d"));
+        let until = format!("{} 23:59:59", range.end().format("%Y-%m-%d"));
+        let authors = self.config.authors();
+        let author_filter = AuthorFilter::new(authors.clone(), &self.config.exclude_authors)?;
+        let schedules = schedules_from_config(&self.config)?;
+        let test_patterns = match &self.config.test_patterns {
+            Some(patterns) => TestPatterns::new(patterns)?,
+            None => TestPatterns::default(),
+        };
+        // Like --json, which shows the lines changed along with the files
+        let history = HistoryFilter {
+            incremental: self.incremental,
+            changes: self.files != FileScope::Skip,
+            forges: self.config.forges.clone(),
+            ..HistoryFilter::default()
+        };
+        let cache = if self.cached {
+            ResultCache::open()
+        } else {
+            None
+        };
+        update_clones(&self.config);
+
+        let (categories, repo_stats) = analyze_all_categories(
+            &self.config,
+            &author_filter,
+            &since,
+            &until,
+            false,
+            false,
+            None,
+            self.files,
+            &history,
+            Some(&schedules),
+            SESSION_GAP,
+            &test_patterns,
+            cache.as_ref(),
+            &mut |_, _| {},
+        );
+        let repo_stats_map: HashMap<String, RepoStats> = repo_stats.iter().cloned().collect();
+        let repo_infos = parse_repos_from_config(&self.config);
+        let projects = analyze_all_projects(
+            &repo_infos,
+            &repo_stats_map,
+            &author_filter,
+            &since,
+            &until,
+            false,
+            false,
+            None,
+            self.files,
+            &history,
+            Some(&schedules),
+            SESSION_GAP,
+            &test_patterns,
+            cache.as_ref(),
+        );
+        let total_stats = aggregate_stats(
+            &repo_stats
+                .iter()
+                .map(|(_, stats)| stats.clone())
+                .collect::<Vec<RepoStats>>(),
+        );
+
+        Ok(build_report(
+            &since,
+            &until,
+            &authors,
+            &schedules.global.to_string(),
+            &total_stats,
+            &repo_stats,
+            &repo_infos,
+            &categories,
+            &projects,
+            SHORT_SLEEP_BEFORE,
+            MIN_GAP_DAYS,
+        ))
+    }
+}
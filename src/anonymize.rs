@@ -0,0 +1,168 @@
+use crate::models::{AuthorStats, CategoryStats, ProjectStats, RepoInfo, RepoStats};
+use std::collections::{BTreeMap, HashMap};
+
+/// What `--anonymize` hides from the output.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Anonymize {
+    /// Author names and emails
+    Authors,
+    /// Author names and emails, and repository paths
+    All,
+}
+
+impl Anonymize {
+    /// Names accepted by `--anonymize`.
+    pub const NAMES: [&'static str; 2] = ["authors", "all"];
+
+    /// Parses a scope name as accepted by `--anonymize`.
+    pub fn from_name(name: &str) -> Option<Anonymize> {
+        match name {
+            "authors" => Some(Anonymize::Authors),
+            "all" => Some(Anonymize::All),
+            _ => None,
+        }
+    }
+
+    /// Checks if repository paths are hidden too.
+    pub fn paths(&self) -> bool {
+        *self == Anonymize::All
+    }
+}
+
+/// Hashes text with 64-bit FNV-1a, which unlike the std hasher is stable
+/// across Rust versions, so pseudonyms stay the same from one run to the next.
+fn fnv1a(text: &str) -> u64 {
+    text.bytes().fold(0xcbf29ce484222325, |hash, byte| {
+        (hash ^ byte as u64).wrapping_mul(0x100000001b3)
+    })
+}
+
+/// Returns the stable pseudonym of an author.
+///
+/// Authors are identified by their email when there is one, so an author
+/// pattern like `jane@example.com` gets the same pseudonym as `Jane <jane@example.com>`.
+///
+/// # Arguments
+/// * `author` - The author, as `Name <email>`, a name or an email
+///
+/// # Returns
+/// * `String` - The pseudonym, e.g. `author-3f9a1c`
+pub fn author_pseudonym(author: &str) -> String {
+    let identity = author
+        .rsplit_once('<')
+        .and_then(|(_, email)| email.strip_suffix('>'))
+        .unwrap_or(author)
+        .trim()
+        .to_lowercase();
+    format!("author-{:06x}", fnv1a(&identity) & 0xffffff)
+}
+
+/// Returns the stable pseudonym of a repository path, e.g. `repo-5be2d0`.
+pub fn path_pseudonym(path: &str) -> String {
+    format!("repo-{:06x}", fnv1a(path) & 0xffffff)
+}
+
+/// Returns the stable pseudonym of a project, e.g. `project-0c41e7`, which
+/// hides the repositories it is named after.
+pub fn project_pseudonym(project: &str) -> String {
+    format!("project-{:06x}", fnv1a(project) & 0xffffff)
+}
+
+/// Replaces the names of projects with their pseudonyms when repository paths
+/// are hidden, as projects are named after their repositories, or after their
+/// paths when the config gives them no name.
+///
+/// # Arguments
+/// * `scope` - What to hide
+/// * `names` - The project names, e.g. of the projects of a timesheet
+pub fn anonymize_projects<'a>(scope: Anonymize, names: impl IntoIterator<Item = &'a mut String>) {
+    if scope.paths() {
+        for name in names {
+            *name = project_pseudonym(name);
+        }
+    }
+}
+
+/// Replaces the authors of the statistics with their pseudonyms.
+pub fn anonymize_stats(stats: &mut RepoStats) {
+    let authors = std::mem::take(&mut stats.authors);
+    let mut anonymized = BTreeMap::new();
+    for (author, author_stats) in authors {
+        let entry: &mut AuthorStats = anonymized.entry(author_pseudonym(&author)).or_default();
+        entry.commit_count += author_stats.commit_count;
+        entry.out_of_hours_commits += author_stats.out_of_hours_commits;
+        entry.out_of_hours_minutes += author_stats.out_of_hours_minutes;
+    }
+    stats.authors = anonymized;
+}
+
+/// Replaces the authors, and the repository paths along with the project
+/// names when asked to, of the whole analysis.
+///
+/// # Arguments
+/// * `scope` - What to hide
+/// * `repos` - Statistics of every analyzed repository
+/// * `repo_stats` - Statistics of every analyzed repository, by path
+/// * `repo_infos` - Repository information parsed from the config
+/// * `categories` - Category statistics
+/// * `projects` - Optional project statistics
+/// * `project_names` - Names of projects found elsewhere, e.g. next to tickets
+pub fn anonymize_analysis<'a>(
+    scope: Anonymize,
+    repos: &mut [(String, RepoStats)],
+    repo_stats: &mut HashMap<String, RepoStats>,
+    repo_infos: &mut [RepoInfo],
+    categories: &mut [CategoryStats],
+    projects: Option<&mut Vec<ProjectStats>>,
+    project_names: impl IntoIterator<Item = &'a mut String>,
+) {
+    let path = |path: &mut String| {
+        if scope.paths() {
+            *path = path_pseudonym(path);
+        }
+    };
+
+    for (repo, stats) in repos.iter_mut() {
+        path(repo);
+        anonymize_stats(stats);
+    }
+
+    *repo_stats = std::mem::take(repo_stats)
+        .into_iter()
+        .map(|(mut repo, mut stats)| {
+            path(&mut repo);
+            anonymize_stats(&mut stats);
+            (repo, stats)
+        })
+        .collect();
+
+    for info in repo_infos.iter_mut() {
+        // Keep the info matching the statistics, which are stored under its key
+        if scope.paths() {
+            info.path = path_pseudonym(&info.key());
+            info.pathspecs.clear();
+        }
+    }
+    // The name of a repository is the one of its project
+    anonymize_projects(
+        scope,
+        repo_infos.iter_mut().map(|info| &mut info.vanity_name),
+    );
+    anonymize_projects(scope, project_names);
+
+    for category in categories.iter_mut() {
+        for (repo, stats) in category.repos.iter_mut() {
+            path(repo);
+            anonymize_stats(stats);
+        }
+        anonymize_stats(&mut category.total);
+    }
+
+    for project in projects.into_iter().flatten() {
+        anonymize_projects(scope, [&mut project.name]);
+        for repo in project.repos.iter_mut() {
+            path(repo);
+        }
+        anonymize_stats(&mut project.stats);
+    }
+}
@@ -0,0 +1,122 @@
+use regex::{Regex, RegexBuilder};
+
+/// Separator between the author and the rest of a `git log` line, as produced by
+/// `DateSource::identity_format`.
+const AUTHOR_SEPARATOR: char = '\u{1f}';
+
+/// Whose name and date are read from every commit, as chosen by `--date-source`.
+///
+/// Rebased or cherry-picked commits keep their original author date, while the
+/// committer date tells when they were actually applied.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum DateSource {
+    /// The author of the commit and the date it was written
+    #[default]
+    Author,
+    /// The committer of the commit and the date it was committed
+    Committer,
+}
+
+impl DateSource {
+    /// Names accepted by `--date-source`.
+    pub const NAMES: [&'static str; 2] = ["author", "committer"];
+
+    /// Parses a date source name as accepted by `--date-source`.
+    pub fn from_name(name: &str) -> Option<DateSource> {
+        match name {
+            "author" => Some(DateSource::Author),
+            "committer" => Some(DateSource::Committer),
+            _ => None,
+        }
+    }
+
+    /// Format prepended to every `git log` line so the identity of each commit is known.
+    pub fn identity_format(&self) -> &'static str {
+        match self {
+            DateSource::Author => "%aN <%aE>%x1f",
+            DateSource::Committer => "%cN <%cE>%x1f",
+        }
+    }
+
+    /// `git log` placeholder of the commit date.
+    pub fn date_placeholder(&self) -> &'static str {
+        match self {
+            DateSource::Author => "%ad",
+            DateSource::Committer => "%cd",
+        }
+    }
+
+    /// `git log` option filtering commits by identity, e.g. `--author=jane`.
+    pub fn filter_arg(&self, pattern: &str) -> String {
+        match self {
+            DateSource::Author => format!("--author={}", pattern),
+            DateSource::Committer => format!("--committer={}", pattern),
+        }
+    }
+}
+
+/// Which commits are counted, based on their author.
+#[derive(Debug, Clone, Default)]
+pub struct AuthorFilter {
+    /// Author patterns passed to `git log --author`, any of them matching; empty for all authors
+    pub include: Vec<String>,
+    /// Case-insensitive patterns of authors whose commits are ignored, e.g. bots
+    pub exclude: Vec<Regex>,
+    /// Whether authors or committers are matched, along with their dates
+    pub source: DateSource,
+}
+
+impl AuthorFilter {
+    /// Builds an author filter.
+    ///
+    /// # Arguments
+    /// * `include` - Author patterns to keep, empty for all authors
+    /// * `exclude` - Regular expressions matched against `Name <email>` of the authors to ignore
+    ///
+    /// # Returns
+    /// * `Result<AuthorFilter, String>` - The filter, or an error naming the invalid pattern
+    pub fn new(include: Vec<String>, exclude: &[String]) -> Result<AuthorFilter, String> {
+        let exclude = exclude
+            .iter()
+            .map(|pattern| {
+                RegexBuilder::new(pattern)
+                    .case_insensitive(true)
+                    .build()
+                    .map_err(|err| format!("Invalid exclude-author pattern '{}': {}", pattern, err))
+            })
+            .collect::<Result<Vec<Regex>, String>>()?;
+
+        Ok(AuthorFilter {
+            include,
+            exclude,
+            source: DateSource::default(),
+        })
+    }
+
+    /// Checks if the commits of an author (`Name <email>`) are ignored.
+    pub fn is_excluded(&self, author: &str) -> bool {
+        self.exclude.iter().any(|pattern| pattern.is_match(author))
+    }
+
+    /// Checks if an author (`Name <email>`) matches the author patterns, as
+    /// `git log --author` does, for the commits not read through `git log`.
+    pub fn is_included(&self, author: &str) -> bool {
+        self.include.is_empty()
+            || self
+                .include
+                .iter()
+                .any(|pattern| match Regex::new(pattern) {
+                    Ok(regex) => regex.is_match(author),
+                    Err(_) => author.contains(pattern.as_str()),
+                })
+    }
+}
+
+/// Splits a `git log` line produced with `DateSource::identity_format` into its author and the rest of the line.
+///
+/// # Returns
+/// * `Option<(&str, &str)>` - The author (`Name <email>`) and the commit line, None for
+///   lines that are not commits (e.g. `--numstat` lines)
+pub fn split_author(line: &str) -> Option<(&str, &str)> {
+    line.split_once(AUTHOR_SEPARATOR)
+}
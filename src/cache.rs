@@ -0,0 +1,221 @@
+use crate::analyzer::Backend;
+#[cfg(feature = "libgit2")]
+use crate::libgit2;
+use crate::models::RepoStats;
+use crate::utils::{bound_timestamp, debug, key_file_name, user_dir};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::PathBuf;
+use std::process::Command;
+use std::time::{Duration, SystemTime};
+
+/// Entries not used for this long are removed when the cache is opened.
+const MAX_AGE: Duration = Duration::from_secs(30 * 24 * 60 * 60);
+
+/// Statistics of a repository as saved in the cache, with the fields the
+/// reports leave out.
+#[derive(Serialize, Deserialize)]
+struct CacheEntry {
+    /// The full key, to tell apart keys with the same hash
+    key: String,
+    stats: RepoStats,
+    files_counted: bool,
+    commit_times: Vec<i64>,
+}
+
+/// Statistics of repositories saved on disk, so that running again over the
+/// same history with the same options, e.g. to try other display flags, does
+/// not analyze the repositories again.
+#[derive(Debug)]
+pub struct ResultCache {
+    /// Directory holding one file per entry
+    dir: PathBuf,
+}
+
+impl ResultCache {
+    /// Opens the cache in `$XDG_CACHE_HOME/gitnapped`, `~/.cache/gitnapped` when
+    /// it is not set, and removes the entries that have not been used for a month.
+    ///
+    /// # Returns
+    /// * `Option<ResultCache>` - The cache, None if there is no home directory to put it in
+    pub fn open() -> Option<ResultCache> {
+        let cache = ResultCache {
+            dir: user_dir("XDG_CACHE_HOME", ".cache")?,
+        };
+        cache.prune();
+        Some(cache)
+    }
+
+    /// Builds the key of the statistics of a repository: the commit its HEAD
+    /// points to, so that any new commit misses the cache, the end of the
+    /// period and the options of the analysis. Repositories with uncommitted
+    /// changes have no key, as their files may change without git telling.
+    ///
+    /// A period ending after HEAD was committed holds the same commits however
+    /// late it ends, so its end is left out of the key: the periods ending now,
+    /// e.g. of `--today`, hit the cache until a new commit is made.
+    ///
+    /// # Arguments
+    /// * `repo` - Path to the Git repository
+    /// * `backend` - How the state of the repository is read
+    /// * `until` - End of the analyzed period
+    /// * `options` - Everything else the statistics depend on, e.g. the start of the period and the authors
+    ///
+    /// # Returns
+    /// * `Option<String>` - The key, None if the repository has uncommitted changes or
+    ///   its state could not be read
+    pub fn key(&self, repo: &str, backend: Backend, until: &str, options: &str) -> Option<String> {
+        let head = match backend {
+            Backend::Git => git_head(repo),
+            #[cfg(feature = "libgit2")]
+            Backend::Libgit2 => libgit2::clean_head(repo)
+                .map_err(|err| debug(&format!("Error reading the state of {}: {}", repo, err)))
+                .ok()
+                .flatten(),
+        }?;
+        let head_time = match backend {
+            Backend::Git => git_commit_time(repo, &head),
+            #[cfg(feature = "libgit2")]
+            Backend::Libgit2 => libgit2::commit_time(repo, &head).ok(),
+        };
+        let until = match (bound_timestamp(until), head_time) {
+            (Some(until), Some(head_time)) if until >= head_time => "after HEAD",
+            _ => until,
+        };
+        let path = fs::canonicalize(repo)
+            .map(|path| path.to_string_lossy().into_owned())
+            .unwrap_or_else(|_| repo.to_string());
+
+        Some(format!(
+            "{} {}\n{}\n{}\n{}\n{}",
+            env!("CARGO_PKG_NAME"),
+            env!("CARGO_PKG_VERSION"),
+            path,
+            head,
+            until,
+            options
+        ))
+    }
+
+    /// Loads the statistics saved under a key.
+    ///
+    /// # Returns
+    /// * `Option<RepoStats>` - The statistics, None if they are not in the cache
+    pub fn load(&self, key: &str) -> Option<RepoStats> {
+        let path = self.entry_path(key);
+        let content = fs::read_to_string(&path).ok()?;
+        let entry: CacheEntry = match serde_json::from_str(&content) {
+            Ok(entry) => entry,
+            Err(err) => {
+                debug(&format!(
+                    "Ignoring unreadable cache entry {:?}: {}",
+                    path, err
+                ));
+                return None;
+            }
+        };
+        if entry.key != key {
+            return None;
+        }
+
+        // Keep the entry from being pruned while it is used
+        if let Ok(file) = fs::File::options().append(true).open(&path) {
+            let _ = file.set_modified(SystemTime::now());
+        }
+
+        Some(RepoStats {
+            files_counted: entry.files_counted,
+            commit_times: entry.commit_times,
+            ..entry.stats
+        })
+    }
+
+    /// Saves the statistics of a repository under a key. Failing to save them
+    /// only costs the next run the time to analyze the repository again.
+    pub fn store(&self, key: &str, stats: &RepoStats) {
+        let entry = CacheEntry {
+            key: key.to_string(),
+            stats: stats.clone(),
+            files_counted: stats.files_counted,
+            commit_times: stats.commit_times.clone(),
+        };
+        let result = fs::create_dir_all(&self.dir)
+            .map_err(|err| err.to_string())
+            .and_then(|_| serde_json::to_string(&entry).map_err(|err| err.to_string()))
+            .and_then(|json| fs::write(self.entry_path(key), json).map_err(|err| err.to_string()));
+        if let Err(err) = result {
+            debug(&format!(
+                "Failed to write the cache in {:?}: {}",
+                self.dir, err
+            ));
+        }
+    }
+
+    /// Returns the file an entry is saved in, named after the hash of its key.
+    fn entry_path(&self, key: &str) -> PathBuf {
+        self.dir.join(key_file_name(key))
+    }
+
+    /// Removes the entries that have not been used for `MAX_AGE`.
+    fn prune(&self) {
+        let Ok(entries) = fs::read_dir(&self.dir) else {
+            return;
+        };
+        for entry in entries.flatten() {
+            let expired = entry
+                .metadata()
+                .and_then(|metadata| metadata.modified())
+                .ok()
+                .and_then(|modified| modified.elapsed().ok())
+                .is_some_and(|age| age > MAX_AGE);
+            if expired {
+                let _ = fs::remove_file(entry.path());
+            }
+        }
+    }
+}
+
+/// Reads the commit HEAD points to with `git status`, which tells about
+/// uncommitted changes too.
+///
+/// # Returns
+/// * `Option<String>` - The commit, None if the repository has uncommitted changes
+fn git_head(repo: &str) -> Option<String> {
+    let output = Command::new("git")
+        .args([
+            "-C",
+            repo,
+            "status",
+            "--porcelain=v2",
+            "--branch",
+            "--untracked-files=no",
+        ])
+        .output()
+        .ok()?;
+    if !output.status.success() {
+        return None;
+    }
+
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    // Lines other than the `# branch.` headers are changes
+    if stdout.lines().any(|line| !line.starts_with('#')) {
+        debug(&format!("Not caching {}, it has uncommitted changes", repo));
+        return None;
+    }
+    stdout
+        .lines()
+        .find_map(|line| line.strip_prefix("# branch.oid "))
+        .map(|oid| oid.to_string())
+}
+
+/// Reads the committer date of a commit as a Unix timestamp.
+fn git_commit_time(repo: &str, commit: &str) -> Option<i64> {
+    let output = Command::new("git")
+        .args(["-C", repo, "log", "-1", "--format=%ct", commit])
+        .output()
+        .ok()?;
+    if !output.status.success() {
+        return None;
+    }
+    String::from_utf8_lossy(&output.stdout).trim().parse().ok()
+}
@@ -0,0 +1,228 @@
+use crate::report::Report;
+use chrono::{Duration, NaiveDate};
+use std::fs;
+use std::path::Path;
+
+const WIDTH: f64 = 800.0;
+const HEIGHT: f64 = 300.0;
+const MARGIN: f64 = 40.0;
+const COMMIT_COLOR: &str = "#0969da";
+const GITNAPPED_COLOR: &str = "#cf222e";
+
+/// Escapes text for use inside SVG elements and attributes.
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+/// Wraps chart elements into a standalone SVG document.
+fn svg_document(title: &str, height: f64, body: &str) -> String {
+    format!(
+        "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{w}\" height=\"{h}\" viewBox=\"0 0 {w} {h}\" font-family=\"Helvetica, Arial, sans-serif\" font-size=\"11\">\n\
+         <rect width=\"100%\" height=\"100%\" fill=\"#ffffff\"/>\n\
+         <text x=\"{m}\" y=\"22\" font-size=\"14\" font-weight=\"bold\" fill=\"#1f2328\">{title}</text>\n\
+         {body}</svg>\n",
+        w = WIDTH,
+        h = height,
+        m = MARGIN,
+        title = escape(title),
+        body = body
+    )
+}
+
+/// Renders the daily commits of the period as a stacked column chart,
+/// with out-of-hours commits on top of in-hours ones.
+///
+/// # Arguments
+/// * `report` - The report holding the daily commit counts
+///
+/// # Returns
+/// * `String` - The SVG document
+pub fn commits_over_time_svg(report: &Report) -> String {
+    let stats = &report.totals.stats;
+    let dates: Vec<NaiveDate> = stats
+        .commits_by_date
+        .keys()
+        .filter_map(|date| NaiveDate::parse_from_str(date, "%Y-%m-%d").ok())
+        .collect();
+    let title = format!("Commits from {} to {}", report.since, report.until);
+
+    let (first, last) = match (dates.first(), dates.last()) {
+        (Some(first), Some(last)) => (*first, *last),
+        _ => {
+            let body = format!(
+                "<text x=\"{}\" y=\"{}\" fill=\"#656d76\">No commits in this period</text>\n",
+                MARGIN,
+                HEIGHT / 2.0
+            );
+            return svg_document(&title, HEIGHT, &body);
+        }
+    };
+
+    let days = (last - first).num_days() as usize + 1;
+    let max_count = stats.commits_by_date.values().copied().max().unwrap_or(1) as f64;
+    let plot_width = WIDTH - MARGIN * 2.0;
+    let plot_height = HEIGHT - MARGIN * 2.0;
+    let step = plot_width / days as f64;
+    let bottom = HEIGHT - MARGIN;
+
+    let mut body = String::new();
+    for offset in 0..days {
+        let day = first + Duration::days(offset as i64);
+        let key = day.format("%Y-%m-%d").to_string();
+        let total = stats.commits_by_date.get(&key).copied().unwrap_or(0);
+        if total == 0 {
+            continue;
+        }
+        let out_of_hours = stats.out_of_hours_by_date.get(&key).copied().unwrap_or(0);
+        let x = MARGIN + offset as f64 * step;
+        let bar_width = (step - 1.0).max(1.0);
+        let total_height = total as f64 / max_count * plot_height;
+        let night_height = out_of_hours as f64 / max_count * plot_height;
+
+        body.push_str(&format!(
+            "<rect x=\"{:.1}\" y=\"{:.1}\" width=\"{:.1}\" height=\"{:.1}\" fill=\"{}\"><title>{}: {} commits, {} gitnapped</title></rect>\n",
+            x,
+            bottom - total_height,
+            bar_width,
+            total_height - night_height,
+            COMMIT_COLOR,
+            key,
+            total,
+            out_of_hours
+        ));
+        if out_of_hours > 0 {
+            body.push_str(&format!(
+                "<rect x=\"{:.1}\" y=\"{:.1}\" width=\"{:.1}\" height=\"{:.1}\" fill=\"{}\"/>\n",
+                x,
+                bottom - total_height,
+                bar_width,
+                night_height,
+                GITNAPPED_COLOR
+            ));
+        }
+    }
+
+    body.push_str(&format!(
+        "<line x1=\"{m}\" y1=\"{b}\" x2=\"{r}\" y2=\"{b}\" stroke=\"#d0d7de\"/>\n\
+         <text x=\"{m}\" y=\"{t}\" fill=\"#656d76\">{first}</text>\n\
+         <text x=\"{r}\" y=\"{t}\" fill=\"#656d76\" text-anchor=\"end\">{last}</text>\n\
+         <text x=\"{lm}\" y=\"{top}\" fill=\"#656d76\" text-anchor=\"end\">{max}</text>\n\
+         <rect x=\"{legend}\" y=\"12\" width=\"10\" height=\"10\" fill=\"{c}\"/><text x=\"{legend_text}\" y=\"21\" fill=\"#656d76\">in hours</text>\n\
+         <rect x=\"{legend2}\" y=\"12\" width=\"10\" height=\"10\" fill=\"{g}\"/><text x=\"{legend2_text}\" y=\"21\" fill=\"#656d76\">gitnapped</text>\n",
+        m = MARGIN,
+        b = bottom,
+        r = WIDTH - MARGIN,
+        t = bottom + 16.0,
+        lm = MARGIN - 4.0,
+        top = MARGIN + 4.0,
+        first = first.format("%Y-%m-%d"),
+        last = last.format("%Y-%m-%d"),
+        max = max_count,
+        legend = WIDTH - 180.0,
+        legend_text = WIDTH - 166.0,
+        legend2 = WIDTH - 100.0,
+        legend2_text = WIDTH - 86.0,
+        c = COMMIT_COLOR,
+        g = GITNAPPED_COLOR
+    ));
+
+    svg_document(&title, HEIGHT, &body)
+}
+
+/// Renders the gitnapped percentage of each active project as a horizontal bar chart.
+///
+/// # Arguments
+/// * `report` - The report holding the project statistics
+///
+/// # Returns
+/// * `String` - The SVG document
+pub fn gitnapped_percentage_svg(report: &Report) -> String {
+    let mut projects: Vec<(&str, f64, usize)> = report
+        .projects
+        .iter()
+        .filter(|project| project.stats.commit_count > 0)
+        .map(|project| {
+            (
+                project.name.as_str(),
+                project.gitnapped_percentage,
+                project.stats.out_of_hours_commits,
+            )
+        })
+        .collect();
+    projects.sort_by(|a, b| b.1.total_cmp(&a.1));
+
+    const ROW: f64 = 24.0;
+    const LABEL_WIDTH: f64 = 200.0;
+    let height = MARGIN * 2.0 + ROW * projects.len().max(1) as f64;
+    let title = format!(
+        "Gitnapped per project (overall {}%)",
+        report.totals.gitnapped_percentage
+    );
+
+    if projects.is_empty() {
+        let body = format!(
+            "<text x=\"{}\" y=\"{}\" fill=\"#656d76\">No commits in this period</text>\n",
+            MARGIN,
+            MARGIN + 16.0
+        );
+        return svg_document(&title, height, &body);
+    }
+
+    let bar_space = WIDTH - MARGIN * 2.0 - LABEL_WIDTH - 60.0;
+    let mut body = String::new();
+    for (i, (name, percentage, out_of_hours)) in projects.iter().enumerate() {
+        let y = MARGIN + i as f64 * ROW;
+        let bar_width = percentage / 100.0 * bar_space;
+        body.push_str(&format!(
+            "<text x=\"{lx:.1}\" y=\"{ty:.1}\" fill=\"#1f2328\" text-anchor=\"end\">{name}</text>\n\
+             <rect x=\"{bx:.1}\" y=\"{y:.1}\" width=\"{bar_space:.1}\" height=\"{h:.1}\" fill=\"#f6f8fa\"/>\n\
+             <rect x=\"{bx:.1}\" y=\"{y:.1}\" width=\"{bw:.1}\" height=\"{h:.1}\" fill=\"{g}\"><title>{name}: {out_of_hours} out-of-hours commits</title></rect>\n\
+             <text x=\"{vx:.1}\" y=\"{ty:.1}\" fill=\"#656d76\">{percentage}%</text>\n",
+            lx = MARGIN + LABEL_WIDTH - 8.0,
+            ty = y + ROW / 2.0 + 4.0,
+            name = escape(name),
+            bx = MARGIN + LABEL_WIDTH,
+            y = y + 3.0,
+            bar_space = bar_space,
+            h = ROW - 6.0,
+            bw = bar_width,
+            g = GITNAPPED_COLOR,
+            out_of_hours = out_of_hours,
+            vx = MARGIN + LABEL_WIDTH + bar_space + 6.0,
+            percentage = percentage
+        ));
+    }
+
+    svg_document(&title, height, &body)
+}
+
+/// Writes the SVG charts of the report into a directory.
+///
+/// # Arguments
+/// * `report` - The report to chart
+/// * `dir` - Destination directory, created if missing
+///
+/// # Returns
+/// * `Result<Vec<String>, String>` - Paths of the written files, or an error message
+pub fn write_svg_charts(report: &Report, dir: &str) -> Result<Vec<String>, String> {
+    fs::create_dir_all(dir)
+        .map_err(|err| format!("Failed to create chart directory '{}': {}", dir, err))?;
+
+    let charts = [
+        ("gitnapped-commits.svg", commits_over_time_svg(report)),
+        ("gitnapped-percentage.svg", gitnapped_percentage_svg(report)),
+    ];
+
+    let mut written = Vec::new();
+    for (file_name, svg) in charts {
+        let path = Path::new(dir).join(file_name);
+        fs::write(&path, svg)
+            .map_err(|err| format!("Failed to write chart '{}': {}", path.display(), err))?;
+        written.push(path.display().to_string());
+    }
+
+    Ok(written)
+}
@@ -0,0 +1,78 @@
+use crate::config::schedules_from_config;
+use crate::models::Config;
+use crate::schedule::Schedule;
+use crate::utils::debug;
+use chrono::{DateTime, Datelike, FixedOffset, NaiveDateTime, Timelike};
+use std::fs;
+use std::process::Command;
+
+/// Whether a commit made at some point in time would be within working hours.
+pub struct HoursCheck {
+    /// The schedule the commit is evaluated against
+    pub schedule: Schedule,
+    /// The wall clock time of the commit in the timezone of the schedule
+    pub time: NaiveDateTime,
+    /// Whether the time is within the working hours of the schedule
+    pub within_hours: bool,
+}
+
+/// Tells whether committing at a point in time, usually now, would be out of
+/// hours, for `gitnapped check` run from a Git hook. The commit is evaluated
+/// like the analysis does: against the schedule of the committer when they are
+/// a team member, else the one of the repository.
+///
+/// # Arguments
+/// * `config` - The config holding the working hours
+/// * `repo` - A directory of the repository committed to
+/// * `at` - When the commit is made
+///
+/// # Returns
+/// * `Result<HoursCheck, String>` - The check, or an error naming the invalid setting of the config
+pub fn check_commit_time(
+    config: &Config,
+    repo: &str,
+    at: DateTime<FixedOffset>,
+) -> Result<HoursCheck, String> {
+    let schedules = schedules_from_config(config)?;
+
+    // The schedules are stored by the paths written in the config
+    let toplevel =
+        git_output(repo, &["rev-parse", "--show-toplevel"]).unwrap_or_else(|| repo.to_string());
+    let canonical = fs::canonicalize(&toplevel).ok();
+    let path = schedules
+        .repos
+        .keys()
+        .find(|path| canonical.is_some() && fs::canonicalize(path).ok() == canonical)
+        .cloned()
+        .unwrap_or(toplevel);
+
+    // `Name <email> timestamp offset`, the timestamp left out
+    let author = git_output(repo, &["var", "GIT_AUTHOR_IDENT"])
+        .and_then(|ident| ident.rfind('>').map(|end| ident[..=end].to_string()))
+        .unwrap_or_default();
+    debug(&format!(
+        "Checking a commit of {} to {} at {}",
+        author, path, at
+    ));
+
+    let schedule = schedules.for_commit(&path, &author).clone();
+    let time = schedule.timezone.convert(&at);
+    let weekday = time.weekday().num_days_from_monday() as usize;
+    Ok(HoursCheck {
+        within_hours: schedule.contains(weekday, time.hour(), time.minute()),
+        schedule,
+        time,
+    })
+}
+
+/// Runs a git command in a repository, returning its trimmed output if it succeeds.
+pub(crate) fn git_output(repo: &str, args: &[&str]) -> Option<String> {
+    let output = Command::new("git")
+        .arg("-C")
+        .arg(repo)
+        .args(args)
+        .output()
+        .ok()?;
+    let value = String::from_utf8_lossy(&output.stdout).trim().to_string();
+    (output.status.success() && !value.is_empty()).then_some(value)
+}
@@ -0,0 +1,204 @@
+use crate::config::parse_repos_from_config;
+use crate::models::{Config, RepoInfo};
+use crate::remote::remote_prefix_length;
+use crate::utils::{debug, log, user_dir};
+use colored::*;
+use std::collections::{BTreeSet, HashSet};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+/// Schemes of the repositories of the config cloned into the cache rather
+/// than read from a path, e.g. `https://example.com/repo.git`.
+const CLONE_SCHEMES: [&str; 4] = ["https://", "http://", "ssh://", "git://"];
+
+/// Checks if a repository of the config is the URL of a repository to clone.
+pub fn is_clone_url(path: &str) -> bool {
+    CLONE_SCHEMES.iter().any(|scheme| path.starts_with(scheme))
+}
+
+/// Returns the length of the scheme and host of a clone URL, so the `:` of
+/// `https://host:8443/repo.git` is not taken for the start of pathspecs, 0
+/// for other paths.
+pub fn url_prefix_length(path: &str) -> usize {
+    let Some(scheme) = CLONE_SCHEMES
+        .iter()
+        .find(|scheme| path.starts_with(*scheme))
+    else {
+        return 0;
+    };
+    let rest = &path[scheme.len()..];
+    scheme.len() + rest.find('/').unwrap_or(rest.len())
+}
+
+/// Returns the directory the repositories of clone URLs are cloned into,
+/// `$XDG_CACHE_HOME/gitnapped/repos` (`~/.cache/gitnapped/repos`).
+pub fn clones_dir() -> Option<PathBuf> {
+    user_dir("XDG_CACHE_HOME", ".cache").map(|dir| dir.join("repos"))
+}
+
+/// Returns the directory a clone URL is cloned into, below `clones_dir` by
+/// host and path, e.g. `repos/example.com/org/repo` for
+/// `https://user@example.com/org/repo.git`.
+///
+/// # Returns
+/// * `Option<PathBuf>` - The directory, None if the URL has no path to clone
+pub fn clone_dir(url: &str) -> Option<PathBuf> {
+    let scheme = CLONE_SCHEMES
+        .iter()
+        .find(|scheme| url.starts_with(*scheme))?;
+    let rest = url[scheme.len()..].trim_end_matches('/');
+    let rest = rest.strip_suffix(".git").unwrap_or(rest);
+    let (authority, path) = rest.split_once('/')?;
+    // Credentials stay out of the directory names
+    let host = authority.rsplit('@').next().unwrap_or(authority);
+
+    let mut dir = clones_dir()?.join(host.replace(':', "_"));
+    for segment in path.split('/') {
+        if segment.is_empty() || segment == "." || segment == ".." {
+            return None;
+        }
+        dir.push(segment);
+    }
+    Some(dir)
+}
+
+/// Returns the local clone of a clone URL of the config, cloning the
+/// repository first if it is not cloned yet.
+///
+/// # Arguments
+/// * `url` - The URL, e.g. `https://example.com/org/repo.git`
+///
+/// # Returns
+/// * `String` - The path of the clone, or the URL when it has no path to clone
+pub fn local_clone(url: &str) -> String {
+    let Some(dir) = clone_dir(url) else {
+        debug(&format!("No directory to clone {} into", url));
+        return url.to_string();
+    };
+    if !dir.join(".git").exists() {
+        log(&format!(
+            "{} {} {} {}",
+            "Cloning".bright_yellow(),
+            url.bright_cyan(),
+            "into".bright_yellow(),
+            dir.display().to_string().bright_cyan()
+        ));
+        if let Some(parent) = dir.parent() {
+            let _ = fs::create_dir_all(parent);
+        }
+        let clone_args = ["clone", "--quiet", url, &dir.to_string_lossy()];
+        if let Err(err) = run_git(None, &clone_args) {
+            log(&format!(
+                "{} {}: {}",
+                "Warning: Failed to clone".bright_red(),
+                url,
+                err
+            ));
+        }
+    }
+    dir.to_string_lossy().into_owned()
+}
+
+/// Fetches the repositories of the config cloned from clone URLs, moving
+/// their clones to the last commit of the default branch, so that commits
+/// pushed since the last analysis are counted.
+pub fn update_clones(config: &Config) {
+    let Some(root) = clones_dir() else {
+        return;
+    };
+    let clones: BTreeSet<String> = parse_repos_from_config(config)
+        .into_iter()
+        .map(|repo| repo.path)
+        .filter(|path| Path::new(path).starts_with(&root) && Path::new(path).join(".git").exists())
+        .collect();
+
+    for clone in clones {
+        debug(&format!("Fetching {}", clone));
+        let updated = run_git(Some(&clone), &["fetch", "--quiet", "--prune", "origin"])
+            .and_then(|_| run_git(Some(&clone), &["reset", "--quiet", "--hard", "origin/HEAD"]));
+        if let Err(err) = updated {
+            log(&format!(
+                "{} {}: {}",
+                "Warning: Failed to update".bright_red(),
+                clone,
+                err
+            ));
+        }
+    }
+}
+
+/// Fetches the repositories of the config from their remotes, leaving out
+/// the ones read through an API and the clones of `update_clones`.
+///
+/// # Arguments
+/// * `repos` - The repositories of the config
+/// * `pull` - Whether their checked-out branches are also fast-forwarded to their upstreams
+pub fn fetch_repositories(repos: &[RepoInfo], pull: bool) {
+    let root = clones_dir();
+    let mut seen = HashSet::new();
+    let repos: Vec<&str> = repos
+        .iter()
+        .map(|repo| repo.path.as_str())
+        .filter(|path| remote_prefix_length(path) == 0 && Path::new(path).exists())
+        .filter(|path| {
+            !root
+                .as_ref()
+                .is_some_and(|root| Path::new(path).starts_with(root))
+        })
+        // Parts of a monorepo are fetched once
+        .filter(|path| seen.insert(fs::canonicalize(path).unwrap_or_else(|_| path.into())))
+        .collect();
+    log(&format!(
+        "{} {} {}",
+        if pull { "Pulling" } else { "Fetching" }.bright_yellow(),
+        repos.len().to_string().bright_cyan(),
+        "repositories".bright_yellow()
+    ));
+
+    for repo in repos {
+        debug(&format!("Fetching {}", repo));
+        let result = if pull {
+            // Local-only branches have nothing to pull
+            if run_git(
+                Some(repo),
+                &["rev-parse", "--verify", "--quiet", "@{upstream}"],
+            )
+            .is_err()
+            {
+                debug(&format!("No upstream to pull in {}", repo));
+                continue;
+            }
+            run_git(Some(repo), &["pull", "--quiet", "--ff-only"])
+        } else {
+            run_git(Some(repo), &["fetch", "--quiet", "--all"])
+        };
+        if let Err(err) = result {
+            log(&format!(
+                "{} {}: {}",
+                format!("Warning: Failed to {}", if pull { "pull" } else { "fetch" }).bright_red(),
+                repo,
+                err
+            ));
+        }
+    }
+}
+
+/// Runs a git command, in a repository when given one.
+///
+/// # Returns
+/// * `Result<(), String>` - An error holding what git printed when it fails
+fn run_git(repo: Option<&str>, args: &[&str]) -> Result<(), String> {
+    let mut cmd = Command::new("git");
+    if let Some(repo) = repo {
+        cmd.args(["-C", repo]);
+    }
+    // A clone asking for credentials would hang the analysis
+    cmd.env("GIT_TERMINAL_PROMPT", "0");
+    let output = cmd.args(args).output().map_err(|err| err.to_string())?;
+    if output.status.success() {
+        Ok(())
+    } else {
+        Err(String::from_utf8_lossy(&output.stderr).trim().to_string())
+    }
+}
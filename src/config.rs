@@ -1,12 +1,24 @@
-use crate::models::{Config, RepoInfo};
-use crate::parser::parse_repo_string;
+use crate::clones::{clone_dir, is_clone_url, local_clone};
+use crate::discovery::{
+    expand_env, expand_home, expand_repo_path, find_repositories, DEFAULT_SCAN_DEPTH,
+};
+use crate::models::{Config, RepoConfig, RepoEntry, RepoInfo, ScanConfig};
+use crate::parser::{parse_repo_entry, parse_repo_string, repo_entry_path, with_repo_entry_path};
+use crate::schedule::{
+    CommitTimezone, Schedule, Schedules, TeamMember, WorkingHours, WorkingTimeSpec,
+    DEFAULT_WORKING_TIME,
+};
+use crate::utils::debug;
 use colored::*;
-use std::collections::HashMap;
+use directories::BaseDirs;
+use regex::RegexBuilder;
+use serde_yaml::{Mapping, Value};
+use std::collections::{BTreeMap, HashMap};
 use std::fs;
-use std::path::Path;
+use std::path::{Path, PathBuf};
 use std::process::Command;
 
-fn is_git_repository(dir: &str) -> bool {
+pub fn is_git_repository(dir: &str) -> bool {
     let output = Command::new("git")
         .args(["-C", dir, "rev-parse", "--is-inside-work-tree"])
         .output();
@@ -17,43 +29,369 @@ fn is_git_repository(dir: &str) -> bool {
     }
 }
 
-pub fn push_to_empty_config(dir: &str) -> Result<Config, String> {
-    if !is_git_repository(dir) {
+/// Builds the config of a directory given with `-d`: the repository it is, or
+/// with `--recursive` every repository found below it.
+///
+/// # Arguments
+/// * `dir` - The directory to analyze
+/// * `scan_depth` - How many directory levels below `dir` are searched, None for `dir` alone
+///
+/// # Returns
+/// * `Result<Config, String>` - The config, or an error if there is no repository to analyze
+pub fn push_to_empty_config(dir: &str, scan_depth: Option<usize>) -> Result<Config, String> {
+    let entry = match scan_depth {
+        Some(depth) => RepoEntry::Scan(ScanConfig {
+            scan: dir.to_string(),
+            depth: Some(depth),
+        }),
+        None if is_git_repository(dir) => {
+            RepoEntry::Line(format!("{} [Uncategorized][Unnamed]", dir))
+        }
+        None => {
+            return Err(format!(
+                "'{}' {}",
+                dir.yellow(),
+                "is not a Git repository. Please provide a valid Git repository path.".bright_red()
+            ));
+        }
+    };
+
+    let mut repos = HashMap::new();
+    repos.insert("Uncategorized".to_string(), vec![entry]);
+
+    let mut config = Config {
+        include: Vec::new(),
+        author: None,
+        exclude_authors: Vec::new(),
+        repos,
+        working_time: None,
+        working_time_by_day: HashMap::new(),
+        workdays: None,
+        working_time_by_category: HashMap::new(),
+        working_time_by_repo: HashMap::new(),
+        week_start: None,
+        fiscal_year_start: None,
+        team: Vec::new(),
+        test_patterns: None,
+        ticket_patterns: None,
+        profiles: HashMap::new(),
+        defaults: BTreeMap::new(),
+        email: None,
+        forges: BTreeMap::new(),
+    };
+    expand_repo_entries(&mut config);
+    if config.repos.values().all(Vec::is_empty) {
         return Err(format!(
-            "'{}' {}",
-            dir.yellow(),
-            "is not a Git repository. Please provide a valid Git repository path.".bright_red()
+            "{} '{}'",
+            "No Git repository found in".bright_red(),
+            dir.yellow()
         ));
     }
+    Ok(config)
+}
 
-    let mut repos = HashMap::new();
-    let dir_to_string = format!("{} [Uncategorized][Unnamed]", dir);
-    repos.insert("Uncategorized".to_string(), vec![dir_to_string]);
+/// Expands the repositories of a config, so machine-specific paths can be left
+/// out of it: `~`, environment variables and wildcards in their paths, see
+/// `expand_repo_path`, clone URLs, replaced with their clones in the cache,
+/// cloned when missing, and `scan` entries, replaced with the repositories
+/// found below their directories and listed by path.
+pub fn expand_repo_entries(config: &mut Config) {
+    config.working_time_by_repo = std::mem::take(&mut config.working_time_by_repo)
+        .into_iter()
+        .flat_map(|(repo, spec)| {
+            let paths = match clone_dir(&repo) {
+                Some(dir) => vec![dir.to_string_lossy().into_owned()],
+                None => expand_repo_path(&repo),
+            };
+            paths.into_iter().map(move |path| (path, spec.clone()))
+        })
+        .collect();
 
-    Ok(Config {
-        author: None,
-        repos: repos,
-    })
+    for entries in config.repos.values_mut() {
+        *entries = std::mem::take(entries)
+            .into_iter()
+            .flat_map(|entry| {
+                let path = repo_entry_path(&entry);
+                let paths = if is_clone_url(path) {
+                    vec![local_clone(path)]
+                } else {
+                    expand_repo_path(path)
+                };
+                paths
+                    .into_iter()
+                    .map(move |path| with_repo_entry_path(&entry, &path))
+            })
+            .flat_map(|entry| match entry {
+                RepoEntry::Scan(scan) => {
+                    let root = Path::new(&scan.scan);
+                    let found = find_repositories(root, scan.depth.unwrap_or(DEFAULT_SCAN_DEPTH));
+                    debug(&format!("Found {} repositories in {:?}", found.len(), root));
+                    found
+                        .into_iter()
+                        .map(|path| {
+                            RepoEntry::Structured(RepoConfig {
+                                path: path.to_string_lossy().into_owned(),
+                                name: None,
+                                category: None,
+                                tags: Vec::new(),
+                                working_hours: None,
+                                exclude: Vec::new(),
+                            })
+                        })
+                        .collect()
+                }
+                entry => vec![entry],
+            })
+            .collect();
+    }
+}
+
+/// Config files looked for in the current directory when none is given.
+const DEFAULT_CONFIGS: [&str; 2] = ["gitnapped.yaml", "gitnapped.toml"];
+
+/// Config files looked for in the user config directory, after the ones of
+/// the current directory.
+const USER_CONFIGS: [&str; 2] = ["config.yaml", "config.toml"];
+
+/// Finds the config file to use when none is given with `-c`: `gitnapped.yaml`
+/// or `gitnapped.toml` in the current directory, else `config.yaml` or
+/// `config.toml` in the user config directory, `~/.config/gitnapped` on Linux.
+///
+/// # Returns
+/// * `String` - The path of the config file, `gitnapped.yaml` if none exists
+pub fn default_config_path() -> String {
+    let user_dir = BaseDirs::new().map(|dirs| dirs.config_dir().join("gitnapped"));
+    let user_configs = user_dir
+        .iter()
+        .flat_map(|dir| USER_CONFIGS.iter().map(move |name| dir.join(name)));
+
+    DEFAULT_CONFIGS
+        .iter()
+        .map(PathBuf::from)
+        .chain(user_configs)
+        .find(|path| path.exists())
+        .map(|path| path.to_string_lossy().into_owned())
+        .unwrap_or_else(|| DEFAULT_CONFIGS[0].to_string())
 }
 
-pub fn load_config(path: &str) -> Result<Config, String> {
+/// Loads a config file, as TOML when its extension is `.toml` and as YAML
+/// otherwise, merged with the files it includes.
+///
+/// # Arguments
+/// * `path` - The config file
+/// * `profile` - The profile of the config to use, as given with `--profile`
+///
+/// # Returns
+/// * `Result<Config, String>` - The config, or an error telling why it could not be read
+pub fn load_config(path: &str, profile: Option<&str>) -> Result<Config, String> {
+    let mut config = read_config(path, profile)?;
+    expand_repo_entries(&mut config);
+
+    Ok(config)
+}
+
+/// Reads a config file like `load_config`, leaving its repositories as
+/// written, for the settings needed before they are looked for.
+///
+/// # Arguments
+/// * `path` - The config file
+/// * `profile` - The profile of the config to use, as given with `--profile`
+///
+/// # Returns
+/// * `Result<Config, String>` - The config, or an error telling why it could not be read
+pub fn read_config(path: &str, profile: Option<&str>) -> Result<Config, String> {
     if !Path::new(path).exists() {
         return Err(format!("Config file '{}' not found", path));
     }
 
+    let (mut config, value) = load_with_includes(Path::new(path), &mut Vec::new())?;
+    if let Some(profile) = profile {
+        config = apply_profile(&config, value, profile)?;
+    }
+
+    Ok(config)
+}
+
+/// Reads a single config file, as TOML when its extension is `.toml` and as
+/// YAML otherwise.
+///
+/// # Returns
+/// * `Result<(Config, Value), String>` - The config and its raw contents, to be merged with other files, or an error
+fn read_config_file(path: &Path) -> Result<(Config, Value), String> {
     let contents = match fs::read_to_string(path) {
         Ok(content) => content,
         Err(err) => {
-            return Err(format!("Error reading config file '{}': {}", path, err));
+            return Err(format!(
+                "Error reading config file '{}': {}",
+                path.display(),
+                err
+            ));
         }
     };
 
-    match serde_yaml::from_str(&contents) {
-        Ok(config) => Ok(config),
-        Err(err) => Err(format!(
-            "Invalid YAML format in config file '{}': {}",
-            path, err
-        )),
+    let is_toml = path
+        .extension()
+        .is_some_and(|extension| extension.eq_ignore_ascii_case("toml"));
+    let invalid = |format: &str, err: &dyn std::fmt::Display| {
+        format!(
+            "Invalid {} format in config file '{}': {}",
+            format,
+            path.display(),
+            err
+        )
+    };
+    if is_toml {
+        let config = toml::from_str(&contents).map_err(|err| invalid("TOML", &err))?;
+        let table: toml::Table = toml::from_str(&contents).map_err(|err| invalid("TOML", &err))?;
+        let value = serde_yaml::to_value(table).map_err(|err| invalid("TOML", &err))?;
+        Ok((config, value))
+    } else {
+        let config = serde_yaml::from_str(&contents).map_err(|err| invalid("YAML", &err))?;
+        let value = serde_yaml::from_str(&contents).map_err(|err| invalid("YAML", &err))?;
+        Ok((config, value))
+    }
+}
+
+/// Reads a config file and the files listed in its `include` key, merged in
+/// order: later files override the keys of earlier ones and the including file
+/// overrides them all. Maps such as `repos` are merged key by key, so files can
+/// add categories of their own, while any other value is replaced.
+///
+/// # Arguments
+/// * `path` - The config file
+/// * `including` - The files including it, to refuse include cycles
+///
+/// # Returns
+/// * `Result<(Config, Value), String>` - The merged config and its raw contents, or an error
+fn load_with_includes(
+    path: &Path,
+    including: &mut Vec<PathBuf>,
+) -> Result<(Config, Value), String> {
+    let (config, mut value) = read_config_file(path)?;
+    if config.include.is_empty() {
+        return Ok((config, value));
+    }
+
+    let canonical = fs::canonicalize(path).unwrap_or_else(|_| path.to_path_buf());
+    if including.contains(&canonical) {
+        return Err(format!("Config file '{}' includes itself", path.display()));
+    }
+    including.push(canonical);
+
+    let mut merged = Value::Mapping(Mapping::new());
+    for include in &config.include {
+        let include_path = include_path(path, include);
+        if !include_path.exists() {
+            return Err(format!(
+                "Config file '{}' included by '{}' not found",
+                include_path.display(),
+                path.display()
+            ));
+        }
+        debug(&format!("Including {:?} in {:?}", include_path, path));
+        let (_, included) = load_with_includes(&include_path, including)?;
+        merge_values(&mut merged, included);
+    }
+    including.pop();
+
+    if let Value::Mapping(own) = &mut value {
+        own.remove("include");
+    }
+    merge_values(&mut merged, value);
+    let config = serde_yaml::from_value(merged.clone()).map_err(|err| {
+        format!(
+            "Invalid config '{}' once its includes are merged: {}",
+            path.display(),
+            err
+        )
+    })?;
+
+    Ok((config, merged))
+}
+
+/// Resolves a file listed in the `include` key of a config file: relative
+/// paths start from the directory of the config file, `~` and environment
+/// variables are expanded.
+pub fn include_path(config: &Path, include: &str) -> PathBuf {
+    let dir = config.parent().unwrap_or(Path::new(""));
+    dir.join(expand_home(&expand_env(include)))
+}
+
+/// Merges a profile of the config over the rest of it, the way an included
+/// file is merged, e.g. for another author or other working hours. The
+/// `categories` of a profile keep only these categories of repositories.
+///
+/// # Arguments
+/// * `config` - The config, includes merged
+/// * `value` - Its raw contents
+/// * `name` - The name of the profile
+///
+/// # Returns
+/// * `Result<Config, String>` - The config of the profile, or an error if it is unknown or invalid
+fn apply_profile(config: &Config, mut value: Value, name: &str) -> Result<Config, String> {
+    let Some(mut profile) = config.profiles.get(name).cloned() else {
+        let mut known: Vec<&str> = config.profiles.keys().map(String::as_str).collect();
+        known.sort();
+        return Err(format!(
+            "Unknown profile '{}', the config has: {}",
+            name,
+            if known.is_empty() {
+                "none".to_string()
+            } else {
+                known.join(", ")
+            }
+        ));
+    };
+
+    let categories: Option<Vec<String>> = profile
+        .as_mapping_mut()
+        .and_then(|profile| profile.remove("categories"))
+        .map(serde_yaml::from_value)
+        .transpose()
+        .map_err(|err| format!("Invalid categories in profile '{}': {}", name, err))?;
+    if let Some(own) = value.as_mapping_mut() {
+        own.remove("profiles");
+    }
+    merge_values(&mut value, profile);
+    let mut config: Config = serde_yaml::from_value(value)
+        .map_err(|err| format!("Invalid profile '{}': {}", name, err))?;
+
+    if let Some(categories) = categories {
+        if let Some(unknown) = categories
+            .iter()
+            .find(|category| !config.repos.contains_key(*category))
+        {
+            return Err(format!(
+                "Unknown category '{}' in profile '{}'",
+                unknown, name
+            ));
+        }
+        config
+            .repos
+            .retain(|category, _| categories.contains(category));
+        config
+            .working_time_by_category
+            .retain(|category, _| categories.contains(category));
+    }
+    debug(&format!("Using profile {}", name));
+
+    Ok(config)
+}
+
+/// Merges the contents of a config file over the ones read before it.
+fn merge_values(base: &mut Value, overlay: Value) {
+    match (base, overlay) {
+        (Value::Mapping(base), Value::Mapping(overlay)) => {
+            for (key, value) in overlay {
+                match base.get_mut(&key) {
+                    Some(existing) => merge_values(existing, value),
+                    None => {
+                        base.insert(key, value);
+                    }
+                }
+            }
+        }
+        (base, overlay) => *base = overlay,
     }
 }
 
@@ -65,12 +403,121 @@ pub fn parse_repos_from_config(config: &Config) -> Vec<RepoInfo> {
 
     let mut result = Vec::new();
 
-    for (_, repos) in &config.repos {
-        for repo_str in repos {
-            let repo_info = parse_repo_string(repo_str);
-            result.push(repo_info);
+    for repos in config.repos.values() {
+        for entry in repos {
+            result.push(parse_repo_entry(entry));
         }
     }
 
     result
 }
+
+/// Builds the schedules of a config alone, without the command line options:
+/// its working hours, `DEFAULT_WORKING_TIME` when missing, per-weekday hours
+/// and workdays, then the overrides of `parse_schedules_from_config`.
+///
+/// # Returns
+/// * `Result<Schedules, String>` - The schedules, or an error naming the invalid setting
+pub fn schedules_from_config(config: &Config) -> Result<Schedules, String> {
+    let working_time = config
+        .working_time
+        .as_ref()
+        .map(|spec| spec.as_spec())
+        .unwrap_or_else(|| DEFAULT_WORKING_TIME.to_string());
+    let hours = WorkingHours::parse(&working_time)
+        .ok_or_else(|| format!("Invalid working time '{}'", working_time))?;
+    let mut schedule = Schedule::with_overrides(hours, &config.working_time_by_day)?;
+    if let Some(workdays) = &config.workdays {
+        schedule.set_workdays(workdays)?;
+    }
+
+    parse_schedules_from_config(config, schedule)
+}
+
+/// Builds the schedules of every repository out of the global schedule and the
+/// per-category and per-repo working time overrides of the config, plus the
+/// schedules of the team members.
+///
+/// An override replaces the default working hours of the global schedule, while
+/// its workdays and per-weekday hours still apply. Per-repo overrides win over
+/// per-category ones. A team member starts from the global schedule and replaces
+/// the working hours, timezone and workdays they set.
+///
+/// # Arguments
+/// * `config` - The configuration structure
+/// * `global` - The schedule of repositories without an override
+///
+/// # Returns
+/// * `Result<Schedules, String>` - The schedules, or an error naming the invalid override
+pub fn parse_schedules_from_config(config: &Config, global: Schedule) -> Result<Schedules, String> {
+    let with_hours = |spec: &WorkingTimeSpec, owner: &str| -> Result<Schedule, String> {
+        let spec = spec.as_spec();
+        let hours = WorkingHours::parse(&spec)
+            .ok_or_else(|| format!("Invalid working time '{}' for {}", spec, owner))?;
+        Ok(Schedule {
+            default: hours,
+            ..global.clone()
+        })
+    };
+
+    let mut repos = HashMap::new();
+
+    for (category, spec) in &config.working_time_by_category {
+        let Some(category_repos) = config.repos.get(category) else {
+            return Err(format!(
+                "Unknown category '{}' in working_time_by_category",
+                category
+            ));
+        };
+        let schedule = with_hours(spec, &format!("category '{}'", category))?;
+        for entry in category_repos {
+            repos.insert(parse_repo_entry(entry).path, schedule.clone());
+        }
+    }
+
+    for (repo, spec) in &config.working_time_by_repo {
+        let path = parse_repo_string(repo).path;
+        let schedule = with_hours(spec, &format!("repository '{}'", path))?;
+        repos.insert(path, schedule);
+    }
+
+    // The working hours of a structured entry are the closest to its repository
+    for entry in config.repos.values().flatten() {
+        if let RepoEntry::Structured(repo) = entry {
+            if let Some(spec) = &repo.working_hours {
+                let path = parse_repo_entry(entry).path;
+                let schedule = with_hours(spec, &format!("repository '{}'", path))?;
+                repos.insert(path, schedule);
+            }
+        }
+    }
+
+    let mut team = Vec::new();
+    for member in &config.team {
+        let owner = format!("team member '{}'", member.author);
+        let mut schedule = match &member.working_time {
+            Some(spec) => with_hours(spec, &owner)?,
+            None => global.clone(),
+        };
+        if let Some(name) = &member.timezone {
+            schedule.timezone = match CommitTimezone::from_name(name) {
+                Some(timezone) => timezone,
+                None => CommitTimezone::named(name)?,
+            };
+        }
+        if let Some(workdays) = &member.workdays {
+            schedule.set_workdays(workdays)?;
+        }
+        let pattern = RegexBuilder::new(&member.author)
+            .case_insensitive(true)
+            .build()
+            .map_err(|err| format!("Invalid pattern for {}: {}", owner, err))?;
+        team.push(TeamMember { pattern, schedule });
+    }
+
+    Ok(Schedules {
+        global,
+        repos,
+        team,
+    })
+}
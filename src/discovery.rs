@@ -0,0 +1,133 @@
+use crate::utils::debug;
+use directories::BaseDirs;
+use std::env;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// How many directory levels are searched for repositories when not told.
+pub const DEFAULT_SCAN_DEPTH: usize = 3;
+
+/// Expands a leading `~` of a path to the home directory.
+pub fn expand_home(path: &str) -> PathBuf {
+    let home = BaseDirs::new().map(|dirs| dirs.home_dir().to_path_buf());
+    match (path.strip_prefix('~'), home) {
+        (Some(rest), Some(home)) if rest.is_empty() || rest.starts_with(['/', '\\']) => {
+            home.join(rest.trim_start_matches(['/', '\\']))
+        }
+        _ => PathBuf::from(path),
+    }
+}
+
+/// Expands the `$VAR` and `${VAR}` environment variables of a path, leaving
+/// the ones that are not set as written.
+pub fn expand_env(path: &str) -> String {
+    let mut expanded = String::new();
+    let mut rest = path;
+    while let Some(index) = rest.find('$') {
+        expanded.push_str(&rest[..index]);
+        let after = &rest[index + 1..];
+        let (name, length) = match after.strip_prefix('{') {
+            Some(braced) => match braced.find('}') {
+                Some(end) => (&braced[..end], end + 2),
+                None => ("", 0),
+            },
+            None => {
+                let end = after
+                    .find(|c: char| !(c.is_ascii_alphanumeric() || c == '_'))
+                    .unwrap_or(after.len());
+                (&after[..end], end)
+            }
+        };
+
+        match env::var(name) {
+            Ok(value) if !name.is_empty() => expanded.push_str(&value),
+            _ => {
+                debug(&format!("Variable ${} of {} is not set", name, path));
+                expanded.push_str(&rest[index..index + 1 + length]);
+            }
+        }
+        rest = &after[length..];
+    }
+    expanded.push_str(rest);
+    expanded
+}
+
+/// Expands a repository path of the config: `~`, environment variables, then
+/// the `*`, `?` and `**` wildcards against the existing directories, e.g.
+/// `~/work/*/backend` or `$PROJECTS/foo`.
+///
+/// # Arguments
+/// * `path` - The path as written in the config
+///
+/// # Returns
+/// * `Vec<String>` - The matching directories, or the expanded path alone when it has no wildcard
+pub fn expand_repo_path(path: &str) -> Vec<String> {
+    let expanded = expand_home(&expand_env(path))
+        .to_string_lossy()
+        .into_owned();
+    if !expanded.contains(['*', '?']) {
+        return vec![expanded];
+    }
+
+    match glob::glob(&expanded) {
+        Ok(paths) => {
+            let found: Vec<String> = paths
+                .flatten()
+                .filter(|path| path.is_dir())
+                .map(|path| path.to_string_lossy().into_owned())
+                .collect();
+            debug(&format!("{} matches {} directories", path, found.len()));
+            found
+        }
+        Err(err) => {
+            debug(&format!("Invalid pattern {}: {}", path, err));
+            vec![expanded]
+        }
+    }
+}
+
+/// Finds the Git repositories in a directory tree: every directory holding a
+/// `.git` directory, or a `.git` file for worktrees. Repositories are not
+/// searched for nested ones, e.g. their submodules, and hidden directories are
+/// skipped.
+///
+/// # Arguments
+/// * `root` - The directory to search, itself included
+/// * `max_depth` - How many directory levels below `root` are searched
+///
+/// # Returns
+/// * `Vec<PathBuf>` - The paths of the repositories, sorted
+pub fn find_repositories(root: &Path, max_depth: usize) -> Vec<PathBuf> {
+    let mut repos = Vec::new();
+    let mut pending = vec![(root.to_path_buf(), 0)];
+
+    while let Some((dir, depth)) = pending.pop() {
+        if dir.join(".git").exists() {
+            debug(&format!("Found repository {:?}", dir));
+            repos.push(dir);
+            continue;
+        }
+        if depth >= max_depth {
+            continue;
+        }
+
+        let entries = match fs::read_dir(&dir) {
+            Ok(entries) => entries,
+            Err(err) => {
+                debug(&format!("Skipping {:?}: {}", dir, err));
+                continue;
+            }
+        };
+        for entry in entries.flatten() {
+            let hidden = entry.file_name().to_string_lossy().starts_with('.');
+            // Symbolic links are not followed, so that loops cannot be walked forever
+            let is_dir = entry.file_type().is_ok_and(|file_type| file_type.is_dir());
+            if is_dir && !hidden {
+                pending.push((entry.path(), depth + 1));
+            }
+        }
+    }
+
+    repos.sort();
+    repos
+}
@@ -1,16 +1,37 @@
+use crate::health::health_score;
+use crate::language::FileTypeOrder;
+use crate::models::AuthorStats;
 use crate::models::CategoryStats;
 use crate::models::ProjectStats;
 use crate::models::RepoStats;
+use crate::report::{
+    ActivityCount, EditorTime, RepoReport, Report, ReviewActivity, SnapshotComparison, Tickets,
+    Trend, TrendMetric,
+};
+use crate::schedule::Schedule;
+use crate::snapshots::SnapshotPeriod;
 use crate::utils::get_max_commit_day;
 use crate::utils::log;
+use crate::utils::{
+    binary_files_detail, commit_intervals, commit_size_summary, commit_streaks, commits_per_day,
+    file_type_detail, format_minutes, inactivity_gaps, line_kinds_detail, short_sleep_days,
+    tests_detail, top_file_types, typical_day,
+};
+use askama::Template;
+use chrono::{Datelike, Duration, Local, NaiveDate, NaiveTime, TimeZone, Weekday};
 use colored::*;
-use std::collections::HashMap;
+use comfy_table::presets::UTF8_FULL_CONDENSED;
+use comfy_table::{Cell, CellAlignment, Color, ContentArrangement, Table};
+use std::cmp::Reverse;
+use std::collections::{BTreeMap, HashMap};
+use std::fs;
 
 pub fn print_category_summary(
     categories: &[CategoryStats],
     sort_by: &str,
-    show_filetypes: bool,
+    filetypes: Option<FileTypeOrder>,
     pretty: bool,
+    as_table: bool,
 ) {
     log(&format!("\n{}", "Category Statistics:".bright_green()));
 
@@ -50,43 +71,35 @@ pub fn print_category_summary(
                 0
             };
             log(&format!(
-                "{}: {}% ({})",
+                "{}: {}% ({}) {}",
                 "Gitnapped for".yellow(),
                 percentage.to_string().red(),
-                category.total.out_of_hours_commits.to_string().red()
+                category.total.out_of_hours_commits.to_string().red(),
+                gitnapped_hours(category.total.out_of_hours_minutes).red()
+            ));
+        }
+        if category.total.files_counted {
+            log(&format!(
+                "{}: {}",
+                "Total files".yellow(),
+                category.total.file_count.to_string().cyan()
+            ));
+            log(&format!(
+                "{}: {}",
+                "Total lines of code".yellow(),
+                category.total.line_count.to_string().cyan()
             ));
         }
-        log(&format!(
-            "{}: {}",
-            "Total files".yellow(),
-            category.total.file_count.to_string().cyan()
-        ));
-        log(&format!(
-            "{}: {}",
-            "Total lines of code".yellow(),
-            category.total.line_count.to_string().cyan()
-        ));
 
         // Show file types for this category if requested
-        if show_filetypes && !category.total.file_types.is_empty() {
+        if let (Some(order), false) = (filetypes, category.total.file_types.is_empty()) {
             log(&format!("  {}", "File types:".bright_magenta()));
-            let mut types: Vec<(String, usize)> = category
-                .total
-                .file_types
-                .iter()
-                .map(|(k, v)| (k.clone(), *v))
-                .collect();
-
-            // Sort by count (descending)
-            types.sort_by(|a, b| b.1.cmp(&a.1));
-
             // Show top 5 file types per category
-            for (ext, count) in types.iter().take(5) {
+            for (language, files, lines) in top_file_types(&category.total, order, 5) {
                 log(&format!(
-                    "    {} - {} {}",
-                    ext.bright_yellow(),
-                    count,
-                    "files".green()
+                    "    {} - {}",
+                    language.bright_yellow(),
+                    file_type_detail(files, lines)
                 ));
             }
         }
@@ -94,9 +107,9 @@ pub fn print_category_summary(
         // Sort repos by criterion
         let mut sorted_repos = category.repos.clone();
         match sort_by {
-            "commits" => sorted_repos.sort_by(|a, b| b.1.commit_count.cmp(&a.1.commit_count)),
-            "files" => sorted_repos.sort_by(|a, b| b.1.file_count.cmp(&a.1.file_count)),
-            "lines" => sorted_repos.sort_by(|a, b| b.1.line_count.cmp(&a.1.line_count)),
+            "commits" => sorted_repos.sort_by_key(|item| Reverse(item.1.commit_count)),
+            "files" => sorted_repos.sort_by_key(|item| Reverse(item.1.file_count)),
+            "lines" => sorted_repos.sort_by_key(|item| Reverse(item.1.line_count)),
             _ => {}
         }
 
@@ -107,11 +120,21 @@ pub fn print_category_summary(
                 "Top repositories:".bright_blue(),
                 sort_by
             ));
+            if as_table {
+                let rows: Vec<(String, &RepoStats)> = sorted_repos
+                    .iter()
+                    .take(3)
+                    .filter(|(_, stats)| stats.commit_count > 0 || sort_by != "commits")
+                    .map(|(repo, stats)| (repo.clone(), stats))
+                    .collect();
+                print_repo_table(&rows, 3);
+                continue;
+            }
             for (i, (repo, stats)) in sorted_repos.iter().enumerate().take(3) {
                 if stats.commit_count > 0 || sort_by != "commits" {
                     if pretty {
                         // Extract vanity name from the path
-                        let vanity_name = repo.split('/').last().unwrap_or(repo);
+                        let vanity_name = repo.split('/').next_back().unwrap_or(repo);
                         log(&format!(
                             "   {}. {} - {} commits",
                             (i + 1).to_string().bright_yellow(),
@@ -126,25 +149,30 @@ pub fn print_category_summary(
                                 0
                             };
                             log(&format!(
-                                "      {}: {}% ({})",
+                                "      {}: {}% ({}) {}",
                                 "Gitnapped for".yellow(),
                                 percentage.to_string().red(),
-                                stats.out_of_hours_commits.to_string().red()
+                                stats.out_of_hours_commits.to_string().red(),
+                                gitnapped_hours(stats.out_of_hours_minutes).red()
                             ));
                         }
                     } else {
                         log(&format!(
-                            "   {}. {} - {} commits, {} files, {} lines",
+                            "   {}. {} - {} commits{}",
                             (i + 1).to_string().bright_yellow(),
-                            repo.split('/').last().unwrap_or(repo).green(),
+                            repo.split('/').next_back().unwrap_or(repo).green(),
                             stats.commit_count.to_string().cyan(),
-                            stats.file_count.to_string().blue(),
-                            stats.line_count.to_string().magenta()
+                            files_and_lines(stats)
                         ));
                         if stats.out_of_hours_commits > 0 {
                             log(&format!(
-                                "      {} commits",
-                                format!("Gitnapped for {}", stats.out_of_hours_commits).red()
+                                "      {}",
+                                format!(
+                                    "Gitnapped for {} commits ({})",
+                                    stats.out_of_hours_commits,
+                                    gitnapped_hours(stats.out_of_hours_minutes)
+                                )
+                                .red()
                             ));
                         }
                     }
@@ -157,7 +185,7 @@ pub fn print_category_summary(
 pub fn print_projects_summary(
     projects: &[ProjectStats],
     sort_by: &str,
-    show_filetypes: bool,
+    filetypes: Option<FileTypeOrder>,
     show_repo_details: bool,
 ) {
     log(&format!("\n{}", "Projects Statistics:".bright_green()));
@@ -167,7 +195,7 @@ pub fn print_projects_summary(
     for project in projects {
         by_group
             .entry(project.group.clone())
-            .or_insert_with(Vec::new)
+            .or_default()
             .push(project);
     }
 
@@ -186,29 +214,31 @@ pub fn print_projects_summary(
         // Sort projects in group based on criterion
         let mut sorted_projects = group_projects.clone();
         match sort_by {
-            "commits" => {
-                sorted_projects.sort_by(|a, b| b.stats.commit_count.cmp(&a.stats.commit_count))
-            }
-            "files" => sorted_projects.sort_by(|a, b| b.stats.file_count.cmp(&a.stats.file_count)),
-            "lines" => sorted_projects.sort_by(|a, b| b.stats.line_count.cmp(&a.stats.line_count)),
+            "commits" => sorted_projects.sort_by_key(|item| Reverse(item.stats.commit_count)),
+            "files" => sorted_projects.sort_by_key(|item| Reverse(item.stats.file_count)),
+            "lines" => sorted_projects.sort_by_key(|item| Reverse(item.stats.line_count)),
             _ => {}
         }
 
         // Print statistics for each project
         for (i, project) in sorted_projects.iter().enumerate() {
             log(&format!(
-                "{}. {} - {} commits, {} files, {} lines (from {} repos)",
+                "{}. {} - {} commits{} (from {} repos)",
                 (i + 1).to_string().bright_yellow(),
                 project.name.green(),
                 project.stats.commit_count.to_string().cyan(),
-                project.stats.file_count.to_string().blue(),
-                project.stats.line_count.to_string().magenta(),
+                files_and_lines(&project.stats),
                 project.repos.len().to_string().yellow()
             ));
             if project.stats.out_of_hours_commits > 0 {
                 log(&format!(
-                    "   {} commits",
-                    format!("Gitnapped for {}", project.stats.out_of_hours_commits).red()
+                    "   {}",
+                    format!(
+                        "Gitnapped for {} commits ({})",
+                        project.stats.out_of_hours_commits,
+                        gitnapped_hours(project.stats.out_of_hours_minutes)
+                    )
+                    .red()
                 ));
             }
 
@@ -220,25 +250,14 @@ pub fn print_projects_summary(
             }
 
             // If requested, show the file types
-            if show_filetypes && !project.stats.file_types.is_empty() {
+            if let (Some(order), false) = (filetypes, project.stats.file_types.is_empty()) {
                 log(&format!("   {}", "File types:".bright_magenta()));
-                let mut types: Vec<(String, usize)> = project
-                    .stats
-                    .file_types
-                    .iter()
-                    .map(|(k, v)| (k.clone(), *v))
-                    .collect();
-
-                // Sort by count (descending)
-                types.sort_by(|a, b| b.1.cmp(&a.1));
-
                 // Show top 5 file types per project
-                for (ext, count) in types.iter().take(5) {
+                for (language, files, lines) in top_file_types(&project.stats, order, 5) {
                     log(&format!(
-                        "     {} - {} {}",
-                        ext.bright_yellow(),
-                        count,
-                        "files".green()
+                        "     {} - {}",
+                        language.bright_yellow(),
+                        file_type_detail(files, lines)
                     ));
                 }
             }
@@ -246,26 +265,274 @@ pub fn print_projects_summary(
     }
 }
 
-pub fn print_most_active_day(commits_by_date: &HashMap<String, usize>) {
+/// Prints how the analyzed period compares with the previous period of the same length.
+///
+/// # Arguments
+/// * `trend` - The compared metrics
+pub fn print_trend(trend: &Trend) {
+    log(&format!(
+        "\n{} {} {} {}",
+        "Compared with".bright_green(),
+        trend.since.bright_cyan(),
+        "to".bright_green(),
+        trend.until.bright_cyan()
+    ));
+    for metric in &trend.metrics {
+        log(&metric_line(&capitalize(&metric.name).yellow(), metric));
+    }
+}
+
+/// Prints the pull requests and reviews of the period on GitHub, and how
+/// many of them were made outside working hours.
+///
+/// # Arguments
+/// * `activity` - The counted activity
+pub fn print_review_activity(activity: &ReviewActivity) {
+    log(&format!(
+        "\n{} {}",
+        "GitHub activity of".bright_green(),
+        activity.login.bright_cyan()
+    ));
+    let line = |label: &str, count: &ActivityCount| {
+        format!(
+            "{}: {} ({} {})",
+            label.yellow(),
+            count.total.to_string().cyan(),
+            count.out_of_hours.to_string().red(),
+            "out of hours".yellow()
+        )
+    };
+    log(&line("Pull requests opened", &activity.pull_requests));
+    log(&line("Reviews submitted", &activity.reviews));
+    log(&line("Review comments", &activity.review_comments));
+    log(&format!(
+        "{}: {}%",
+        "Gitnapped for".yellow(),
+        activity.gitnapped_percentage.to_string().red()
+    ));
+}
+
+/// Prints the time spent in editors next to the one estimated out of the
+/// commits, over the period then day by day.
+///
+/// # Arguments
+/// * `editor_time` - The compared times
+pub fn print_editor_time(editor_time: &EditorTime) {
+    log(&format!(
+        "\n{}",
+        "Editor time (WakaTime) and commit sessions:".bright_green()
+    ));
+    for day in &editor_time.days {
+        log(&format!(
+            "  {} - editor {}, commits {}",
+            day.date.bright_cyan(),
+            format_minutes(day.editor_minutes as u64),
+            format_minutes(day.commit_minutes as u64)
+        ));
+    }
+    let coverage = editor_time
+        .coverage_percentage
+        .map(|percentage| format!(" ({}% of the editor time)", percentage))
+        .unwrap_or_default();
+    log(&format!(
+        "{}: {}, {}: {}{}",
+        "Editor time".yellow(),
+        format_minutes(editor_time.editor_minutes as u64).cyan(),
+        "commit sessions".yellow(),
+        format_minutes(editor_time.commit_minutes as u64).cyan(),
+        coverage
+    ));
+}
+
+/// Prints the commits and estimated time of every ticket, then of the
+/// commits referring to none.
+///
+/// # Arguments
+/// * `tickets` - The ticket totals
+pub fn print_tickets(tickets: &Tickets) {
+    log(&format!("\n{}", "Time per ticket:".bright_green()));
+    for ticket in &tickets.tickets {
+        let days = if ticket.first_day == ticket.last_day {
+            ticket.first_day.clone()
+        } else {
+            format!("{} – {}", ticket.first_day, ticket.last_day)
+        };
+        log(&format!(
+            "  {} - {} commits, {} ({}, {})",
+            ticket.ticket.bright_cyan(),
+            ticket.commits,
+            format_minutes(ticket.minutes as u64).cyan(),
+            ticket.projects.join(", "),
+            days
+        ));
+    }
+    if tickets.tickets.is_empty() {
+        log(&format!("  {}", "No commit refers to a ticket".yellow()));
+    }
+    log(&format!(
+        "{}: {} commits, {}",
+        "Without a ticket".yellow(),
+        tickets.untracked_commits,
+        format_minutes(tickets.untracked_minutes as u64).cyan()
+    ));
+}
+
+/// Formats a compared metric after its label, e.g. `Commits: 12 (was 9) ↑ +33%`,
+/// the change colored by whether it is an improvement.
+fn metric_line(label: &ColoredString, metric: &TrendMetric) -> String {
+    let unit = if metric.points { "%" } else { "" };
+    let change = format!("{} {}", metric.arrow(), metric.change());
+    format!(
+        "{}: {}{} ({} {}{}) {}",
+        label,
+        metric.current.to_string().cyan(),
+        unit.cyan(),
+        "was".yellow(),
+        metric.previous,
+        unit,
+        match metric.improved() {
+            Some(true) => change.green(),
+            Some(false) => change.red(),
+            None => change.normal(),
+        }
+    )
+}
+
+/// Prints what changed since the last recorded snapshot: the totals, the
+/// repositories that started or stopped having commits, and the projects
+/// whose gitnapped percentage changed.
+///
+/// # Arguments
+/// * `comparison` - The analyzed period compared with the snapshot
+pub fn print_snapshot_comparison(comparison: &SnapshotComparison) {
+    log(&format!(
+        "\n{} {}",
+        "Compared with the snapshot of".bright_green(),
+        comparison.date.bright_cyan()
+    ));
+    for metric in &comparison.metrics {
+        log(&metric_line(&capitalize(&metric.name).yellow(), metric));
+    }
+    if !comparison.new_active_repos.is_empty() {
+        log(&format!(
+            "{}: {}",
+            "New active repos".yellow(),
+            comparison.new_active_repos.join(", ").green()
+        ));
+    }
+    if !comparison.inactive_repos.is_empty() {
+        log(&format!(
+            "{}: {}",
+            "No longer active".yellow(),
+            comparison.inactive_repos.join(", ").red()
+        ));
+    }
+
+    let changed: Vec<(&String, &TrendMetric)> = comparison
+        .projects
+        .iter()
+        .filter(|(_, metric)| metric.improved().is_some())
+        .collect();
+    if !changed.is_empty() {
+        log(&format!("{}", "Gitnapped by project:".yellow()));
+        for (name, metric) in changed {
+            log(&format!("  {}", metric_line(&name.bright_blue(), metric)));
+        }
+    }
+}
+
+/// Prints the metrics of every project side by side for two periods.
+///
+/// # Arguments
+/// * `a` - Start and end of the first period
+/// * `b` - Start and end of the second period
+/// * `projects` - Name of every project with the second period compared to the first one
+pub fn print_period_comparison(a: (&str, &str), b: (&str, &str), projects: &[(String, Trend)]) {
+    log(&format!(
+        "{} {} {} {} {} {} {} {}",
+        "Comparing A".bright_green(),
+        a.0.bright_cyan(),
+        "to".bright_green(),
+        a.1.bright_cyan(),
+        "with B".bright_green(),
+        b.0.bright_cyan(),
+        "to".bright_green(),
+        b.1.bright_cyan()
+    ));
+    for (name, trend) in projects {
+        log(&format!("\n{}", name.bright_blue()));
+        log(&format!(
+            "  {:<18} {:>10} {:>10}  {}",
+            "Metric".yellow(),
+            "A".yellow(),
+            "B".yellow(),
+            "Change".yellow()
+        ));
+        for metric in &trend.metrics {
+            let unit = if metric.points { "%" } else { "" };
+            let change = format!("{} {}", metric.arrow(), metric.change());
+            log(&format!(
+                "  {:<18} {:>10} {:>10}  {}",
+                capitalize(&metric.name),
+                format!("{}{}", metric.previous, unit),
+                format!("{}{}", metric.current, unit).cyan(),
+                match metric.improved() {
+                    Some(true) => change.green(),
+                    Some(false) => change.red(),
+                    None => change.normal(),
+                }
+            ));
+        }
+    }
+}
+
+/// Uppercases the first letter of a text.
+fn capitalize(text: &str) -> String {
+    let mut chars = text.chars();
+    match chars.next() {
+        Some(first) => first.to_uppercase().chain(chars).collect(),
+        None => String::new(),
+    }
+}
+
+/// Formats the files and lines of a repository after its commits, e.g.
+/// `, 12 files, 340 lines`, empty when they have not been counted.
+pub fn files_and_lines(stats: &RepoStats) -> String {
+    if !stats.files_counted {
+        return String::new();
+    }
+    format!(
+        ", {} files, {} lines",
+        stats.file_count.to_string().blue(),
+        stats.line_count.to_string().magenta()
+    )
+}
+
+pub fn print_most_active_day(commits_by_date: &BTreeMap<String, usize>) {
     if let Some((max_date, max_count)) = get_max_commit_day(commits_by_date) {
         log(&format!(
             "\n{} {} ({} {})",
             "Most active day:".bright_magenta(),
             max_date.bright_cyan(),
-            max_count.to_string(),
+            max_count,
             "commits".green()
         ));
     }
 }
 
+#[allow(clippy::too_many_arguments)]
 pub fn print_total_stats(
     stats: &RepoStats,
     active_count: usize,
     entity_name: &str,
-    show_filetypes: bool,
+    since: &str,
+    until: &str,
+    filetypes: Option<FileTypeOrder>,
     show_most_active: bool,
     hide_gitnapped_stats: bool,
     show_total_stats: bool,
+    short_sleep_before: u32,
+    min_gap_days: usize,
 ) {
     log(&format!(
         "\n{}",
@@ -277,9 +544,10 @@ pub fn print_total_stats(
         active_count.to_string().cyan()
     ));
     log(&format!(
-        "{}: {}",
+        "{}: {} {}",
         "Commits".yellow(),
-        stats.commit_count.to_string().cyan()
+        stats.commit_count.to_string().cyan(),
+        sparkline(&stats.commits_by_date, since, until).bright_blue()
     ));
     if !hide_gitnapped_stats {
         let percentage = if stats.commit_count > 0 {
@@ -288,23 +556,140 @@ pub fn print_total_stats(
             0
         };
         log(&format!(
-            "{}: {}% ({})",
+            "{}: {}% ({}) {}",
             "Gitnapped for".yellow(),
             percentage.to_string().red(),
-            stats.out_of_hours_commits.to_string().red()
+            stats.out_of_hours_commits.to_string().red(),
+            gitnapped_hours(stats.out_of_hours_minutes).red()
+        ));
+
+        let health = health_score(stats, since, until);
+        let score = health.score.to_string();
+        let factors: Vec<String> = health
+            .factors
+            .iter()
+            .map(|factor| format!("{}: {} (-{})", factor.name, factor.detail, factor.penalty))
+            .collect();
+        log(&format!(
+            "{}: {}/100{}",
+            "Health score".yellow(),
+            match health.score {
+                80.. => score.green(),
+                50..=79 => score.yellow(),
+                _ => score.red(),
+            },
+            if factors.is_empty() {
+                String::new()
+            } else {
+                format!(" - {}", factors.join("; "))
+            }
         ));
     }
-    if show_total_stats {
+    if stats.merge_commits > 0 {
         log(&format!(
             "{}: {}",
-            "Total files".yellow(),
-            stats.file_count.to_string().cyan()
+            "Merge commits".yellow(),
+            stats.merge_commits.to_string().cyan()
         ));
+    }
+    if let Some((first_commit, last_commit)) = typical_day(stats) {
+        log(&format!(
+            "{}: first commit {}, last commit {}",
+            "Typical day".yellow(),
+            first_commit.cyan(),
+            last_commit.cyan()
+        ));
+    }
+    if let Some((mean, median)) = commit_intervals(&stats.commit_times) {
+        log(&format!(
+            "{}: mean {}, median {}",
+            "Time between commits".yellow(),
+            format_minutes(mean).cyan(),
+            format_minutes(median).cyan()
+        ));
+    }
+    let gaps = inactivity_gaps(&stats.commits_by_date, since, until, min_gap_days);
+    let gap_days: usize = gaps
+        .iter()
+        .map(|(start, end)| (*end - *start).num_days() as usize + 1)
+        .sum();
+    let average = commits_per_day(stats.commit_count, since, until, 0);
+    if gaps.is_empty() {
         log(&format!(
             "{}: {}",
+            "Commits per day".yellow(),
+            format!("{:.2}", average).cyan()
+        ));
+    } else {
+        log(&format!(
+            "{}: {} ({} {})",
+            "Commits per day".yellow(),
+            format!("{:.2}", average).cyan(),
+            format!(
+                "{:.2}",
+                commits_per_day(stats.commit_count, since, until, gap_days)
+            )
+            .cyan(),
+            "excluding inactivity gaps".yellow()
+        ));
+        log(&format!(
+            "{}: {} ({} {})",
+            format!("Inactivity gaps of {}+ days", min_gap_days).yellow(),
+            gaps.len().to_string().cyan(),
+            gap_days.to_string().cyan(),
+            if gap_days == 1 { "day" } else { "days" }
+        ));
+        for (start, end) in &gaps {
+            log(&format!(
+                "  {} → {} ({} days)",
+                start.format("%Y-%m-%d").to_string().bright_cyan(),
+                end.format("%Y-%m-%d").to_string().bright_cyan(),
+                (*end - *start).num_days() + 1
+            ));
+        }
+    }
+    let short_sleep = short_sleep_days(stats, short_sleep_before);
+    if !short_sleep.is_empty() {
+        log(&format!(
+            "{}: {} (after midnight, back before {:02}:00)",
+            "Short-sleep days".yellow(),
+            short_sleep.len().to_string().red(),
+            short_sleep_before
+        ));
+    }
+    let (longest_streak, current_streak, longest_gap) =
+        commit_streaks(&stats.commits_by_date, since, until);
+    log(&format!(
+        "{}: {} {} ({} {}), {}: {} {}",
+        "Longest streak".yellow(),
+        longest_streak.to_string().cyan(),
+        if longest_streak == 1 { "day" } else { "days" },
+        "current".yellow(),
+        current_streak.to_string().cyan(),
+        "longest gap".yellow(),
+        longest_gap.to_string().cyan(),
+        if longest_gap == 1 { "day" } else { "days" }
+    ));
+    log(&format!(
+        "{}: {} {}",
+        "Lines changed".yellow(),
+        format!("+{}", stats.lines_added).green(),
+        format!("-{}", stats.lines_removed).red()
+    ));
+    if show_total_stats {
+        log(&format!(
+            "{}: {}{}",
+            "Total files".yellow(),
+            stats.file_count.to_string().cyan(),
+            binary_files_detail(stats)
+        ));
+        log(&format!(
+            "{}: {} {}",
             "Total lines of code".yellow(),
-            stats.line_count.to_string().cyan()
+            stats.line_kinds.code.to_string().cyan(),
+            line_kinds_detail(&stats.line_kinds)
         ));
+        log(&format!("{}: {}", "Tests".yellow(), tests_detail(stats)));
     }
 
     if show_most_active {
@@ -312,28 +697,1010 @@ pub fn print_total_stats(
     }
 
     // Show total file types if requested
-    if show_filetypes && !stats.file_types.is_empty() {
+    if let (Some(order), false) = (filetypes, stats.file_types.is_empty()) {
         log(&format!(
             "\n{}",
             format!("File types across all {}:", entity_name).bright_magenta()
         ));
-        let mut types: Vec<(String, usize)> = stats
-            .file_types
+        // Show top 10 file types
+        for (language, files, lines) in top_file_types(stats, order, 10) {
+            log(&format!(
+                "  {} - {}",
+                language.bright_yellow(),
+                file_type_detail(files, lines)
+            ));
+        }
+    }
+}
+
+/// Parses the date part (YYYY-MM-DD) of a `--since`/`--until` value.
+fn parse_range_date(value: &str) -> Option<NaiveDate> {
+    value
+        .get(..10)
+        .and_then(|date| NaiveDate::parse_from_str(date, "%Y-%m-%d").ok())
+}
+
+/// Formats the estimated time worked outside working hours, e.g. `~6.5 hours`.
+pub fn gitnapped_hours(minutes: usize) -> String {
+    format!("~{:.1} hours", minutes as f64 / 60.0)
+}
+
+/// Maximum width of the repository column of a table; longer names are shortened.
+const TABLE_NAME_WIDTH: usize = 40;
+
+/// Shortens text to `width` characters by cutting its beginning, which keeps
+/// the most specific part of a path visible.
+fn truncate_start(text: &str, width: usize) -> String {
+    let length = text.chars().count();
+    if length <= width {
+        return text.to_string();
+    }
+    let tail: String = text.chars().skip(length - width + 1).collect();
+    format!("…{}", tail)
+}
+
+/// Prints repositories as an aligned table with commits, gitnapped percentage,
+/// files and lines.
+///
+/// # Arguments
+/// * `repos` - Display name and statistics of each repository, in display order
+/// * `indent` - Number of spaces to prefix every line with
+pub fn print_repo_table(repos: &[(String, &RepoStats)], indent: usize) {
+    let mut table = Table::new();
+    table
+        .load_preset(UTF8_FULL_CONDENSED)
+        .set_content_arrangement(ContentArrangement::Dynamic)
+        .set_header(vec![
+            "#",
+            "Repository",
+            "Commits",
+            "Gitnapped",
+            "Files",
+            "Lines",
+        ]);
+
+    for (i, (name, stats)) in repos.iter().enumerate() {
+        let percentage = if stats.commit_count > 0 {
+            stats.out_of_hours_commits as f32 / stats.commit_count as f32 * 100.0
+        } else {
+            0.0
+        };
+        let gitnapped = Cell::new(format!(
+            "{:.1}% ({}, {})",
+            percentage,
+            stats.out_of_hours_commits,
+            gitnapped_hours(stats.out_of_hours_minutes)
+        ));
+        table.add_row(vec![
+            Cell::new(i + 1),
+            Cell::new(truncate_start(name, TABLE_NAME_WIDTH)).fg(Color::Green),
+            Cell::new(stats.commit_count).fg(Color::Cyan),
+            if stats.out_of_hours_commits > 0 {
+                gitnapped.fg(Color::Red)
+            } else {
+                gitnapped
+            },
+            Cell::new(stats.file_count),
+            Cell::new(stats.line_count),
+        ]);
+    }
+
+    for column in [0, 2, 3, 4, 5] {
+        if let Some(column) = table.column_mut(column) {
+            column.set_cell_alignment(CellAlignment::Right);
+        }
+    }
+
+    let padding = " ".repeat(indent);
+    for line in table.lines() {
+        log(&format!("{}{}", padding, line));
+    }
+}
+
+/// Maximum number of characters of a sparkline; longer periods are bucketed.
+const SPARKLINE_WIDTH: usize = 30;
+
+/// Renders a unicode sparkline of the daily commit counts over the period.
+///
+/// Periods longer than `SPARKLINE_WIDTH` days are split into equal buckets
+/// whose commits are summed.
+///
+/// # Arguments
+/// * `commits_by_date` - Map of dates to number of commits
+/// * `since` - Start of the analyzed period
+/// * `until` - End of the analyzed period
+///
+/// # Returns
+/// * `String` - The sparkline, empty if the period cannot be determined
+pub fn sparkline(commits_by_date: &BTreeMap<String, usize>, since: &str, until: &str) -> String {
+    let start = parse_range_date(since).or_else(|| {
+        commits_by_date
+            .keys()
+            .next()
+            .and_then(|date| parse_range_date(date))
+    });
+    let end = parse_range_date(until).or_else(|| {
+        commits_by_date
+            .keys()
+            .next_back()
+            .and_then(|date| parse_range_date(date))
+    });
+    let (start, end) = match (start, end) {
+        (Some(start), Some(end)) if start <= end => (start, end),
+        _ => return String::new(),
+    };
+
+    let days = (end - start).num_days() as usize + 1;
+    let width = days.min(SPARKLINE_WIDTH);
+    let mut buckets = vec![0; width];
+    for (date, count) in commits_by_date {
+        if let Some(day) = parse_range_date(date) {
+            if day >= start && day <= end {
+                let offset = (day - start).num_days() as usize;
+                buckets[offset * width / days] += count;
+            }
+        }
+    }
+
+    const BARS: [char; 8] = ['▁', '▂', '▃', '▄', '▅', '▆', '▇', '█'];
+    let max_count = buckets.iter().copied().max().unwrap_or(0);
+    buckets
+        .iter()
+        .map(|count| match count {
+            0 => BARS[0],
+            _ => {
+                BARS[1
+                    + (count * (BARS.len() - 2))
+                        .div_ceil(max_count)
+                        .min(BARS.len() - 2)]
+            }
+        })
+        .collect()
+}
+
+/// Prints a GitHub-style contribution calendar of the commits in the period.
+///
+/// Rows are weekdays, columns are weeks. Cell shading grows with the number
+/// of commits; days with out-of-hours commits are drawn in red.
+///
+/// # Arguments
+/// * `stats` - The statistics holding commits and out-of-hours commits per date
+/// * `since` - Start of the analyzed period
+/// * `until` - End of the analyzed period
+/// * `week_start` - The first day of the week, used as the first row
+pub fn print_heatmap(stats: &RepoStats, since: &str, until: &str, week_start: Weekday) {
+    let commit_dates: Vec<NaiveDate> = stats
+        .commits_by_date
+        .keys()
+        .filter_map(|date| NaiveDate::parse_from_str(date, "%Y-%m-%d").ok())
+        .collect();
+
+    let start = parse_range_date(since).or_else(|| commit_dates.first().copied());
+    let end = parse_range_date(until).or_else(|| commit_dates.last().copied());
+    let (start, end) = match (start, end) {
+        (Some(start), Some(end)) if start <= end => (start, end),
+        _ => return,
+    };
+
+    log(&format!("\n{}", "Commit heatmap:".bright_magenta()));
+
+    // Align the grid on the week start so every column is a full week
+    let first_day = start - Duration::days(start.weekday().days_since(week_start) as i64);
+    let weeks = ((end - first_day).num_days() / 7 + 1) as usize;
+    let max_count = stats.commits_by_date.values().copied().max().unwrap_or(0);
+
+    // Month labels above the first week of each month, skipped when they would overlap
+    let month_starts: Vec<usize> = (0..weeks)
+        .filter(|week| (first_day + Duration::days(*week as i64 * 7 + 6)).day() <= 7)
+        .collect();
+    let mut label_weeks = month_starts.clone();
+    if month_starts.first().is_none_or(|week| *week > 3) {
+        label_weeks.insert(0, 0);
+    }
+    let mut header = vec![' '; weeks + 3];
+    let mut next_free = 0;
+    for week in label_weeks {
+        if week < next_free {
+            continue;
+        }
+        let last_day = first_day + Duration::days(week as i64 * 7 + 6);
+        for (offset, c) in last_day.format("%b").to_string().chars().enumerate() {
+            header[week + offset] = c;
+        }
+        next_free = week + 4;
+    }
+    let header: String = header.into_iter().collect();
+    log(&format!("    {}", header.trim_end())
+        .truecolor(128, 128, 128)
+        .to_string());
+
+    for (row, weekday) in week_days(week_start).enumerate() {
+        let mut line = format!("{} ", weekday);
+        for week in 0..weeks {
+            let day = first_day + Duration::days((week * 7 + row) as i64);
+            if day < start || day > end {
+                line.push(' ');
+                continue;
+            }
+
+            let key = day.format("%Y-%m-%d").to_string();
+            let count = stats.commits_by_date.get(&key).copied().unwrap_or(0);
+            let cell = heatmap_cell(count, max_count);
+            if stats.out_of_hours_by_date.get(&key).copied().unwrap_or(0) > 0 {
+                line.push_str(&cell.red().to_string());
+            } else if count > 0 {
+                line.push_str(&cell.green().to_string());
+            } else {
+                line.push_str(&cell.truecolor(128, 128, 128).to_string());
+            }
+        }
+        log(&line);
+    }
+
+    log(&format!(
+        "    Less {} More   {} gitnapped day",
+        ["·", "░", "▒", "▓", "█"].join("").green(),
+        "█".red()
+    ));
+}
+
+/// Iterates over the seven days of a week, from its first day.
+fn week_days(week_start: Weekday) -> impl Iterator<Item = Weekday> {
+    std::iter::successors(Some(week_start), |day| Some(day.succ())).take(7)
+}
+
+/// Picks the shading character of a heatmap cell.
+fn heatmap_cell(count: usize, max_count: usize) -> &'static str {
+    if count == 0 || max_count == 0 {
+        return "·";
+    }
+    match (count * 4).div_ceil(max_count) {
+        0 | 1 => "░",
+        2 => "▒",
+        3 => "▓",
+        _ => "█",
+    }
+}
+
+/// Prints a bar chart of the commits made in each hour of the day.
+///
+/// Hours that start inside the default working window are drawn in green
+/// and marked with `│`, the others in red.
+///
+/// # Arguments
+/// * `stats` - The statistics holding commits per hour
+/// * `working_hours` - Optional working hours to overlay
+pub fn print_hour_histogram(stats: &RepoStats, working_hours: Option<&Schedule>) {
+    log(&format!("\n{}", "Commits by hour of day:".bright_magenta()));
+
+    let max_count = stats.commits_by_hour.iter().copied().max().unwrap_or(0);
+    const BAR_WIDTH: usize = 40;
+
+    for (hour, count) in stats.commits_by_hour.iter().enumerate() {
+        let in_hours = working_hours.is_some_and(|hours| hours.default.contains(hour as u32, 0));
+        let width = if max_count > 0 {
+            (count * BAR_WIDTH).div_ceil(max_count)
+        } else {
+            0
+        };
+        let bar = "█".repeat(width);
+        let bar = if in_hours { bar.green() } else { bar.red() };
+        log(&format!(
+            "{:02}:00 {} {} {}",
+            hour,
+            if in_hours { "│" } else { " " }.bright_blue(),
+            bar,
+            if *count > 0 {
+                count.to_string().cyan()
+            } else {
+                "".normal()
+            }
+        ));
+    }
+}
+
+/// Prints the gitnapped percentage and the commits of every recorded week or
+/// month as bars, the percentage against 100% and the commits against the
+/// busiest period.
+///
+/// # Arguments
+/// * `periods` - The weeks or months, oldest first
+/// * `unit` - What the periods are, `week` or `month`
+pub fn print_snapshot_trend(periods: &[SnapshotPeriod], unit: &str) {
+    log(&format!(
+        "\n{}",
+        format!("Gitnapped by {}:", unit).bright_magenta()
+    ));
+
+    let max_commits = periods
+        .iter()
+        .map(|period| period.commits)
+        .max()
+        .unwrap_or(0);
+    const BAR_WIDTH: usize = 20;
+
+    for period in periods {
+        let percentage = period.gitnapped_percentage();
+        let gitnapped_width = (percentage / 100.0 * BAR_WIDTH as f64).round() as usize;
+        let commits_width = if max_commits > 0 {
+            (period.commits * BAR_WIDTH).div_ceil(max_commits)
+        } else {
+            0
+        };
+        log(&format!(
+            "{:<8} {:<20} {:>6} {:<20} {} {}",
+            period.label.bright_cyan(),
+            "█".repeat(gitnapped_width).red(),
+            format!("{:.1}%", percentage),
+            "█".repeat(commits_width).green(),
+            period.commits.to_string().cyan(),
+            format!(
+                "commits, {} {} recorded",
+                period.days,
+                if period.days == 1 { "day" } else { "days" }
+            )
+            .dimmed()
+        ));
+    }
+}
+
+/// Prints a punch card of the commits: weekdays as rows, hours as columns,
+/// with a bigger dot for more commits. Out-of-hours slots are drawn in red.
+///
+/// # Arguments
+/// * `stats` - The statistics holding the punch card
+/// * `working_hours` - Optional working hours used to color the slots
+/// * `week_start` - The first day of the week, used as the first row
+pub fn print_punch_card(stats: &RepoStats, working_hours: Option<&Schedule>, week_start: Weekday) {
+    log(&format!("\n{}", "Punch card:".bright_magenta()));
+
+    let max_count = stats
+        .punch_card
+        .iter()
+        .flat_map(|hours| hours.iter().copied())
+        .max()
+        .unwrap_or(0);
+
+    let header: String = (0..24).map(|hour| format!("{:>3}", hour)).collect();
+    log(&format!("   {}", header)
+        .truecolor(128, 128, 128)
+        .to_string());
+
+    for day in week_days(week_start) {
+        let weekday = day.num_days_from_monday() as usize;
+        let hours = &stats.punch_card[weekday];
+        let mut line = day.to_string();
+        for (hour, count) in hours.iter().enumerate() {
+            let dot = if *count == 0 {
+                "·"
+            } else {
+                match (*count * 4).div_ceil(max_count) {
+                    0 | 1 => "•",
+                    2 => "●",
+                    _ => "⬤",
+                }
+            };
+            let in_hours =
+                working_hours.is_some_and(|hours| hours.contains(weekday, hour as u32, 0));
+            let cell = format!("{:>3}", dot);
+            line.push_str(&if *count == 0 {
+                cell.truecolor(128, 128, 128).to_string()
+            } else if in_hours || working_hours.is_none() {
+                cell.green().to_string()
+            } else {
+                cell.red().to_string()
+            });
+        }
+        log(&line);
+    }
+}
+
+/// Prints commits, out-of-hours commits and gitnapped percentage of every
+/// author, for each active repository and overall.
+///
+/// # Arguments
+/// * `repos` - Display name and statistics of each repository
+/// * `total` - The aggregated statistics of all repositories
+pub fn print_author_breakdown(repos: &[(String, &RepoStats)], total: &RepoStats) {
+    log(&format!("\n{}", "Commits by author:".bright_magenta()));
+
+    for (name, stats) in repos.iter().filter(|(_, stats)| stats.commit_count > 0) {
+        log(&format!("\n{} {}", "Repo:".bright_blue(), name.green()));
+        print_author_lines(stats, "   ");
+    }
+
+    log(&format!("\n{}", "All repositories:".bright_blue()));
+    print_author_lines(total, "   ");
+}
+
+/// Prints one line per author of the statistics, most commits first.
+fn print_author_lines(stats: &RepoStats, indent: &str) {
+    let mut authors: Vec<(&String, &AuthorStats)> = stats.authors.iter().collect();
+    authors.sort_by_key(|(_, author)| Reverse(author.commit_count));
+
+    for (i, (author, author_stats)) in authors.iter().enumerate() {
+        let percentage = if author_stats.commit_count > 0 {
+            (author_stats.out_of_hours_commits as f32 / author_stats.commit_count as f32 * 100.0)
+                as u32
+        } else {
+            0
+        };
+        log(&format!(
+            "{}{}. {} - {} commits [{}: {}% ({})]",
+            indent,
+            (i + 1).to_string().bright_yellow(),
+            author.green(),
+            author_stats.commit_count.to_string().cyan(),
+            "Gitnapped for".yellow(),
+            percentage.to_string().red(),
+            author_stats.out_of_hours_commits.to_string().red()
+        ));
+    }
+}
+
+/// Prints the authors ranked by their share of out-of-hours commits, then by
+/// the estimated time worked outside working hours.
+///
+/// # Arguments
+/// * `stats` - The aggregated statistics of all repositories
+/// * `min_commits` - Authors with fewer commits are left out of the ranking
+pub fn print_leaderboard(stats: &RepoStats, min_commits: usize) {
+    let mut authors: Vec<(&String, &AuthorStats, f64)> = stats
+        .authors
+        .iter()
+        .filter(|(_, author)| author.commit_count >= min_commits && author.commit_count > 0)
+        .map(|(name, author)| {
+            let percentage =
+                author.out_of_hours_commits as f64 / author.commit_count as f64 * 100.0;
+            (name, author, percentage)
+        })
+        .collect();
+    authors.sort_by(|a, b| {
+        b.2.total_cmp(&a.2)
+            .then(b.1.out_of_hours_minutes.cmp(&a.1.out_of_hours_minutes))
+    });
+
+    log(&format!(
+        "\n{} {}",
+        "Gitnapped leaderboard".bright_magenta(),
+        format!("(at least {} commits)", min_commits).truecolor(128, 128, 128)
+    ));
+
+    if authors.is_empty() {
+        log(&format!(
+            "   {}",
+            "No author has enough commits in this period".yellow()
+        ));
+        return;
+    }
+
+    for (i, (name, author, percentage)) in authors.iter().enumerate() {
+        log(&format!(
+            "   {}. {} - {}% of {} commits, {}",
+            (i + 1).to_string().bright_yellow(),
+            name.green(),
+            format!("{:.0}", percentage).red(),
+            author.commit_count.to_string().cyan(),
+            gitnapped_hours(author.out_of_hours_minutes).red()
+        ));
+    }
+
+    let skipped = stats.authors.len() - authors.len();
+    if skipped > 0 {
+        log(&format!(
+            "   {}",
+            format!("{} authors with fewer commits not ranked", skipped).truecolor(128, 128, 128)
+        ));
+    }
+}
+
+/// Upper bounds (inclusive) of the buckets of the commit size histogram, the last one open ended.
+const COMMIT_SIZE_BUCKETS: [(usize, &str); 6] = [
+    (0, "0"),
+    (10, "1-10"),
+    (50, "11-50"),
+    (200, "51-200"),
+    (1000, "201-1000"),
+    (usize::MAX, ">1000"),
+];
+
+/// Prints the distribution of the lines changed per commit, for each active
+/// repository and overall, with out-of-hours commits in red.
+///
+/// # Arguments
+/// * `repos` - Display name and statistics of each repository
+/// * `total` - The aggregated statistics of all repositories
+pub fn print_commit_sizes(repos: &[(String, &RepoStats)], total: &RepoStats) {
+    log(&format!(
+        "\n{}",
+        "Lines changed per commit:".bright_magenta()
+    ));
+
+    for (name, stats) in repos.iter().filter(|(_, stats)| stats.commit_count > 0) {
+        log(&format!("\n{} {}", "Repo:".bright_blue(), name.green()));
+        print_commit_size_lines(stats);
+    }
+
+    log(&format!("\n{}", "All repositories:".bright_blue()));
+    print_commit_size_lines(total);
+}
+
+/// Prints the summary and histogram of the commit sizes of the statistics.
+fn print_commit_size_lines(stats: &RepoStats) {
+    let Some((min, median, mean, max)) = commit_size_summary(&stats.commit_sizes) else {
+        log(&format!("   {}", "No commits".yellow()));
+        return;
+    };
+    log(&format!(
+        "   min {}, median {}, mean {}, max {}",
+        min.to_string().cyan(),
+        median.to_string().cyan(),
+        format!("{:.1}", mean).cyan(),
+        max.to_string().cyan()
+    ));
+    if let Some((_, median, _, max)) = commit_size_summary(&stats.out_of_hours_commit_sizes) {
+        log(&format!(
+            "   {}: median {}, max {}",
+            "Out of hours".yellow(),
+            median.to_string().red(),
+            max.to_string().red()
+        ));
+    }
+
+    let bucket = |size: &usize| {
+        COMMIT_SIZE_BUCKETS
             .iter()
-            .map(|(k, v)| (k.clone(), *v))
-            .collect();
+            .position(|(bound, _)| size <= bound)
+            .unwrap_or(COMMIT_SIZE_BUCKETS.len() - 1)
+    };
+    let mut counts = [0usize; COMMIT_SIZE_BUCKETS.len()];
+    let mut night_counts = [0usize; COMMIT_SIZE_BUCKETS.len()];
+    for size in &stats.commit_sizes {
+        counts[bucket(size)] += 1;
+    }
+    for size in &stats.out_of_hours_commit_sizes {
+        night_counts[bucket(size)] += 1;
+    }
 
-        // Sort by count (descending)
-        types.sort_by(|a, b| b.1.cmp(&a.1));
+    let max_count = counts.iter().copied().max().unwrap_or(0);
+    const BAR_WIDTH: usize = 30;
+    for (i, (_, label)) in COMMIT_SIZE_BUCKETS.iter().enumerate() {
+        let width = (counts[i] * BAR_WIDTH).div_ceil(max_count.max(1));
+        let night_width = (night_counts[i] * BAR_WIDTH).div_ceil(max_count.max(1));
+        log(&format!(
+            "   {:>9} {}{} {}",
+            label,
+            "█".repeat(night_width).red(),
+            "█".repeat(width.saturating_sub(night_width)).green(),
+            if counts[i] > 0 {
+                counts[i].to_string().cyan()
+            } else {
+                "".normal()
+            }
+        ));
+    }
+}
 
-        // Show top 10 file types
-        for (ext, count) in types.iter().take(10) {
-            log(&format!(
-                "  {} - {} {}",
-                ext.bright_yellow(),
-                count,
-                "files".green()
+/// Output formats supported by `--format`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OutputFormat {
+    /// Colored, human readable text
+    Text,
+    /// Text using vanity names and gitnapped percentages
+    Pretty,
+    /// A single JSON document
+    Json,
+    /// One JSON object per repository, streamed while analyzing
+    Jsonl,
+    /// Comma separated values, one row per repository
+    Csv,
+    /// Markdown tables
+    Md,
+    /// Standalone HTML page with charts
+    Html,
+    /// A single YAML document
+    Yaml,
+    /// A few emoji-decorated lines for a quick glance
+    Summary,
+    /// InfluxDB line protocol, one point per repository and day
+    Influx,
+}
+
+impl OutputFormat {
+    /// Names accepted by `--format`.
+    pub const NAMES: [&'static str; 9] = [
+        "text", "pretty", "json", "jsonl", "csv", "md", "html", "yaml", "influx",
+    ];
+
+    /// Parses a format name as accepted by `--format`.
+    pub fn from_name(name: &str) -> Option<OutputFormat> {
+        match name {
+            "text" => Some(OutputFormat::Text),
+            "pretty" => Some(OutputFormat::Pretty),
+            "json" => Some(OutputFormat::Json),
+            "jsonl" => Some(OutputFormat::Jsonl),
+            "csv" => Some(OutputFormat::Csv),
+            "md" | "markdown" => Some(OutputFormat::Md),
+            "html" => Some(OutputFormat::Html),
+            "yaml" | "yml" => Some(OutputFormat::Yaml),
+            "summary" => Some(OutputFormat::Summary),
+            "influx" => Some(OutputFormat::Influx),
+            _ => None,
+        }
+    }
+
+    /// Whether the format is printed line by line through `log` rather than rendered from a `Report`.
+    pub fn is_text(self) -> bool {
+        matches!(self, OutputFormat::Text | OutputFormat::Pretty)
+    }
+}
+
+/// Turns a complete `Report` into its final textual representation.
+///
+/// Adding an output format only requires a new implementation and a match
+/// arm in `renderer_for`.
+pub trait Renderer {
+    /// Renders the report.
+    ///
+    /// # Returns
+    /// * `Result<String, String>` - The rendered output, or an error message
+    fn render(&self, report: &Report) -> Result<String, String>;
+}
+
+/// Renders the report as JSON.
+pub struct JsonRenderer {
+    /// Whether to indent the JSON for human readers
+    pub pretty: bool,
+}
+
+impl Renderer for JsonRenderer {
+    fn render(&self, report: &Report) -> Result<String, String> {
+        if self.pretty {
+            serde_json::to_string_pretty(report)
+        } else {
+            serde_json::to_string(report)
+        }
+        .map_err(|err| format!("Failed to serialize report to JSON: {}", err))
+    }
+}
+
+/// Renders the report as YAML.
+pub struct YamlRenderer;
+
+impl Renderer for YamlRenderer {
+    fn render(&self, report: &Report) -> Result<String, String> {
+        serde_yaml::to_string(report)
+            .map_err(|err| format!("Failed to serialize report to YAML: {}", err))
+    }
+}
+
+/// Quotes a CSV field when it contains separators, quotes or line breaks.
+pub(crate) fn csv_field(value: &str) -> String {
+    if value.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
+
+/// Renders the per-repository statistics as CSV.
+pub struct CsvRenderer;
+
+impl Renderer for CsvRenderer {
+    fn render(&self, report: &Report) -> Result<String, String> {
+        let mut csv = String::from(
+            "path,name,group,commits,out_of_hours_commits,gitnapped_percentage,files,lines,lines_added,lines_removed\n",
+        );
+        for repo in &report.repos {
+            csv.push_str(&format!(
+                "{},{},{},{},{},{},{},{},{},{}\n",
+                csv_field(&repo.path),
+                csv_field(&repo.name),
+                csv_field(repo.group.as_deref().unwrap_or("")),
+                repo.stats.commit_count,
+                repo.stats.out_of_hours_commits,
+                repo.gitnapped_percentage,
+                repo.stats.file_count,
+                repo.stats.line_count,
+                repo.stats.lines_added,
+                repo.stats.lines_removed
+            ));
+        }
+        Ok(csv)
+    }
+}
+
+/// Escapes the characters that would break a tag of the InfluxDB line protocol.
+fn influx_tag(value: &str) -> String {
+    value
+        .replace(',', "\\,")
+        .replace('=', "\\=")
+        .replace(' ', "\\ ")
+}
+
+/// Renders the report in the InfluxDB line protocol: a `gitnapped` point per
+/// repository and day with commits, at local midnight, tagged with the
+/// repository and its category, e.g. for Telegraf or `influx write`.
+pub struct InfluxRenderer;
+
+impl Renderer for InfluxRenderer {
+    fn render(&self, report: &Report) -> Result<String, String> {
+        let mut lines = Vec::new();
+        for repo in &report.repos {
+            let mut tags = format!(
+                "repo={},path={}",
+                influx_tag(&repo.name),
+                influx_tag(&repo.path)
+            );
+            if let Some(group) = &repo.group {
+                tags.push_str(&format!(",category={}", influx_tag(group)));
+            }
+
+            let stats = &repo.stats;
+            for (date, commits) in &stats.commits_by_date {
+                let midnight = NaiveDate::parse_from_str(date, "%Y-%m-%d")
+                    .ok()
+                    .and_then(|day| {
+                        Local
+                            .from_local_datetime(&day.and_time(NaiveTime::MIN))
+                            .earliest()
+                    })
+                    .and_then(|midnight| midnight.timestamp_nanos_opt());
+                let Some(midnight) = midnight else {
+                    continue;
+                };
+                let count =
+                    |by_date: &BTreeMap<String, usize>| by_date.get(date).copied().unwrap_or(0);
+                lines.push(format!(
+                    "gitnapped,{} commits={}i,out_of_hours_commits={}i,lines_added={}i,lines_removed={}i {}",
+                    tags,
+                    commits,
+                    count(&stats.out_of_hours_by_date),
+                    count(&stats.lines_added_by_date),
+                    count(&stats.lines_removed_by_date),
+                    midnight
+                ));
+            }
+        }
+        Ok(lines.join("\n"))
+    }
+}
+
+/// Escapes the characters that would break a Markdown table cell.
+/// Escapes the pipes that would end a cell of a Markdown table.
+pub(crate) fn md_cell(value: &str) -> String {
+    value.replace('|', "\\|")
+}
+
+/// Renders the report as Markdown tables.
+pub struct MarkdownRenderer;
+
+impl Renderer for MarkdownRenderer {
+    fn render(&self, report: &Report) -> Result<String, String> {
+        let mut md = String::from("# Gitnapped report\n\n");
+        md.push_str(&format!(
+            "- **Period:** {} – {}\n- **Author:** {}\n- **Working hours:** {}\n\n",
+            report.since,
+            report.until,
+            report.author.as_deref().unwrap_or("all authors"),
+            report.working_time
+        ));
+
+        md.push_str("## Totals\n\n| Metric | Value |\n| --- | ---: |\n");
+        md.push_str(&format!(
+            "| Active repositories | {} |\n",
+            report.totals.active_repos
+        ));
+        md.push_str(&format!(
+            "| Commits | {} |\n",
+            report.totals.stats.commit_count
+        ));
+        md.push_str(&format!(
+            "| Gitnapped | {}% ({}) |\n",
+            report.totals.gitnapped_percentage, report.totals.stats.out_of_hours_commits
+        ));
+        if let Some(day) = &report.totals.most_active_day {
+            md.push_str(&format!(
+                "| Most active day | {} ({} commits) |\n",
+                day.date, day.commits
             ));
         }
+
+        if !report.projects.is_empty() {
+            md.push_str("\n## Projects\n\n| Project | Group | Repos | Commits | Gitnapped |\n| --- | --- | ---: | ---: | ---: |\n");
+            for project in &report.projects {
+                md.push_str(&format!(
+                    "| {} | {} | {} | {} | {}% |\n",
+                    md_cell(&project.name),
+                    md_cell(project.group.as_deref().unwrap_or("")),
+                    project.repos.len(),
+                    project.stats.commit_count,
+                    project.gitnapped_percentage
+                ));
+            }
+        }
+
+        md.push_str("\n## Repositories\n\n| Repository | Path | Commits | Gitnapped | Files | Lines | Changed |\n| --- | --- | ---: | ---: | ---: | ---: | ---: |\n");
+        for repo in &report.repos {
+            md.push_str(&format!(
+                "| {} | `{}` | {} | {}% | {} | {} | +{} -{} |\n",
+                md_cell(&repo.name),
+                repo.path,
+                repo.stats.commit_count,
+                repo.gitnapped_percentage,
+                repo.stats.file_count,
+                repo.stats.line_count,
+                repo.stats.lines_added,
+                repo.stats.lines_removed
+            ));
+        }
+
+        Ok(md)
+    }
+}
+
+/// Renders a compact, emoji-decorated summary meant for a quick glance or a
+/// standup message.
+pub struct SummaryRenderer;
+
+impl Renderer for SummaryRenderer {
+    fn render(&self, report: &Report) -> Result<String, String> {
+        let totals = &report.totals;
+        let mut lines = vec![format!(
+            "📊 {} commits in {} active repos ({} → {})",
+            totals.stats.commit_count, totals.active_repos, report.since, report.until
+        )];
+        lines.push(format!(
+            "🌙 Gitnapped: {}% ({} commits outside {}, {})",
+            totals.gitnapped_percentage,
+            totals.stats.out_of_hours_commits,
+            report.working_time,
+            gitnapped_hours(totals.stats.out_of_hours_minutes)
+        ));
+        if let Some(repo) = report
+            .repos
+            .iter()
+            .filter(|repo| repo.stats.commit_count > 0)
+            .max_by_key(|repo| repo.stats.commit_count)
+        {
+            lines.push(format!(
+                "🔥 Most active: {} ({} commits)",
+                repo.name, repo.stats.commit_count
+            ));
+        }
+        if !totals.short_sleep_days.is_empty() {
+            lines.push(format!(
+                "😴 Short-sleep days: {}",
+                totals.short_sleep_days.len()
+            ));
+        }
+        if let Some(trend) = &report.trend {
+            let changes: Vec<String> = trend
+                .metrics
+                .iter()
+                .filter(|metric| ["commits", "gitnapped"].contains(&metric.name.as_str()))
+                .map(|metric| format!("{} {}", metric.name, metric.change()))
+                .collect();
+            lines.push(format!("📈 vs previous period: {}", changes.join(", ")));
+        }
+        if let Some(day) = &totals.most_active_day {
+            lines.push(format!(
+                "📅 Most active day: {} ({} commits)",
+                day.date, day.commits
+            ));
+        }
+        Ok(lines.join("\n"))
+    }
+}
+
+/// Standalone HTML report, rendered from `templates/report.html`.
+#[derive(Template)]
+#[template(path = "report.html")]
+struct HtmlReport<'a> {
+    report: &'a Report,
+    /// The report serialized as JSON, embedded in the page for the charts
+    data: String,
+    version: &'a str,
+}
+
+/// Renders the report as a self-contained HTML page with charts.
+pub struct HtmlRenderer;
+
+impl Renderer for HtmlRenderer {
+    fn render(&self, report: &Report) -> Result<String, String> {
+        let data = serde_json::to_string(report)
+            .map_err(|err| format!("Failed to serialize report to JSON: {}", err))?
+            // Keep the embedded JSON from closing the surrounding <script> tag
+            .replace('<', "\\u003c");
+
+        HtmlReport {
+            report,
+            data,
+            version: env!("CARGO_PKG_VERSION"),
+        }
+        .render()
+        .map_err(|err| format!("Failed to render HTML report: {}", err))
+    }
+}
+
+/// Renders the report through a user-provided Tera template.
+///
+/// The template receives the report fields (`since`, `until`, `author`,
+/// `working_time`, `totals`, `repos`, `categories`, `projects`) plus `version`.
+pub struct TemplateRenderer {
+    /// Path to the template file
+    pub path: String,
+}
+
+impl Renderer for TemplateRenderer {
+    fn render(&self, report: &Report) -> Result<String, String> {
+        let template = fs::read_to_string(&self.path)
+            .map_err(|err| format!("Error reading template file '{}': {}", self.path, err))?;
+
+        let mut context = tera::Context::from_serialize(report)
+            .map_err(|err| format!("Failed to build template context: {}", err))?;
+        context.insert("version", env!("CARGO_PKG_VERSION"));
+
+        tera::Tera::one_off(&template, &context, false).map_err(|err| {
+            // Tera keeps the useful part of the message in the error source
+            let detail = std::error::Error::source(&err)
+                .map(|source| source.to_string())
+                .unwrap_or_default();
+            format!(
+                "Failed to render template '{}': {} {}",
+                self.path, err, detail
+            )
+        })
+    }
+}
+
+/// Returns the renderer for a report-based output format.
+///
+/// # Arguments
+/// * `format` - The requested output format
+/// * `pretty` - Whether human readable indentation was requested
+///
+/// # Returns
+/// * `Option<Box<dyn Renderer>>` - The renderer, or None for formats that are
+///   printed incrementally (text, pretty and jsonl)
+pub fn renderer_for(format: OutputFormat, pretty: bool) -> Option<Box<dyn Renderer>> {
+    match format {
+        OutputFormat::Text | OutputFormat::Pretty | OutputFormat::Jsonl => None,
+        OutputFormat::Json => Some(Box::new(JsonRenderer { pretty })),
+        OutputFormat::Csv => Some(Box::new(CsvRenderer)),
+        OutputFormat::Md => Some(Box::new(MarkdownRenderer)),
+        OutputFormat::Html => Some(Box::new(HtmlRenderer)),
+        OutputFormat::Yaml => Some(Box::new(YamlRenderer)),
+        OutputFormat::Summary => Some(Box::new(SummaryRenderer)),
+        OutputFormat::Influx => Some(Box::new(InfluxRenderer)),
+    }
+}
+
+/// Prints a single repository as one line of JSON (JSON Lines).
+///
+/// Used to stream results while the analysis is still running.
+///
+/// # Arguments
+/// * `repo` - The repository entry to print
+pub fn print_jsonl_repo(repo: &RepoReport) -> Result<(), String> {
+    let line = serde_json::to_string(repo)
+        .map_err(|err| format!("Failed to serialize repository to JSON: {}", err))?;
+    println!("{}", line);
+    Ok(())
+}
+
+/// Writes rendered output to a file, or to standard output if no file is given.
+///
+/// # Arguments
+/// * `content` - The rendered output
+/// * `output_path` - Optional destination file
+pub fn write_output(content: &str, output_path: Option<&str>) -> Result<(), String> {
+    match output_path {
+        Some(path) => fs::write(path, content)
+            .map_err(|err| format!("Failed to write output file '{}': {}", path, err)),
+        None => {
+            println!("{}", content);
+            Ok(())
+        }
     }
 }
@@ -0,0 +1,89 @@
+use crate::models::EmailConfig;
+use crate::utils::debug;
+use lettre::message::header::ContentType;
+use lettre::message::Mailbox;
+use lettre::transport::smtp::authentication::Credentials;
+use lettre::{Message, SmtpTransport, Transport};
+use std::env;
+
+/// Environment variable holding the SMTP password when the config names none.
+pub const DEFAULT_PASSWORD_VARIABLE: &str = "GITNAPPED_SMTP_PASSWORD";
+
+/// Sends a rendered report by email to the recipients of the config.
+///
+/// # Arguments
+/// * `settings` - The SMTP server, sender and recipients
+/// * `subject` - Subject of the email, unless the config sets one
+/// * `body` - The rendered report
+/// * `html` - Whether the report is an HTML page rather than plain text, e.g. Markdown
+///
+/// # Returns
+/// * `Result<(), String>` - An error telling why the email was not sent
+pub fn send_report(
+    settings: &EmailConfig,
+    subject: &str,
+    body: String,
+    html: bool,
+) -> Result<(), String> {
+    let mailbox = |address: &str| {
+        address
+            .parse::<Mailbox>()
+            .map_err(|err| format!("Invalid email address '{}': {}", address, err))
+    };
+    if settings.to.is_empty() {
+        return Err(String::from(
+            "No recipient in the email settings of the config",
+        ));
+    }
+
+    let mut message = Message::builder()
+        .from(mailbox(&settings.from)?)
+        .subject(settings.subject.as_deref().unwrap_or(subject));
+    for recipient in &settings.to {
+        message = message.to(mailbox(recipient)?);
+    }
+    let message = message
+        .header(if html {
+            ContentType::TEXT_HTML
+        } else {
+            ContentType::TEXT_PLAIN
+        })
+        .body(body)
+        .map_err(|err| format!("Failed to build the email: {}", err))?;
+
+    let host = settings.smtp_host.as_str();
+    let (transport, port) = match settings.tls.as_deref().unwrap_or("starttls") {
+        "starttls" => (SmtpTransport::starttls_relay(host), 587),
+        "tls" => (SmtpTransport::relay(host), 465),
+        "none" => (Ok(SmtpTransport::builder_dangerous(host)), 25),
+        other => {
+            return Err(format!(
+                "Unknown email tls mode '{}', expected starttls, tls or none",
+                other
+            ))
+        }
+    };
+    let mut transport = transport
+        .map_err(|err| format!("Invalid SMTP server {}: {}", host, err))?
+        .port(settings.smtp_port.unwrap_or(port));
+    if let Some(username) = &settings.username {
+        let variable = settings
+            .password_env
+            .as_deref()
+            .unwrap_or(DEFAULT_PASSWORD_VARIABLE);
+        let password = env::var(variable)
+            .map_err(|_| format!("Set the SMTP password of {} in ${}", username, variable))?;
+        transport = transport.credentials(Credentials::new(username.clone(), password));
+    }
+
+    debug(&format!(
+        "Sending the report to {} through {}",
+        settings.to.join(", "),
+        host
+    ));
+    transport
+        .build()
+        .send(&message)
+        .map(|_| ())
+        .map_err(|err| format!("Failed to send the email through {}: {}", host, err))
+}
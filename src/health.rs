@@ -0,0 +1,111 @@
+use crate::models::RepoStats;
+use crate::utils::commit_streaks;
+use serde::Serialize;
+
+/// Maximum points taken off the score by each factor.
+const OUT_OF_HOURS_WEIGHT: f64 = 40.0;
+const WEEKEND_WEIGHT: f64 = 20.0;
+const AFTER_MIDNIGHT_WEIGHT: f64 = 25.0;
+const STREAK_WEIGHT: f64 = 15.0;
+
+/// Streaks up to this many days are considered healthy.
+const HEALTHY_STREAK_DAYS: usize = 5;
+
+/// Points taken off the score for every day of streak beyond the healthy length.
+const STREAK_PENALTY_PER_DAY: f64 = 1.5;
+
+/// Commits from midnight until this hour count as after-midnight commits.
+const AFTER_MIDNIGHT_UNTIL_HOUR: usize = 6;
+
+/// A factor lowering the work-health score.
+#[derive(Debug, Clone, Serialize)]
+pub struct HealthFactor {
+    /// Name of the factor, e.g. `weekend commits`
+    pub name: String,
+    /// Human readable measure of the factor, e.g. `12% of commits`
+    pub detail: String,
+    /// Points taken off the score
+    pub penalty: u32,
+}
+
+/// A 0-100 work-health score, 100 meaning no sign of overwork.
+#[derive(Debug, Clone, Serialize)]
+pub struct HealthScore {
+    /// The score, from 0 (burning out) to 100 (healthy)
+    pub score: u32,
+    /// The factors that lowered the score, biggest penalty first
+    pub factors: Vec<HealthFactor>,
+}
+
+/// Computes the work-health score of a period out of the share of gitnapped,
+/// weekend and after-midnight commits and the longest streak of days with commits.
+///
+/// # Arguments
+/// * `stats` - The statistics of the period
+/// * `since` - Start of the period
+/// * `until` - End of the period
+///
+/// # Returns
+/// * `HealthScore` - The score and the factors that lowered it
+pub fn health_score(stats: &RepoStats, since: &str, until: &str) -> HealthScore {
+    let share = |count: usize| {
+        if stats.commit_count > 0 {
+            count as f64 / stats.commit_count as f64
+        } else {
+            0.0
+        }
+    };
+    let weekend_commits: usize = stats.punch_card[5..]
+        .iter()
+        .flat_map(|hours| hours.iter())
+        .sum();
+    let after_midnight_commits: usize = stats.commits_by_hour[..AFTER_MIDNIGHT_UNTIL_HOUR]
+        .iter()
+        .sum();
+    let (longest_streak, _, _) = commit_streaks(&stats.commits_by_date, since, until);
+
+    let mut factors = Vec::new();
+    let mut add = |name: &str, detail: String, penalty: f64| {
+        let penalty = penalty.round() as u32;
+        if penalty > 0 {
+            factors.push(HealthFactor {
+                name: name.to_string(),
+                detail,
+                penalty,
+            });
+        }
+    };
+
+    let out_of_hours = share(stats.out_of_hours_commits);
+    add(
+        "gitnapped commits",
+        format!("{:.0}% of commits", out_of_hours * 100.0),
+        out_of_hours * OUT_OF_HOURS_WEIGHT,
+    );
+    let weekend = share(weekend_commits);
+    add(
+        "weekend commits",
+        format!("{:.0}% of commits", weekend * 100.0),
+        weekend * WEEKEND_WEIGHT,
+    );
+    let after_midnight = share(after_midnight_commits);
+    add(
+        "after-midnight commits",
+        format!("{:.0}% of commits", after_midnight * 100.0),
+        after_midnight * AFTER_MIDNIGHT_WEIGHT,
+    );
+    add(
+        "long streak",
+        format!("{} days in a row", longest_streak),
+        (longest_streak.saturating_sub(HEALTHY_STREAK_DAYS) as f64 * STREAK_PENALTY_PER_DAY)
+            .min(STREAK_WEIGHT),
+    );
+
+    factors.sort_by_key(|factor| std::cmp::Reverse(factor.penalty));
+    let penalty: u32 = factors.iter().map(|factor| factor.penalty).sum();
+
+    HealthScore {
+        score: 100u32.saturating_sub(penalty),
+        factors,
+    }
+}
@@ -0,0 +1,30 @@
+use crate::utils::debug;
+use std::env;
+
+/// Environment variable holding the API token sent to InfluxDB, if any.
+pub const TOKEN_VARIABLE: &str = "INFLUX_TOKEN";
+
+/// Writes points in the line protocol to InfluxDB over HTTP.
+///
+/// # Arguments
+/// * `url` - The write endpoint, e.g. `http://localhost:8086/api/v2/write?org=me&bucket=dev`
+///   for InfluxDB 2 or `http://localhost:8086/write?db=dev` for InfluxDB 1
+/// * `lines` - The points, one per line, timestamps in nanoseconds
+///
+/// # Returns
+/// * `Result<(), String>` - An error telling why the points were not written
+pub fn write_points(url: &str, lines: &str) -> Result<(), String> {
+    let mut request = ureq::post(url).header("Content-Type", "text/plain; charset=utf-8");
+    if let Ok(token) = env::var(TOKEN_VARIABLE) {
+        request = request.header("Authorization", &format!("Token {}", token));
+    }
+    debug(&format!(
+        "Writing {} points to {}",
+        lines.lines().count(),
+        url
+    ));
+    request
+        .send(lines)
+        .map(|_| ())
+        .map_err(|err| format!("Failed to write the points to {}: {}", url, err))
+}
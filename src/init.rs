@@ -0,0 +1,147 @@
+use crate::discovery::find_repositories;
+use crate::models::RepoConfig;
+use crate::utils::{debug, git_config_author};
+use colored::*;
+use serde::Serialize;
+use std::collections::BTreeMap;
+use std::fs;
+use std::io::{self, BufRead, Write};
+use std::path::Path;
+use std::process::Command;
+
+/// The config written by `gitnapped init`.
+#[derive(Serialize)]
+struct StarterConfig {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    author: Option<String>,
+    repos: BTreeMap<String, Vec<RepoConfig>>,
+}
+
+/// Scans a directory tree for Git repositories and writes a starter config
+/// listing them, in TOML when the file has a `.toml` extension and in YAML
+/// otherwise.
+///
+/// Repositories are named after their `origin` remote, or their folder when
+/// they have none, and put in a category named after the first folder below
+/// `root` holding them, unless the categories are asked for one by one.
+///
+/// # Arguments
+/// * `root` - The directory to scan
+/// * `output` - The config file to write
+/// * `max_depth` - How many directory levels below `root` are scanned
+/// * `interactive` - Whether to ask for the category of every repository
+/// * `force` - Whether to overwrite an existing config file
+///
+/// # Returns
+/// * `Result<usize, String>` - Number of repositories written, or an error
+pub fn write_starter_config(
+    root: &str,
+    output: &str,
+    max_depth: usize,
+    interactive: bool,
+    force: bool,
+) -> Result<usize, String> {
+    if Path::new(output).exists() && !force {
+        return Err(format!(
+            "'{}' already exists, use --force to overwrite it",
+            output
+        ));
+    }
+    let root = fs::canonicalize(root).map_err(|err| format!("Cannot read '{}': {}", root, err))?;
+    let repo_paths = find_repositories(&root, max_depth);
+    if repo_paths.is_empty() {
+        return Err(format!("No Git repository found in '{}'", root.display()));
+    }
+
+    let mut repos: BTreeMap<String, Vec<RepoConfig>> = BTreeMap::new();
+    let mut stdin = io::stdin().lock();
+    for repo in &repo_paths {
+        let path = repo.to_string_lossy().into_owned();
+        let name = remote_name(&path).unwrap_or_else(|| folder_name(repo));
+
+        let mut category = default_category(&root, repo);
+        if interactive {
+            print!(
+                "{} {} ({}) [{}]: ",
+                "Category of".bright_yellow(),
+                name.bright_cyan(),
+                path,
+                category
+            );
+            io::stdout().flush().map_err(|err| err.to_string())?;
+            let mut answer = String::new();
+            stdin
+                .read_line(&mut answer)
+                .map_err(|err| err.to_string())?;
+            if !answer.trim().is_empty() {
+                category = answer.trim().to_string();
+            }
+        }
+
+        repos.entry(category.clone()).or_default().push(RepoConfig {
+            path,
+            name: Some(name),
+            category: Some(category),
+            tags: Vec::new(),
+            working_hours: None,
+            exclude: Vec::new(),
+        });
+    }
+
+    let config = StarterConfig {
+        author: git_config_author(&root.to_string_lossy()),
+        repos,
+    };
+    let is_toml = Path::new(output)
+        .extension()
+        .is_some_and(|extension| extension.eq_ignore_ascii_case("toml"));
+    let content = if is_toml {
+        toml::to_string(&config).map_err(|err| err.to_string())?
+    } else {
+        serde_yaml::to_string(&config).map_err(|err| err.to_string())?
+    };
+    let content = format!(
+        "# Written by gitnapped init, see the README for the other settings\n{}",
+        content
+    );
+    fs::write(output, content).map_err(|err| format!("Cannot write '{}': {}", output, err))?;
+
+    Ok(repo_paths.len())
+}
+
+/// Names a repository after its `origin` remote, e.g. `gitnapped` for
+/// `git@github.com:solexma/gitnapped.git`.
+fn remote_name(repo: &str) -> Option<String> {
+    let output = Command::new("git")
+        .args(["-C", repo, "remote", "get-url", "origin"])
+        .output()
+        .ok()?;
+    if !output.status.success() {
+        debug(&format!("No origin remote in {}", repo));
+        return None;
+    }
+
+    let url = String::from_utf8_lossy(&output.stdout).trim().to_string();
+    let url = url.trim_end_matches('/');
+    let name = url.rsplit(['/', ':']).next()?;
+    let name = name.strip_suffix(".git").unwrap_or(name);
+    (!name.is_empty()).then(|| name.to_string())
+}
+
+/// Returns the name of the folder of a path.
+fn folder_name(path: &Path) -> String {
+    path.file_name()
+        .map(|name| name.to_string_lossy().into_owned())
+        .unwrap_or_else(|| path.to_string_lossy().into_owned())
+}
+
+/// Returns the category of a repository: the first folder below the scanned
+/// directory holding it, e.g. `clients` for `~/code/clients/acme` when
+/// scanning `~/code`, or the scanned directory for its direct children.
+fn default_category(root: &Path, repo: &Path) -> String {
+    repo.parent()
+        .and_then(|parent| parent.strip_prefix(root).ok())
+        .and_then(|relative| relative.components().next())
+        .map(|folder| folder.as_os_str().to_string_lossy().into_owned())
+        .unwrap_or_else(|| folder_name(root))
+}
@@ -0,0 +1,338 @@
+use crate::analyzer::{HistoryFilter, LoggedCommit, MergeFilter};
+use crate::authors::{AuthorFilter, DateSource};
+use crate::check::{check_commit_time, git_output};
+use crate::models::Config;
+use crate::utils::{bound_timestamp, debug, key_file_name, user_dir};
+use chrono::DateTime;
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+/// Line of the post-commit hook telling that gitnapped installed it, so that
+/// it is replaced without `--force`.
+const HOOK_MARKER: &str = "# Installed by gitnapped hook install";
+
+/// Separator between the fields of the `git log` line a commit is recorded from.
+const FIELD_SEPARATOR: char = '\u{1f}';
+
+/// A commit as recorded by the post-commit hook, when it was made.
+///
+/// Rewriting the history, e.g. squashing or amending commits, loses the time
+/// the rewritten commits were made, which the journal keeps.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct JournalEntry {
+    /// Canonical path of the repository committed to
+    pub repo: String,
+    /// Full hash of the commit
+    pub hash: String,
+    /// Author of the commit, as `Name <email>`
+    pub author: String,
+    /// Author date, ISO 8601
+    pub date: String,
+    /// Committer of the commit, as `Name <email>`
+    pub committer: String,
+    /// Committer date, ISO 8601
+    pub committer_date: String,
+    /// Subject of the commit message
+    pub subject: String,
+    /// Whether the commit has more than one parent
+    pub merge: bool,
+    /// Whether the commit was made within the working hours of the config
+    pub within_hours: bool,
+    /// Lines added by the commit
+    pub lines_added: usize,
+    /// Lines removed by the commit
+    pub lines_removed: usize,
+    /// Paths of the files changed by the commit
+    pub paths: Vec<String>,
+}
+
+/// Returns the path of the journal, `$XDG_STATE_HOME/gitnapped/journal.jsonl`
+/// (`~/.local/state/gitnapped/journal.jsonl`), one commit per line.
+pub fn journal_path() -> Option<PathBuf> {
+    user_dir("XDG_STATE_HOME", ".local/state").map(|dir| dir.join("journal.jsonl"))
+}
+
+/// Hashes the content of the journal, so that statistics counting its
+/// commits are not reused once it changed.
+///
+/// # Returns
+/// * `String` - The hash, empty when there is no journal
+pub fn journal_digest() -> String {
+    journal_path()
+        .and_then(|path| fs::read_to_string(path).ok())
+        .map(|content| key_file_name(&content))
+        .unwrap_or_default()
+}
+
+/// Installs the post-commit hook recording every commit of a repository in
+/// the journal. A hook installed by gitnapped is replaced, any other one only
+/// with `force`.
+///
+/// # Arguments
+/// * `repo` - A directory of the repository
+/// * `command` - The gitnapped command line the hook runs, `hook record` appended
+/// * `force` - Whether an existing post-commit hook is replaced
+///
+/// # Returns
+/// * `Result<PathBuf, String>` - The path of the hook, or an error telling why it was not installed
+pub fn install_hook(repo: &str, command: &str, force: bool) -> Result<PathBuf, String> {
+    // Honors core.hooksPath and the hooks of worktrees
+    let hooks = git_output(repo, &["rev-parse", "--git-path", "hooks"])
+        .ok_or_else(|| format!("{} is not a Git repository", repo))?;
+    let hooks = Path::new(repo).join(hooks);
+    let path = hooks.join("post-commit");
+
+    if let Ok(existing) = fs::read_to_string(&path) {
+        if !existing.contains(HOOK_MARKER) && !force {
+            return Err(format!(
+                "{} already exists, use --force to replace it",
+                path.display()
+            ));
+        }
+    }
+
+    let script = format!(
+        "#!/bin/sh\n{}\n{} hook record --repo . >/dev/null 2>&1 || true\n",
+        HOOK_MARKER, command
+    );
+    fs::create_dir_all(&hooks)
+        .and_then(|_| fs::write(&path, script))
+        .map_err(|err| format!("Failed to write {}: {}", path.display(), err))?;
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        fs::set_permissions(&path, fs::Permissions::from_mode(0o755))
+            .map_err(|err| format!("Failed to make {} executable: {}", path.display(), err))?;
+    }
+
+    debug(&format!("Installed {}", path.display()));
+    Ok(path)
+}
+
+/// Records the commit HEAD points to in the journal, as run by the
+/// post-commit hook right after every commit.
+///
+/// # Arguments
+/// * `config` - The config holding the working hours
+/// * `repo` - A directory of the repository committed to
+///
+/// # Returns
+/// * `Result<JournalEntry, String>` - The recorded commit, or an error telling why it was not recorded
+pub fn record_commit(config: &Config, repo: &str) -> Result<JournalEntry, String> {
+    let toplevel = git_output(repo, &["rev-parse", "--show-toplevel"])
+        .ok_or_else(|| format!("{} is not a Git repository", repo))?;
+    let output = git_output(
+        repo,
+        &[
+            "log",
+            "-1",
+            "--use-mailmap",
+            "--numstat",
+            "--format=%H%x1f%P%x1f%aN <%aE>%x1f%aI%x1f%cN <%cE>%x1f%cI%x1f%s",
+            "HEAD",
+        ],
+    )
+    .ok_or_else(|| format!("No commit to record in {}", repo))?;
+
+    let mut lines = output.lines();
+    let header = lines.next().unwrap_or_default();
+    let fields: Vec<&str> = header.split(FIELD_SEPARATOR).collect();
+    let [hash, parents, author, date, committer, committer_date, subject] = fields[..] else {
+        return Err(format!("Unexpected commit line: {}", header));
+    };
+    let authored = DateTime::parse_from_rfc3339(date)
+        .map_err(|err| format!("Invalid commit date {}: {}", date, err))?;
+
+    let mut entry = JournalEntry {
+        repo: fs::canonicalize(&toplevel)
+            .map(|path| path.to_string_lossy().into_owned())
+            .unwrap_or(toplevel),
+        hash: hash.to_string(),
+        author: author.to_string(),
+        date: date.to_string(),
+        committer: committer.to_string(),
+        committer_date: committer_date.to_string(),
+        subject: subject.to_string(),
+        merge: parents.split_whitespace().count() > 1,
+        within_hours: check_commit_time(config, repo, authored)?.within_hours,
+        lines_added: 0,
+        lines_removed: 0,
+        paths: Vec::new(),
+    };
+    // `added<TAB>removed<TAB>path`, `-` for binary files
+    for line in lines {
+        let mut parts = line.splitn(3, '\t');
+        if let (Some(added), Some(removed), Some(path)) = (parts.next(), parts.next(), parts.next())
+        {
+            entry.lines_added += added.parse::<usize>().unwrap_or(0);
+            entry.lines_removed += removed.parse::<usize>().unwrap_or(0);
+            entry.paths.push(path.to_string());
+        }
+    }
+
+    let path = journal_path().ok_or("No directory to keep the journal in")?;
+    let line = serde_json::to_string(&entry).map_err(|err| err.to_string())?;
+    if let Some(dir) = path.parent() {
+        let _ = fs::create_dir_all(dir);
+    }
+    OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(&path)
+        .and_then(|mut file| writeln!(file, "{}", line))
+        .map_err(|err| format!("Failed to write {}: {}", path.display(), err))?;
+
+    debug(&format!(
+        "Recorded {} of {} in the journal",
+        hash, entry.repo
+    ));
+    Ok(entry)
+}
+
+/// Reads every commit of the journal, skipping the lines that do not parse.
+pub fn read_journal() -> Vec<JournalEntry> {
+    let Some(content) = journal_path().and_then(|path| fs::read_to_string(path).ok()) else {
+        return Vec::new();
+    };
+    content
+        .lines()
+        .filter_map(|line| match serde_json::from_str(line) {
+            Ok(entry) => Some(entry),
+            Err(err) => {
+                debug(&format!("Skipping journal line {}: {}", line, err));
+                None
+            }
+        })
+        .collect()
+}
+
+/// Finds the commits of the journal made in a period that the history of a
+/// repository no longer has, e.g. squashed, amended or dropped ones, so that
+/// `--journal` counts them along with the history.
+///
+/// A recorded commit is gone when no branch or tag reaches it anymore, and no
+/// commit read from the history has its identity and date, as a rebased commit
+/// would. Repositories analyzed through pathspecs are left alone, the journal
+/// not telling which parts of the repository a commit belonged to.
+///
+/// # Arguments
+/// * `repo` - Path to the Git repository
+/// * `authors` - Authors whose commits are counted
+/// * `since` - Start of the period, as `YYYY-MM-DD HH:MM:SS` or ISO 8601
+/// * `until` - End of the period, as `YYYY-MM-DD HH:MM:SS` or ISO 8601
+/// * `history` - Which part of the history is analyzed
+/// * `commits` - The commits read from the history
+///
+/// # Returns
+/// * `Vec<LoggedCommit>` - The commits gone from the history, as if read from it
+pub fn lost_commits(
+    repo: &str,
+    authors: &AuthorFilter,
+    since: &str,
+    until: &str,
+    history: &HistoryFilter,
+    commits: &[LoggedCommit],
+) -> Vec<LoggedCommit> {
+    let (Some(start), Some(end)) = (bound_timestamp(since), bound_timestamp(until)) else {
+        return Vec::new();
+    };
+    if !history.pathspecs.is_empty() {
+        debug(&format!(
+            "Not reading the journal for the pathspecs of {}",
+            repo
+        ));
+        return Vec::new();
+    }
+    let Ok(canonical) = fs::canonicalize(repo) else {
+        return Vec::new();
+    };
+    let canonical = canonical.to_string_lossy();
+
+    let committer = authors.source == DateSource::Committer;
+    let entries: Vec<JournalEntry> = read_journal()
+        .into_iter()
+        .filter(|entry| entry.repo == canonical)
+        .filter(|entry| {
+            DateTime::parse_from_rfc3339(&entry.committer_date)
+                .is_ok_and(|date| (start..=end).contains(&date.timestamp()))
+        })
+        .filter(|entry| match history.merges {
+            MergeFilter::Include => true,
+            MergeFilter::Exclude => !entry.merge,
+            MergeFilter::Only => entry.merge,
+        })
+        .filter(|entry| {
+            let identity = if committer {
+                &entry.committer
+            } else {
+                &entry.author
+            };
+            authors.is_included(identity) && !authors.is_excluded(identity)
+        })
+        .collect();
+    if entries.is_empty() {
+        return Vec::new();
+    }
+
+    // Reachable commits of the period, whichever branch they are on
+    let reachable: HashSet<String> = git_output(
+        repo,
+        &[
+            "rev-list",
+            "--all",
+            &format!("--since={}", since),
+            &format!("--until={}", until),
+        ],
+    )
+    .unwrap_or_default()
+    .lines()
+    .map(str::to_string)
+    .collect();
+    // Identity and date of the commits read, as a rewritten commit keeps them
+    let mut seen: HashSet<(String, i64)> = commits
+        .iter()
+        .filter_map(|commit| {
+            let date = commit.line.split_whitespace().nth(1)?;
+            let date = DateTime::parse_from_rfc3339(date).ok()?;
+            Some((commit.author.clone(), date.timestamp()))
+        })
+        .collect();
+
+    let mut lost = Vec::new();
+    for entry in entries {
+        let (identity, date) = if committer {
+            (&entry.committer, &entry.committer_date)
+        } else {
+            (&entry.author, &entry.date)
+        };
+        let Ok(timestamp) = DateTime::parse_from_rfc3339(date) else {
+            continue;
+        };
+        if reachable.contains(&entry.hash)
+            || !seen.insert((identity.clone(), timestamp.timestamp()))
+        {
+            continue;
+        }
+
+        debug(&format!(
+            "Counting commit {} of the journal, gone from {}",
+            entry.hash, repo
+        ));
+        let short_hash = entry.hash.get(..7).unwrap_or(&entry.hash);
+        lost.push(LoggedCommit {
+            author: identity.clone(),
+            line: format!("{} {} {} (journal)", short_hash, date, entry.subject),
+            committed: DateTime::parse_from_rfc3339(&entry.committer_date)
+                .map(|date| date.timestamp())
+                .unwrap_or_default(),
+            lines_added: entry.lines_added,
+            lines_removed: entry.lines_removed,
+            paths: entry.paths,
+            merge: entry.merge,
+        });
+    }
+    lost
+}
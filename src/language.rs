@@ -0,0 +1,289 @@
+use regex::Regex;
+use std::path::Path;
+
+/// Language of the files without an extension that no other rule recognizes.
+pub const UNKNOWN_LANGUAGE: &str = "Other";
+
+/// File names that identify a language on their own, whatever their extension.
+const FILE_NAMES: [(&str, &str); 14] = [
+    ("Makefile", "Makefile"),
+    ("GNUmakefile", "Makefile"),
+    ("makefile", "Makefile"),
+    ("Dockerfile", "Dockerfile"),
+    ("Containerfile", "Dockerfile"),
+    ("CMakeLists.txt", "CMake"),
+    ("Rakefile", "Ruby"),
+    ("Gemfile", "Ruby"),
+    ("Vagrantfile", "Ruby"),
+    ("Jenkinsfile", "Groovy"),
+    ("Justfile", "Just"),
+    ("justfile", "Just"),
+    ("BUILD", "Starlark"),
+    ("WORKSPACE", "Starlark"),
+];
+
+/// File extensions and the language of the files having them.
+const EXTENSIONS: [(&str, &str); 78] = [
+    ("rs", "Rust"),
+    ("c", "C"),
+    ("h", "C"),
+    ("cc", "C++"),
+    ("cpp", "C++"),
+    ("cxx", "C++"),
+    ("hpp", "C++"),
+    ("hh", "C++"),
+    ("cs", "C#"),
+    ("java", "Java"),
+    ("kt", "Kotlin"),
+    ("kts", "Kotlin"),
+    ("scala", "Scala"),
+    ("groovy", "Groovy"),
+    ("gradle", "Groovy"),
+    ("go", "Go"),
+    ("swift", "Swift"),
+    ("m", "Objective-C"),
+    ("mm", "Objective-C++"),
+    ("dart", "Dart"),
+    ("zig", "Zig"),
+    ("js", "JavaScript"),
+    ("mjs", "JavaScript"),
+    ("cjs", "JavaScript"),
+    ("jsx", "JSX"),
+    ("ts", "TypeScript"),
+    ("mts", "TypeScript"),
+    ("cts", "TypeScript"),
+    ("tsx", "TSX"),
+    ("vue", "Vue"),
+    ("svelte", "Svelte"),
+    ("html", "HTML"),
+    ("htm", "HTML"),
+    ("css", "CSS"),
+    ("scss", "SCSS"),
+    ("less", "Less"),
+    ("php", "PHP"),
+    ("py", "Python"),
+    ("pyi", "Python"),
+    ("rb", "Ruby"),
+    ("pl", "Perl"),
+    ("pm", "Perl"),
+    ("lua", "Lua"),
+    ("r", "R"),
+    ("ex", "Elixir"),
+    ("exs", "Elixir"),
+    ("erl", "Erlang"),
+    ("hs", "Haskell"),
+    ("clj", "Clojure"),
+    ("cljs", "Clojure"),
+    ("el", "Emacs Lisp"),
+    ("lisp", "Lisp"),
+    ("scm", "Scheme"),
+    ("sh", "Shell"),
+    ("bash", "Shell"),
+    ("zsh", "Shell"),
+    ("fish", "Fish"),
+    ("ps1", "PowerShell"),
+    ("sql", "SQL"),
+    ("proto", "Protocol Buffers"),
+    ("tf", "HCL"),
+    ("nix", "Nix"),
+    ("cmake", "CMake"),
+    ("mk", "Makefile"),
+    ("dockerfile", "Dockerfile"),
+    ("asm", "Assembly"),
+    ("s", "Assembly"),
+    ("tex", "TeX"),
+    ("md", "Markdown"),
+    ("xml", "XML"),
+    ("svg", "SVG"),
+    ("json", "JSON"),
+    ("yaml", "YAML"),
+    ("yml", "YAML"),
+    ("toml", "TOML"),
+    ("ini", "INI"),
+    ("conf", "INI"),
+    ("txt", "Text"),
+];
+
+/// Interpreters named in shebang lines and the language of their scripts.
+const INTERPRETERS: [(&str, &str); 15] = [
+    ("sh", "Shell"),
+    ("bash", "Shell"),
+    ("zsh", "Shell"),
+    ("dash", "Shell"),
+    ("ksh", "Shell"),
+    ("fish", "Fish"),
+    ("python", "Python"),
+    ("ruby", "Ruby"),
+    ("perl", "Perl"),
+    ("node", "JavaScript"),
+    ("deno", "TypeScript"),
+    ("php", "PHP"),
+    ("lua", "Lua"),
+    ("Rscript", "R"),
+    ("pwsh", "PowerShell"),
+];
+
+/// Detects the language of a file out of its name, its extension and, for
+/// scripts without an extension, the interpreter of its shebang line.
+///
+/// Unknown extensions are kept as the language, so they still show up in the file types.
+///
+/// # Arguments
+/// * `file_path` - The path to the file
+/// * `content` - The content of the file, None when it could not be read as text
+///
+/// # Returns
+/// * `String` - The language, e.g. `Rust`, `TSX` or `Dockerfile`
+pub fn detect_language(file_path: &str, content: Option<&str>) -> String {
+    let path = Path::new(file_path);
+    let file_name = path
+        .file_name()
+        .and_then(|name| name.to_str())
+        .unwrap_or(file_path);
+
+    if let Some((_, language)) = FILE_NAMES.iter().find(|(name, _)| *name == file_name) {
+        return language.to_string();
+    }
+    // Variants like Dockerfile.dev
+    if file_name.starts_with("Dockerfile.") {
+        return String::from("Dockerfile");
+    }
+
+    if let Some(extension) = path.extension().and_then(|extension| extension.to_str()) {
+        let lowercase = extension.to_lowercase();
+        return EXTENSIONS
+            .iter()
+            .find(|(known, _)| *known == lowercase)
+            .map(|(_, language)| language.to_string())
+            .unwrap_or(lowercase);
+    }
+
+    content
+        .and_then(shebang_language)
+        .unwrap_or(UNKNOWN_LANGUAGE)
+        .to_string()
+}
+
+/// Finds the language of a script out of the interpreter of its shebang line,
+/// e.g. `#!/usr/bin/env python3` or `#!/bin/bash`.
+fn shebang_language(content: &str) -> Option<&'static str> {
+    let shebang = content.lines().next()?.strip_prefix("#!")?;
+    let mut words = shebang.split_whitespace();
+    let mut interpreter = words.next()?.rsplit('/').next()?;
+    if interpreter == "env" {
+        interpreter = words.find(|word| !word.starts_with('-'))?;
+    }
+    // python3, python3.12 or ruby2.7 are all their interpreter
+    let interpreter = interpreter.trim_end_matches(|c: char| c.is_ascii_digit() || c == '.');
+
+    INTERPRETERS
+        .iter()
+        .find(|(name, _)| *name == interpreter)
+        .map(|(_, language)| *language)
+}
+
+/// How `--filetypes` orders the languages.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum FileTypeOrder {
+    /// Most files first
+    #[default]
+    Files,
+    /// Most lines first
+    Lines,
+}
+
+impl FileTypeOrder {
+    /// Names accepted by `--filetypes`.
+    pub const NAMES: [&'static str; 2] = ["files", "lines"];
+
+    /// Parses an order name as accepted by `--filetypes`.
+    pub fn from_name(name: &str) -> Option<FileTypeOrder> {
+        match name {
+            "files" => Some(FileTypeOrder::Files),
+            "lines" => Some(FileTypeOrder::Lines),
+            _ => None,
+        }
+    }
+}
+
+/// Path patterns of test files used when the config file has no `test_patterns`.
+pub const DEFAULT_TEST_PATTERNS: [&str; 11] = [
+    "tests/",
+    "test/",
+    "__tests__/",
+    "spec/",
+    "*_test.go",
+    "*_test.py",
+    "test_*.py",
+    "*.test.*",
+    "*.spec.*",
+    "*Test.java",
+    "*_spec.rb",
+];
+
+/// Path patterns telling test files apart from production code.
+///
+/// A pattern ending with `/` matches a directory anywhere in the path, a
+/// pattern with another `/` matches the whole path and any other pattern
+/// matches the file name. `*` matches within a path component and `**` across them.
+#[derive(Debug, Clone)]
+pub struct TestPatterns {
+    patterns: Vec<Regex>,
+}
+
+impl TestPatterns {
+    /// Compiles glob-like test path patterns.
+    ///
+    /// # Arguments
+    /// * `patterns` - The patterns, e.g. `tests/` or `*.spec.ts`
+    ///
+    /// # Returns
+    /// * `Result<TestPatterns, String>` - The compiled patterns, or an error for an invalid one
+    pub fn new<S: AsRef<str>>(patterns: &[S]) -> Result<TestPatterns, String> {
+        patterns
+            .iter()
+            .map(|pattern| {
+                let pattern = pattern.as_ref();
+                let regex = if let Some(directory) = pattern.strip_suffix('/') {
+                    format!("(^|/){}/", glob_to_regex(directory.trim_start_matches('/')))
+                } else if pattern.contains('/') {
+                    format!("^{}$", glob_to_regex(pattern.trim_start_matches('/')))
+                } else {
+                    format!("(^|/){}$", glob_to_regex(pattern))
+                };
+                Regex::new(&regex)
+                    .map_err(|err| format!("Invalid test pattern '{}': {}", pattern, err))
+            })
+            .collect::<Result<Vec<Regex>, String>>()
+            .map(|patterns| TestPatterns { patterns })
+    }
+
+    /// Checks if a file, given by its path in the repository, is a test file.
+    pub fn is_test(&self, path: &str) -> bool {
+        self.patterns.iter().any(|pattern| pattern.is_match(path))
+    }
+}
+
+impl Default for TestPatterns {
+    fn default() -> Self {
+        TestPatterns::new(&DEFAULT_TEST_PATTERNS).expect("default test patterns are valid")
+    }
+}
+
+/// Turns a glob into a regular expression, without anchors.
+fn glob_to_regex(glob: &str) -> String {
+    let mut regex = String::new();
+    let mut chars = glob.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '*' if chars.peek() == Some(&'*') => {
+                chars.next();
+                regex.push_str(".*");
+            }
+            '*' => regex.push_str("[^/]*"),
+            '?' => regex.push_str("[^/]"),
+            _ => regex.push_str(&regex::escape(&c.to_string())),
+        }
+    }
+    regex
+}
@@ -0,0 +1,93 @@
+//! Find out why you didn't sleep: the commit history of Git repositories,
+//! checked against working hours.
+//!
+//! The `gitnapped` binary is built on this library, which other tools such as
+//! editors, bots or dashboards can embed instead of running the binary. An
+//! [`Analyzer`] analyzes the repositories of a config, read with
+//! [`config::load_config`] or built as a [`models::Config`], over a range of
+//! days into the [`report::Report`] printed by `gitnapped --json`:
+//!
+//! ```no_run
+//! use chrono::NaiveDate;
+//! use gitnapped::config::load_config;
+//! use gitnapped::Analyzer;
+//!
+//! let config = load_config("gitnapped.yaml", None)?;
+//! let first_day = NaiveDate::from_ymd_opt(2025, 3, 1).unwrap();
+//! let last_day = NaiveDate::from_ymd_opt(2025, 3, 31).unwrap();
+//! let report = Analyzer::new(config).run(first_day..=last_day)?;
+//! println!("Gitnapped for {}%", report.totals.gitnapped_percentage);
+//! # Ok::<(), String>(())
+//! ```
+//!
+//! The [`analyzer`] functions leave more options to their callers, such as
+//! which part of the history is read, while [`parser`] reads the
+//! `path [Category][Name]` strings of the config.
+
+pub mod analyzer;
+pub mod authors;
+pub mod config;
+pub mod language;
+pub mod models;
+pub mod parser;
+pub mod report;
+pub mod schedule;
+
+// Used by the gitnapped binary, not part of the API
+#[doc(hidden)]
+pub mod anonymize;
+#[doc(hidden)]
+pub mod cache;
+#[doc(hidden)]
+pub mod chart;
+#[doc(hidden)]
+pub mod check;
+#[doc(hidden)]
+pub mod clones;
+#[doc(hidden)]
+pub mod discovery;
+#[doc(hidden)]
+pub mod display;
+#[doc(hidden)]
+pub mod email;
+#[doc(hidden)]
+pub mod health;
+#[doc(hidden)]
+pub mod influx;
+#[doc(hidden)]
+pub mod init;
+#[doc(hidden)]
+pub mod journal;
+#[cfg(feature = "libgit2")]
+#[doc(hidden)]
+pub mod libgit2;
+#[doc(hidden)]
+pub mod lines;
+#[doc(hidden)]
+pub mod notify;
+#[doc(hidden)]
+pub mod progress;
+#[doc(hidden)]
+pub mod prompt;
+#[doc(hidden)]
+pub mod remote;
+#[doc(hidden)]
+pub mod serve;
+#[doc(hidden)]
+pub mod snapshots;
+#[doc(hidden)]
+pub mod state;
+#[doc(hidden)]
+pub mod tickets;
+#[doc(hidden)]
+pub mod timetracking;
+#[doc(hidden)]
+pub mod timing;
+#[doc(hidden)]
+pub mod utils;
+#[doc(hidden)]
+pub mod validate;
+#[doc(hidden)]
+pub mod wakatime;
+
+pub use analyzer::Analyzer;
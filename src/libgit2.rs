@@ -0,0 +1,377 @@
+use crate::analyzer::{HistoryFilter, LogRange, LoggedCommit, MergeFilter};
+use crate::authors::{AuthorFilter, DateSource};
+use crate::utils::{bound_timestamp, debug};
+use chrono::{DateTime, Duration, FixedOffset};
+use git2::{
+    AttrCheckFlags, AttrValue, Commit, DiffFindOptions, DiffOptions, Mailmap, Oid, Pathspec,
+    PathspecFlags, Repository, Sort, StatusOptions, Time,
+};
+use regex::Regex;
+use std::collections::HashSet;
+use std::path::Path;
+
+/// Reads the commits of a repository in a period with libgit2, the way
+/// `git log --use-mailmap --numstat` does.
+///
+/// # Arguments
+/// * `repo` - Path to the Git repository
+/// * `authors` - Authors whose commits are counted
+/// * `range` - Which commits are read
+/// * `history` - Which part of the history is read
+///
+/// # Returns
+/// * `Result<Vec<LoggedCommit>, String>` - The commits, newest first, or the libgit2 error
+pub fn read_log(
+    repo: &str,
+    authors: &AuthorFilter,
+    range: LogRange,
+    history: &HistoryFilter,
+) -> Result<Vec<LoggedCommit>, String> {
+    let git_repo = Repository::discover(repo).map_err(|err| err.to_string())?;
+    let mailmap = git_repo.mailmap().map_err(|err| err.to_string())?;
+
+    // Like git, the period applies to the committer date whatever the date source
+    let (since, until) = match range {
+        LogRange::Period { since, until } => (
+            bound_timestamp(since).ok_or_else(|| format!("Invalid date '{}'", since))?,
+            bound_timestamp(until).ok_or_else(|| format!("Invalid date '{}'", until))?,
+        ),
+        LogRange::After(_) => (i64::MIN, i64::MAX),
+    };
+
+    // git matches --author patterns as regular expressions, anything else as text
+    let include: Vec<Result<Regex, &str>> = authors
+        .include
+        .iter()
+        .map(|pattern| Regex::new(pattern).map_err(|_| pattern.as_str()))
+        .collect();
+
+    let pathspec = if history.pathspecs.is_empty() {
+        None
+    } else {
+        Some(pathspec(&history.pathspecs)?)
+    };
+
+    let mut walk = git_repo.revwalk().map_err(|err| err.to_string())?;
+    walk.set_sorting(Sort::TIME)
+        .map_err(|err| err.to_string())?;
+    walk.push_head().map_err(|err| err.to_string())?;
+    if let LogRange::After(Some(commit)) = range {
+        let oid = Oid::from_str(commit).map_err(|err| err.to_string())?;
+        walk.hide(oid).map_err(|err| err.to_string())?;
+    }
+    if history.first_parent {
+        walk.simplify_first_parent()
+            .map_err(|err| err.to_string())?;
+    }
+
+    let mut commits = Vec::new();
+    for oid in walk {
+        let oid = oid.map_err(|err| err.to_string())?;
+        let commit = git_repo.find_commit(oid).map_err(|err| err.to_string())?;
+
+        let committed = commit.committer().when().seconds();
+        if committed < since || committed > until {
+            continue;
+        }
+
+        let merge = commit.parent_count() > 1;
+        let keep = match history.merges {
+            MergeFilter::Include => true,
+            MergeFilter::Exclude => !merge,
+            MergeFilter::Only => merge,
+        };
+        if !keep {
+            continue;
+        }
+
+        let (author, time) = identity(&commit, &mailmap, authors.source)?;
+        let included = include.is_empty()
+            || include.iter().any(|pattern| match pattern {
+                Ok(regex) => regex.is_match(&author),
+                Err(text) => author.contains(text),
+            });
+        if !included {
+            continue;
+        }
+        if authors.is_excluded(&author) {
+            debug(&format!(
+                "Skipping commit of excluded author {}: {}",
+                author,
+                commit.id()
+            ));
+            continue;
+        }
+
+        let (lines_added, lines_removed, paths) =
+            changes(&git_repo, &commit, history.first_parent)?;
+        // Only the commits touching the pathspecs, e.g. the part of a monorepo we own
+        if let Some(pathspec) = &pathspec {
+            let touches = paths
+                .iter()
+                .any(|path| pathspec.matches_path(Path::new(path), PathspecFlags::DEFAULT));
+            if !touches {
+                continue;
+            }
+        }
+
+        let hash = commit
+            .as_object()
+            .short_id()
+            .map_err(|err| err.to_string())?;
+        commits.push(LoggedCommit {
+            author,
+            committed,
+            line: format!(
+                "{} {} {}",
+                hash.as_str().unwrap_or_default(),
+                to_datetime(time).to_rfc3339(),
+                commit.summary().unwrap_or_default()
+            ),
+            lines_added,
+            lines_removed,
+            paths,
+            merge,
+        });
+    }
+
+    Ok(commits)
+}
+
+/// Lists the submodules of a repository, as `git submodule status` does.
+///
+/// # Returns
+/// * `Result<Vec<String>, String>` - The paths of the submodules, relative to the repository
+pub fn submodule_paths(repo: &str) -> Result<Vec<String>, String> {
+    let git_repo = Repository::discover(repo).map_err(|err| err.to_string())?;
+    let submodules = git_repo.submodules().map_err(|err| err.to_string())?;
+    Ok(submodules
+        .iter()
+        .map(|submodule| submodule.path().to_string_lossy().into_owned())
+        .collect())
+}
+
+/// Looks up the committer date of the commit a tag points to, like `tag_commit_date`.
+///
+/// # Returns
+/// * `Option<String>` - The date in ISO 8601 format, None if the tag does not exist
+pub fn tag_commit_date(repo: &str, tag: &str, after: bool) -> Option<String> {
+    let git_repo = Repository::discover(repo).ok()?;
+    let commit = git_repo
+        .revparse_single(&format!("refs/tags/{}", tag))
+        .ok()?
+        .peel_to_commit()
+        .ok()?;
+
+    let date = to_datetime(commit.committer().when());
+    let date = if after {
+        date + Duration::seconds(1)
+    } else {
+        date
+    };
+    debug(&format!("Tag {} of {} resolved to {}", tag, repo, date));
+
+    Some(date.to_rfc3339())
+}
+
+/// Lists the files tracked in the index of a repository, as `git ls-files` does.
+///
+/// # Arguments
+/// * `repo` - Path to the Git repository
+/// * `pathspecs` - Pathspecs the files are restricted to, empty for all of them
+///
+/// # Returns
+/// * `Result<Vec<String>, String>` - The paths of the files, relative to the repository
+pub fn tracked_files(repo: &str, pathspecs: &[String]) -> Result<Vec<String>, String> {
+    let git_repo = Repository::discover(repo).map_err(|err| err.to_string())?;
+    let index = git_repo.index().map_err(|err| err.to_string())?;
+    let pathspec = pathspec(pathspecs)?;
+
+    Ok(index
+        .iter()
+        .map(|entry| String::from_utf8_lossy(&entry.path).into_owned())
+        .filter(|path| {
+            pathspecs.is_empty() || pathspec.matches_path(Path::new(path), PathspecFlags::DEFAULT)
+        })
+        .collect())
+}
+
+/// Finds the files marked as generated or vendored in `.gitattributes`, like
+/// `linguist_excluded_files`.
+///
+/// # Returns
+/// * `HashSet<String>` - The files with `linguist-generated` or `linguist-vendored` set
+pub fn linguist_excluded_files(repo: &str, files: &[&str], attributes: &[&str]) -> HashSet<String> {
+    let Ok(git_repo) = Repository::discover(repo) else {
+        return HashSet::new();
+    };
+
+    files
+        .iter()
+        .filter(|file| {
+            attributes.iter().any(|attribute| {
+                let value = git_repo
+                    .get_attr(Path::new(file), attribute, AttrCheckFlags::FILE_THEN_INDEX)
+                    .ok()
+                    .flatten();
+                matches!(
+                    AttrValue::from_string(value),
+                    AttrValue::True | AttrValue::String("true")
+                )
+            })
+        })
+        .map(|file| file.to_string())
+        .collect()
+}
+
+/// Builds a libgit2 pathspec out of git pathspecs. libgit2 does not know the
+/// `:(exclude)` and `:!` magic, so exclusions become its `!` negations, checked
+/// first, followed by a match-all when nothing else is included.
+fn pathspec(pathspecs: &[String]) -> Result<Pathspec, String> {
+    let mut patterns = Vec::new();
+    let mut included = Vec::new();
+    for pathspec in pathspecs {
+        let excluded = pathspec
+            .strip_prefix(":(exclude)")
+            .or_else(|| pathspec.strip_prefix(":!"))
+            .or_else(|| pathspec.strip_prefix(":^"));
+        match excluded {
+            Some(path) => patterns.push(format!("!{}", path)),
+            None => included.push(pathspec.clone()),
+        }
+    }
+    if included.is_empty() && !patterns.is_empty() {
+        included.push(String::from("*"));
+    }
+    patterns.extend(included);
+
+    Pathspec::new(patterns).map_err(|err| err.to_string())
+}
+
+/// Returns the identity (`Name <email>`, through the mailmap) and the date of a
+/// commit, from its author or its committer.
+fn identity(
+    commit: &Commit,
+    mailmap: &Mailmap,
+    source: DateSource,
+) -> Result<(String, Time), String> {
+    let signature = match source {
+        DateSource::Author => commit.author_with_mailmap(mailmap),
+        DateSource::Committer => commit.committer_with_mailmap(mailmap),
+    }
+    .map_err(|err| err.to_string())?;
+
+    Ok((
+        format!(
+            "{} <{}>",
+            String::from_utf8_lossy(signature.name_bytes()),
+            String::from_utf8_lossy(signature.email_bytes())
+        ),
+        signature.when(),
+    ))
+}
+
+/// Computes the lines added and removed by a commit and the paths it changed,
+/// renames resolved to their new path, as `git log --numstat` shows them.
+///
+/// Like git, merges only show changes when the first parent is followed, as
+/// the diff against that parent.
+///
+/// # Returns
+/// * `Result<(usize, usize, Vec<String>), String>` - Lines added, lines removed and paths
+fn changes(
+    repo: &Repository,
+    commit: &Commit,
+    first_parent: bool,
+) -> Result<(usize, usize, Vec<String>), String> {
+    if commit.parent_count() > 1 && !first_parent {
+        return Ok((0, 0, Vec::new()));
+    }
+
+    let tree = commit.tree().map_err(|err| err.to_string())?;
+    let parent_tree = match commit.parent(0) {
+        Ok(parent) => Some(parent.tree().map_err(|err| err.to_string())?),
+        // The root commit adds all its files
+        Err(_) => None,
+    };
+
+    let mut options = DiffOptions::new();
+    let mut diff = repo
+        .diff_tree_to_tree(parent_tree.as_ref(), Some(&tree), Some(&mut options))
+        .map_err(|err| err.to_string())?;
+    diff.find_similar(Some(DiffFindOptions::new().renames(true)))
+        .map_err(|err| err.to_string())?;
+
+    let stats = diff.stats().map_err(|err| err.to_string())?;
+    let paths = diff
+        .deltas()
+        .filter_map(|delta| delta.new_file().path().or(delta.old_file().path()))
+        .map(|path| path.to_string_lossy().into_owned())
+        .collect();
+
+    Ok((stats.insertions(), stats.deletions(), paths))
+}
+
+/// Turns a libgit2 time into a date in the timezone it was recorded in.
+fn to_datetime(time: Time) -> DateTime<FixedOffset> {
+    let offset = FixedOffset::east_opt(time.offset_minutes() * 60)
+        .unwrap_or_else(|| FixedOffset::east_opt(0).expect("UTC is a valid offset"));
+    DateTime::from_timestamp(time.seconds(), 0)
+        .unwrap_or_default()
+        .with_timezone(&offset)
+}
+
+/// Reads the commit HEAD points to, unless the repository has uncommitted
+/// changes, like `git status`.
+///
+/// # Returns
+/// * `Result<Option<String>, String>` - The commit, None if there are uncommitted changes
+pub fn clean_head(repo: &str) -> Result<Option<String>, String> {
+    let git_repo = Repository::discover(repo).map_err(|err| err.to_string())?;
+    let mut options = StatusOptions::new();
+    options.include_untracked(false).include_ignored(false);
+    let statuses = git_repo
+        .statuses(Some(&mut options))
+        .map_err(|err| err.to_string())?;
+    if !statuses.is_empty() {
+        debug(&format!("Not caching {}, it has uncommitted changes", repo));
+        return Ok(None);
+    }
+
+    head_commit(repo).map(Some)
+}
+
+/// Reads the commit HEAD points to, like `git rev-parse HEAD`.
+pub fn head_commit(repo: &str) -> Result<String, String> {
+    let git_repo = Repository::discover(repo).map_err(|err| err.to_string())?;
+    let head = git_repo
+        .head()
+        .map_err(|err| err.to_string())?
+        .target()
+        .ok_or_else(|| String::from("HEAD points to no commit"))?;
+    Ok(head.to_string())
+}
+
+/// Reads the committer date of a commit as a Unix timestamp, like
+/// `git log -1 --format=%ct`.
+pub fn commit_time(repo: &str, commit: &str) -> Result<i64, String> {
+    let git_repo = Repository::discover(repo).map_err(|err| err.to_string())?;
+    let oid = Oid::from_str(commit).map_err(|err| err.to_string())?;
+    let commit = git_repo.find_commit(oid).map_err(|err| err.to_string())?;
+    let time = commit.committer().when().seconds();
+    Ok(time)
+}
+
+/// Checks if a commit is an ancestor of another one, or the same commit, like
+/// `git merge-base --is-ancestor`.
+pub fn is_ancestor(repo: &str, ancestor: &str, commit: &str) -> Result<bool, String> {
+    let git_repo = Repository::discover(repo).map_err(|err| err.to_string())?;
+    let ancestor = Oid::from_str(ancestor).map_err(|err| err.to_string())?;
+    let commit = Oid::from_str(commit).map_err(|err| err.to_string())?;
+    if ancestor == commit {
+        return Ok(true);
+    }
+    git_repo
+        .graph_descendant_of(commit, ancestor)
+        .map_err(|err| err.to_string())
+}
@@ -0,0 +1,149 @@
+use serde::{Deserialize, Serialize};
+use std::ops::AddAssign;
+
+/// Lines of a file or group of files, split by kind.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LineCounts {
+    /// Lines holding code, including code followed by a comment
+    pub code: usize,
+    /// Lines holding only a comment
+    pub comments: usize,
+    /// Empty or whitespace-only lines
+    pub blank: usize,
+}
+
+impl LineCounts {
+    /// Total number of lines, whatever their kind.
+    pub fn total(&self) -> usize {
+        self.code + self.comments + self.blank
+    }
+}
+
+impl AddAssign for LineCounts {
+    fn add_assign(&mut self, other: LineCounts) {
+        self.code += other.code;
+        self.comments += other.comments;
+        self.blank += other.blank;
+    }
+}
+
+/// How comments are written in a language.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct CommentSyntax {
+    /// Prefixes starting a comment that runs to the end of the line
+    pub line: &'static [&'static str],
+    /// Delimiters of a comment that may span several lines
+    pub block: Option<(&'static str, &'static str)>,
+}
+
+const C_LIKE: CommentSyntax = CommentSyntax {
+    line: &["//"],
+    block: Some(("/*", "*/")),
+};
+const HASH: CommentSyntax = CommentSyntax {
+    line: &["#"],
+    block: None,
+};
+const MARKUP: CommentSyntax = CommentSyntax {
+    line: &[],
+    block: Some(("<!--", "-->")),
+};
+
+/// Returns the comment syntax of a language. Files in unknown languages have
+/// no comments, so all their non-blank lines count as code.
+///
+/// # Arguments
+/// * `language` - The language, as returned by `detect_language`
+///
+/// # Returns
+/// * `CommentSyntax` - How comments are written in the language
+pub fn comment_syntax(language: &str) -> CommentSyntax {
+    match language {
+        "Rust" | "C" | "C++" | "C#" | "Java" | "Kotlin" | "Scala" | "Groovy" | "Go" | "Swift"
+        | "Objective-C" | "Objective-C++" | "Dart" | "Zig" | "JavaScript" | "JSX"
+        | "TypeScript" | "TSX" | "SCSS" | "Less" | "Protocol Buffers" => C_LIKE,
+        "PHP" => CommentSyntax {
+            line: &["//", "#"],
+            block: Some(("/*", "*/")),
+        },
+        "CSS" => CommentSyntax {
+            line: &[],
+            block: Some(("/*", "*/")),
+        },
+        "Python" | "Ruby" | "Perl" | "R" | "Elixir" | "Shell" | "Fish" | "PowerShell" | "HCL"
+        | "Nix" | "CMake" | "Makefile" | "Dockerfile" | "Starlark" | "Just" | "YAML" | "TOML"
+        | "INI" => HASH,
+        "SQL" | "Haskell" => CommentSyntax {
+            line: &["--"],
+            block: None,
+        },
+        "Lua" => CommentSyntax {
+            line: &["--"],
+            block: Some(("--[[", "]]")),
+        },
+        "Lisp" | "Clojure" | "Emacs Lisp" | "Scheme" | "Assembly" => CommentSyntax {
+            line: &[";"],
+            block: None,
+        },
+        "TeX" | "Erlang" => CommentSyntax {
+            line: &["%"],
+            block: None,
+        },
+        "HTML" | "XML" | "SVG" | "Vue" | "Svelte" | "Markdown" => MARKUP,
+        _ => CommentSyntax::default(),
+    }
+}
+
+/// Classifies the lines of a file as code, comments or blank.
+///
+/// A line with code before a comment counts as code.
+///
+/// # Arguments
+/// * `content` - The content of the file
+/// * `syntax` - How comments are written in the file's language
+///
+/// # Returns
+/// * `LineCounts` - The lines of the file by kind
+pub fn classify_lines(content: &str, syntax: CommentSyntax) -> LineCounts {
+    let mut counts = LineCounts::default();
+    let mut in_block: Option<&str> = None;
+
+    for line in content.lines() {
+        let line = line.trim();
+
+        if let Some(end) = in_block {
+            if line.contains(end) {
+                in_block = None;
+                // Code may follow the end of the comment on the same line
+                let rest = line.split_once(end).map_or("", |(_, rest)| rest).trim();
+                if !rest.is_empty() && !syntax.line.iter().any(|prefix| rest.starts_with(prefix)) {
+                    counts.code += 1;
+                    continue;
+                }
+            }
+            counts.comments += 1;
+        } else if line.is_empty() {
+            counts.blank += 1;
+        } else if let Some((start, end)) = syntax.block.filter(|(start, _)| line.starts_with(start))
+        {
+            if !line[start.len()..].contains(end) {
+                in_block = Some(end);
+            }
+            counts.comments += 1;
+        } else if syntax.line.iter().any(|prefix| line.starts_with(prefix)) {
+            counts.comments += 1;
+        } else {
+            counts.code += 1;
+            // A block comment opened after code continues on the next lines
+            if let Some((start, end)) = syntax.block {
+                if let Some((_, rest)) = line.split_once(start) {
+                    if !rest.contains(end) {
+                        in_block = Some(end);
+                    }
+                }
+            }
+        }
+    }
+
+    counts
+}
@@ -1,27 +1,76 @@
-mod analyzer;
-mod config;
-mod display;
-mod models;
-mod parser;
-mod utils;
-
-use chrono::{Duration, Local};
-use clap::{Arg, Command as ClapCommand};
+use chrono::{Duration, Local, NaiveDate, NaiveDateTime, Weekday};
+use clap::parser::{ValueSource, ValuesRef};
+use clap::{Arg, ArgGroup, ArgMatches, Command as ClapCommand};
 use colored::*;
-use std::collections::HashMap;
+use std::any::Any;
+use std::cmp::Reverse;
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::ops::Deref;
+use std::path::Path;
 use std::process;
+use std::time::Instant;
 
-use analyzer::{analyze_all_categories, analyze_all_projects, create_repo_path_map};
-use config::{load_config, parse_repos_from_config, push_to_empty_config};
-use display::{print_category_summary, print_projects_summary, print_total_stats};
-use models::RepoStats;
-use utils::{
-    aggregate_stats, debug, init_debug_mode, init_silent_mode, is_repo_active, log, parse_period,
-    parse_working_time,
+use gitnapped::analyzer::{
+    analyze_all_categories, analyze_all_projects, session_minutes_by_day, Backend, FileScope,
+    HistoryFilter, MergeFilter,
 };
+use gitnapped::anonymize::{
+    anonymize_analysis, anonymize_projects, anonymize_stats, author_pseudonym, path_pseudonym,
+    Anonymize,
+};
+use gitnapped::authors::{AuthorFilter, DateSource};
+use gitnapped::cache::ResultCache;
+use gitnapped::chart::write_svg_charts;
+use gitnapped::check::check_commit_time;
+use gitnapped::clones::{fetch_repositories, update_clones};
+use gitnapped::config::{
+    default_config_path, load_config, parse_repos_from_config, parse_schedules_from_config,
+    push_to_empty_config, read_config,
+};
+use gitnapped::display::{
+    files_and_lines, print_author_breakdown, print_category_summary, print_commit_sizes,
+    print_editor_time, print_heatmap, print_hour_histogram, print_jsonl_repo, print_leaderboard,
+    print_period_comparison, print_projects_summary, print_punch_card, print_repo_table,
+    print_review_activity, print_snapshot_comparison, print_snapshot_trend, print_tickets,
+    print_total_stats, print_trend, renderer_for, sparkline, write_output, OutputFormat, Renderer,
+    TemplateRenderer,
+};
+use gitnapped::email::send_report;
+use gitnapped::influx::write_points;
+use gitnapped::init::write_starter_config;
+use gitnapped::journal::{install_hook, record_commit};
+use gitnapped::language::{FileTypeOrder, TestPatterns};
+use gitnapped::models::{Config, ProjectStats, RepoInfo, RepoStats};
+use gitnapped::notify::{post_webhook, DiscordRenderer, SlackRenderer};
+use gitnapped::prompt::{
+    lock_refresh, read_prompt_status, unlock_refresh, write_prompt_status, PromptStatus,
+    TmuxRenderer, DEFAULT_TMUX_FORMAT,
+};
+use gitnapped::remote::read_github_activity;
+use gitnapped::report::{build_report, repo_report, EditorTime, ReviewActivity, Tickets, Trend};
+use gitnapped::schedule::{CommitTimezone, Schedule, WorkingHours, DEFAULT_WORKING_TIME};
+use gitnapped::serve::serve as serve_dashboard;
+use gitnapped::snapshots::{
+    append_snapshot, compare_snapshots, read_snapshots, snapshot_periods, Snapshot,
+};
+use gitnapped::tickets::TicketPatterns;
+use gitnapped::timetracking::{
+    author_email, project_sessions, push_to_clockify, push_to_toggl, time_entries_csv, timesheet,
+    timesheet_csv, timesheet_markdown,
+};
+use gitnapped::utils::{
+    aggregate_stats, debug, fiscal_year_range, git_config_author, init_debug_mode,
+    init_silent_mode, is_repo_active, log, month_range, parse_day_range, parse_fiscal_year_start,
+    parse_period, parse_range_bound, period_preset_range, previous_period, resolve_git_date,
+    week_range, with_time_of_day, PERIOD_PRESETS,
+};
+use gitnapped::validate::{validate_config, Issue};
+use gitnapped::wakatime::read_wakatime_summaries;
+use gitnapped::{progress, timing, Analyzer};
 
-fn main() {
-    let matches = ClapCommand::new("gitnapped")
+/// Builds the command line interface.
+fn cli() -> ClapCommand {
+    ClapCommand::new("gitnapped")
         .version(env!("CARGO_PKG_VERSION"))
         .author(env!("CARGO_PKG_AUTHORS"))
         .about("Find out why you didn't sleep — commit history across repos")
@@ -32,25 +81,82 @@ fn main() {
             .short('c')
             .long("config")
             .value_name("FILE")
-            .help("Sets a custom config file, if not provided, the app will look for a 'gitnapped.yaml'"))
+            .help("Sets a custom config file (YAML, or TOML with a .toml extension), if not provided, the app will look for a 'gitnapped.yaml' or 'gitnapped.toml', then for a 'config.yaml' or 'config.toml' in ~/.config/gitnapped"))
+        .arg(Arg::new("profile")
+            .long("profile")
+            .value_name("NAME")
+            .help("Uses a profile of the config file, merging its settings over the others")
+            .conflicts_with("dir"))
         .arg(Arg::new("dir")
             .short('d')
             .long("dir")
             .value_name("DIRECTORY")
             .help("Sets a custom directory to analyze, if not provided, the app will look for a 'gitnapped.yaml' in the current directory"))
+        .arg(Arg::new("recursive")
+            .short('r')
+            .long("recursive")
+            .help("Analyze every Git repository found below the --dir directory")
+            .requires("dir")
+            .action(clap::ArgAction::SetTrue))
+        .arg(Arg::new("max-depth")
+            .long("max-depth")
+            .value_name("LEVELS")
+            .help("How many directory levels below --dir are searched with --recursive")
+            .value_parser(clap::value_parser!(usize))
+            .requires("recursive")
+            .default_value("3"))
         .arg(Arg::new("since")
             .short('s')
             .long("since")
-            .help("Start date for analysis (YYYY-MM-DD)"))
+            .help("Start date for analysis (YYYY-MM-DD or any date git log understands, e.g. 2.weeks.ago)"))
         .arg(Arg::new("until")
             .short('u')
             .long("until")
-            .help("End date for analysis (YYYY-MM-DD)"))
+            .help("End date for analysis (YYYY-MM-DD or any date git log understands, e.g. noon yesterday)"))
+        .arg(Arg::new("since-tag")
+            .long("since-tag")
+            .value_name("TAG")
+            .help("Start the period after the commit of a tag, e.g. v2.3.0, resolved in every repository")
+            .conflicts_with("since"))
+        .arg(Arg::new("until-tag")
+            .long("until-tag")
+            .value_name("TAG")
+            .help("End the period at the commit of a tag, resolved in every repository")
+            .conflicts_with("until"))
+        .arg(Arg::new("until-inclusive")
+            .long("until-inclusive")
+            .value_name("BOOL")
+            .help("Whether a --until date includes the whole day, otherwise it ends at its midnight")
+            .value_parser(clap::value_parser!(bool))
+            .num_args(0..=1)
+            .require_equals(true)
+            .default_missing_value("true")
+            .default_value("true"))
         .arg(Arg::new("period")
             .short('p')
             .long("period")
             .value_name("PERIOD")
-            .help("Relative time period (e.g., 6M, 2Y, 5D, 12H)"))
+            .help("Relative time period (e.g., 6M, 2Y, 5D, 12H) or a named one (today, yesterday, this-week, last-week, this-month, last-month, this-year, FY, last-FY)"))
+        .args(PERIOD_PRESETS.iter().map(|(name, help)| {
+            Arg::new(*name)
+                .long(*name)
+                .help(*help)
+                .action(clap::ArgAction::SetTrue)
+                .conflicts_with_all(["since", "until", "period"])
+        }))
+        .arg(Arg::new("week-of")
+            .long("week-of")
+            .value_name("DATE")
+            .help("Analyze the whole week containing a date (YYYY-MM-DD)")
+            .conflicts_with_all(["since", "until", "period", "period-preset", "month-of"]))
+        .arg(Arg::new("month-of")
+            .long("month-of")
+            .value_name("MONTH")
+            .help("Analyze a whole month (YYYY-MM)")
+            .conflicts_with_all(["since", "until", "period", "period-preset"]))
+        .group(ArgGroup::new("period-preset")
+            .args(PERIOD_PRESETS.map(|(name, _)| name))
+            .multiple(false))
         .arg(Arg::new("active-only")
             .long("active-only")
             .help("Show only repositories with commits in the period")
@@ -70,13 +176,46 @@ fn main() {
             .action(clap::ArgAction::SetTrue))
         .arg(Arg::new("filetypes")
             .long("filetypes")
-            .help("Show file types used in the repositories")
+            .value_name("ORDER")
+            .help("Show the languages used in the repositories, ordered by number of files or with 'lines' by lines")
+            .value_parser(FileTypeOrder::NAMES)
+            .num_args(0..=1)
+            .require_equals(true)
+            .default_missing_value("files"))
+        .arg(Arg::new("touched-only")
+            .long("touched-only")
+            .help("Count only the files and lines touched by commits in the period, not the whole repository")
+            .action(clap::ArgAction::SetTrue))
+        .arg(Arg::new("pathspec")
+            .long("pathspec")
+            .value_name("PATHSPEC")
+            .help("Count only commits touching a path, e.g. src/backend, in repositories without pathspecs in the config (repeatable)")
+            .action(clap::ArgAction::Append))
+        .arg(Arg::new("no-merges")
+            .long("no-merges")
+            .help("Leave merge commits out of the analysis")
+            .action(clap::ArgAction::SetTrue))
+        .arg(Arg::new("merges-only")
+            .long("merges-only")
+            .help("Analyze merge commits only")
+            .action(clap::ArgAction::SetTrue)
+            .conflicts_with("no-merges"))
+        .arg(Arg::new("first-parent")
+            .long("first-parent")
+            .help("Follow only the first parent of merges, so every merged branch counts as one commit")
             .action(clap::ArgAction::SetTrue))
         .arg(Arg::new("author")
             .short('a')
             .long("author")
             .value_name("AUTHOR")
-            .help("Filter commits by specific author (overrides config file)"))
+            .help("Filter commits by author, repeat or comma separate to match any of several names/emails (overrides config file)")
+            .action(clap::ArgAction::Append)
+            .value_delimiter(','))
+        .arg(Arg::new("exclude-author")
+            .long("exclude-author")
+            .value_name("PATTERN")
+            .help("Ignore commits of authors matching a pattern, e.g. bots (repeatable, adds to the config's exclude_authors)")
+            .action(clap::ArgAction::Append))
         .arg(Arg::new("all-authors")
             .long("all-authors")
             .help("Include commits from all authors (ignores author filter)")
@@ -89,20 +228,167 @@ fn main() {
             .long("most-active-day")
             .help("Show the most active day")
             .action(clap::ArgAction::SetTrue))
+        .arg(Arg::new("heatmap")
+            .long("heatmap")
+            .help("Show a calendar heatmap of the commits in the period")
+            .action(clap::ArgAction::SetTrue))
+        .arg(Arg::new("by-hour")
+            .long("by-hour")
+            .help("Show a histogram of commits by hour of day")
+            .action(clap::ArgAction::SetTrue))
+        .arg(Arg::new("punchcard")
+            .long("punchcard")
+            .help("Show a punch card of commits by weekday and hour")
+            .action(clap::ArgAction::SetTrue))
+        .arg(Arg::new("by-author")
+            .long("by-author")
+            .help("Show commits and gitnapped percentage per author, per repository and overall")
+            .action(clap::ArgAction::SetTrue))
+        .arg(Arg::new("commit-sizes")
+            .long("commit-sizes")
+            .help("Show the distribution of lines changed per commit, per repository and overall")
+            .action(clap::ArgAction::SetTrue))
+        .arg(Arg::new("trend")
+            .long("trend")
+            .help("Compare the period with the previous period of the same length")
+            .conflicts_with_all(["since-tag", "until-tag"])
+            .action(clap::ArgAction::SetTrue))
+        .arg(Arg::new("github-activity")
+            .long("github-activity")
+            .help("Also count the pull requests opened, reviews submitted and review comments written on GitHub in the period, with the token of $GITHUB_TOKEN")
+            .action(clap::ArgAction::SetTrue))
+        .arg(Arg::new("wakatime")
+            .long("wakatime")
+            .value_name("FILE")
+            .help("Show the time spent in editors per day, from a JSON file of WakaTime summaries, next to the time estimated out of the commits"))
+        .arg(Arg::new("timesheet")
+            .long("timesheet")
+            .value_name("FORMAT")
+            .help("Print a timesheet of the hours estimated per day and project, in and out of working hours, in Markdown or with 'csv' as CSV")
+            .value_parser(["md", "csv"])
+            .num_args(0..=1)
+            .require_equals(true)
+            .default_missing_value("md"))
+        .arg(Arg::new("tickets")
+            .long("tickets")
+            .help("Show the commits and estimated time of every issue tracker ticket referred to in commit subjects, e.g. PROJ-123 or #456")
+            .action(clap::ArgAction::SetTrue))
+        .arg(Arg::new("ticket-pattern")
+            .long("ticket-pattern")
+            .value_name("REGEX")
+            .help("Regular expression of the ticket IDs, its first group being the ID when it has one, replacing the ticket_patterns of the config (repeatable)")
+            .requires("tickets")
+            .action(clap::ArgAction::Append))
+        .arg(Arg::new("diff-last")
+            .long("diff-last")
+            .help("Compare the period with the last snapshot recorded by gitnapped record: new active repos, gitnapped % per project")
+            .action(clap::ArgAction::SetTrue))
+        .arg(Arg::new("leaderboard")
+            .long("leaderboard")
+            .help("Rank authors by out-of-hours commit percentage and estimated time gitnapped")
+            .action(clap::ArgAction::SetTrue))
+        .arg(Arg::new("min-commits")
+            .long("min-commits")
+            .value_name("N")
+            .help("Minimum commits for an author to be ranked in the leaderboard")
+            .value_parser(clap::value_parser!(usize))
+            .default_value("5"))
+        .arg(Arg::new("anonymize")
+            .long("anonymize")
+            .value_name("SCOPE")
+            .help("Replace author names and emails, or with 'all' also repository paths and project names, with stable pseudonyms")
+            .value_parser(Anonymize::NAMES)
+            .num_args(0..=1)
+            .require_equals(true)
+            .default_missing_value("authors")
+            .conflicts_with("repo-details"))
         .arg(Arg::new("most-active-repos")
             .long("most-active-repos")
             .help("How many most active repositories to show")
             .value_name("MOST_ACTIVE_REPOS_COUNT")
             .default_value("5"))
+        .arg(Arg::new("table")
+            .long("table")
+            .help("Show repository listings as aligned tables")
+            .action(clap::ArgAction::SetTrue))
         .arg(Arg::new("silent")
             .long("silent")
             .help("Silent mode, no output")
             .action(clap::ArgAction::SetTrue))
         .arg(Arg::new("working-time")
             .long("working-time")
-            .help("Working hours in 24-hour (HH:MM-HH:MM) or 12-hour (HAM-PM) format")
+            .help("Working hours in 24-hour (HH:MM-HH:MM) or 12-hour (HAM-PM) format, may span midnight (22:00-06:00), comma separate multiple windows")
             .value_name("WORKING_TIME")
             .default_value("09:00-17:00"))
+        .arg(Arg::new("all-days")
+            .long("all-days")
+            .help("Treat every day as a workday, so weekend commits are only judged by the time")
+            .action(clap::ArgAction::SetTrue))
+        .arg(Arg::new("date-source")
+            .long("date-source")
+            .value_name("SOURCE")
+            .help("Read the name and date of each commit from its author or its committer, e.g. for rebased commits")
+            .value_parser(DateSource::NAMES)
+            .default_value("author"))
+        .arg(Arg::new("backend")
+            .long("backend")
+            .value_name("BACKEND")
+            .help("Read the history by running git or with libgit2, which needs no git binary (built with --features libgit2)")
+            .value_parser(Backend::NAMES)
+            .default_value("git"))
+        .arg(Arg::new("incremental")
+            .long("incremental")
+            .help("Keep the commits of every repository between runs, so only the new ones are read from git")
+            .action(clap::ArgAction::SetTrue))
+        .arg(Arg::new("journal")
+            .long("journal")
+            .help("Also count the commits recorded by the post-commit hook that are gone from the history, e.g. squashed ones")
+            .action(clap::ArgAction::SetTrue))
+        .arg(Arg::new("fetch")
+            .long("fetch")
+            .help("Run git fetch in every repository before analyzing, e.g. on a machine other ones push from")
+            .action(clap::ArgAction::SetTrue))
+        .arg(Arg::new("pull")
+            .long("pull")
+            .help("Fast-forward the checked-out branch of every repository to its upstream before analyzing, so commits pushed from elsewhere are counted")
+            .action(clap::ArgAction::SetTrue))
+        .arg(Arg::new("no-cache")
+            .long("no-cache")
+            .help("Analyze every repository again instead of reusing the statistics of a previous run")
+            .action(clap::ArgAction::SetTrue))
+        .arg(Arg::new("timing")
+            .long("timing")
+            .help("Report how long each repository took to analyze, per phase, on stderr")
+            .action(clap::ArgAction::SetTrue))
+        .arg(Arg::new("commit-timezone")
+            .long("commit-timezone")
+            .value_name("TIMEZONE")
+            .help("Timezone commits are checked against the working hours in: the machine's, the author's or UTC")
+            .value_parser(CommitTimezone::NAMES)
+            .default_value("author"))
+        .arg(Arg::new("timezone")
+            .long("timezone")
+            .value_name("TIMEZONE")
+            .help("Evaluate commits in this IANA timezone (e.g. Europe/Rome), whatever the machine's one")
+            .conflicts_with("commit-timezone"))
+        .arg(Arg::new("session-gap")
+            .long("session-gap")
+            .value_name("MINUTES")
+            .help("Maximum gap between two commits of the same work session, used to estimate the hours gitnapped")
+            .value_parser(clap::value_parser!(i64).range(1..))
+            .default_value("45"))
+        .arg(Arg::new("short-sleep-before")
+            .long("short-sleep-before")
+            .value_name("HOUR")
+            .help("Count a day as short sleep when it ended after midnight and the next one started before this hour")
+            .value_parser(clap::value_parser!(u32).range(0..24))
+            .default_value("9"))
+        .arg(Arg::new("min-gap")
+            .long("min-gap")
+            .value_name("DAYS")
+            .help("Minimum number of days without commits reported as an inactivity gap, e.g. a vacation")
+            .value_parser(clap::value_parser!(u64).range(1..))
+            .default_value("7"))
         .arg(Arg::new("ungitnapped")
             .long("ungitnapped")
             .help("Hide gitnapped information from the output")
@@ -111,19 +397,650 @@ fn main() {
             .long("show-total-stats")
             .help("Show total stats across all analyzed entities")
             .action(clap::ArgAction::SetTrue))
+        .arg(Arg::new("format")
+            .short('f')
+            .long("format")
+            .value_name("FORMAT")
+            .help("Output format")
+            .value_parser(OutputFormat::NAMES)
+            .default_value("text"))
         .arg(Arg::new("pretty")
             .long("pretty")
-            .help("Pretty print the output")
+            .help("Pretty print the output (same as --format pretty, indents JSON)")
             .action(clap::ArgAction::SetTrue))
         .arg(Arg::new("json")
             .long("json")
-            .help("Output in JSON format")
+            .help("Output in JSON format (same as --format json)")
+            .action(clap::ArgAction::SetTrue))
+        .arg(Arg::new("tmux")
+            .long("tmux")
+            .help("Print a single line without color codes for the tmux status bar, e.g. with --today")
+            .conflicts_with_all(["format", "json", "summary", "report", "template", "email", "influx-url"])
+            .action(clap::ArgAction::SetTrue))
+        .arg(Arg::new("tmux-format")
+            .long("tmux-format")
+            .value_name("FORMAT")
+            .help("Line printed by --tmux, with the {icon}, {gitnapped}, {commits} and {out_of_hours} placeholders")
+            .requires("tmux"))
+        .arg(Arg::new("summary")
+            .long("summary")
+            .help("One-screen emoji summary (same as --format summary)")
+            .action(clap::ArgAction::SetTrue))
+        .arg(Arg::new("report")
+            .long("report")
+            .value_name("FORMAT")
+            .help("Generate a standalone report (same as --format html)")
+            .value_parser(["html"]))
+        .arg(Arg::new("template")
+            .long("template")
+            .value_name("FILE")
+            .help("Render the report through a custom Tera template")
+            .conflicts_with("report"))
+        .arg(Arg::new("chart")
+            .long("chart")
+            .value_name("FORMAT")
+            .help("Write commit-over-time and gitnapped percentage charts")
+            .value_parser(["svg"]))
+        .arg(Arg::new("chart-dir")
+            .long("chart-dir")
+            .value_name("DIRECTORY")
+            .help("Directory the charts are written to")
+            .default_value("."))
+        .arg(Arg::new("output")
+            .short('o')
+            .long("output")
+            .value_name("FILE")
+            .help("Write the report to a file instead of standard output"))
+        .arg(Arg::new("influx-url")
+            .long("influx-url")
+            .value_name("URL")
+            .help("Write the points of --format influx to InfluxDB at this write URL, with the token of $INFLUX_TOKEN")
+            .conflicts_with("output"))
+        .arg(Arg::new("email")
+            .long("email")
+            .help("Email the report (HTML unless --format says otherwise) with the SMTP settings of the config")
+            .conflicts_with_all(["output", "influx-url"])
             .action(clap::ArgAction::SetTrue))
         .arg(Arg::new("debug")
             .long("debug")
             .help("Enable debug messages")
             .action(clap::ArgAction::SetTrue))
-        .get_matches();
+        .subcommand(ClapCommand::new("compare")
+            .about("Compare the metrics of every project between two periods, e.g. two sprints")
+            .arg(Arg::new("a")
+                .long("a")
+                .value_name("PERIOD")
+                .help("First period: a month (2025-03), a day (2025-03-14) or a range (2025-03-01..2025-03-14)")
+                .required(true))
+            .arg(Arg::new("b")
+                .long("b")
+                .value_name("PERIOD")
+                .help("Second period, in the same formats as --a")
+                .required(true)))
+        .subcommand(ClapCommand::new("init")
+            .about("Scan a directory tree for Git repositories and write a starter config listing them")
+            .arg(Arg::new("root")
+                .value_name("DIRECTORY")
+                .help("Directory to scan")
+                .default_value("."))
+            .arg(Arg::new("output")
+                .short('o')
+                .long("output")
+                .value_name("FILE")
+                .help("Config file to write, in TOML when it ends with .toml")
+                .default_value("gitnapped.yaml"))
+            .arg(Arg::new("depth")
+                .long("depth")
+                .value_name("LEVELS")
+                .help("How many directory levels below DIRECTORY are scanned")
+                .value_parser(clap::value_parser!(usize))
+                .default_value("3"))
+            .arg(Arg::new("interactive")
+                .short('i')
+                .long("interactive")
+                .help("Ask for the category of every repository found")
+                .action(clap::ArgAction::SetTrue))
+            .arg(Arg::new("force")
+                .long("force")
+                .help("Overwrite the config file if it exists")
+                .action(clap::ArgAction::SetTrue)))
+        .subcommand(ClapCommand::new("check")
+            .about("Tell whether committing now would be out of hours, exiting with status 1 if so, e.g. from a pre-commit hook")
+            .arg(Arg::new("repo")
+                .long("repo")
+                .value_name("PATH")
+                .help("Repository committed to")
+                .default_value("."))
+            .arg(Arg::new("at")
+                .long("at")
+                .value_name("DATETIME")
+                .help("Check another time than now (YYYY-MM-DD HH:MM, local time)")))
+        .subcommand(ClapCommand::new("hook")
+            .about("Record every commit in a local journal with a post-commit hook")
+            .subcommand_required(true)
+            .subcommand(ClapCommand::new("install")
+                .about("Install the post-commit hook recording the commits in the journal")
+                .arg(Arg::new("repo")
+                    .long("repo")
+                    .value_name("PATH")
+                    .help("Repository to install the hook in")
+                    .default_value("."))
+                .arg(Arg::new("all")
+                    .long("all")
+                    .help("Install the hook in every repository of the config")
+                    .conflicts_with("repo")
+                    .action(clap::ArgAction::SetTrue))
+                .arg(Arg::new("force")
+                    .long("force")
+                    .help("Replace a post-commit hook not installed by gitnapped")
+                    .action(clap::ArgAction::SetTrue)))
+            .subcommand(ClapCommand::new("record")
+                .about("Record the last commit in the journal, as run by the hook")
+                .hide(true)
+                .arg(Arg::new("repo")
+                    .long("repo")
+                    .value_name("PATH")
+                    .help("Repository committed to")
+                    .default_value("."))))
+        .subcommand(ClapCommand::new("notify")
+            .about("Post a summary of the period to a chat, e.g. from a weekly cron job")
+            .arg(Arg::new("slack-webhook")
+                .long("slack-webhook")
+                .value_name("URL")
+                .help("Slack incoming webhook to post the summary to"))
+            .arg(Arg::new("discord-webhook")
+                .long("discord-webhook")
+                .value_name("URL")
+                .help("Discord webhook to post the summary to"))
+            .group(ArgGroup::new("webhooks")
+                .args(["slack-webhook", "discord-webhook"])
+                .multiple(true)
+                .required(true)))
+        .subcommand(ClapCommand::new("record")
+            .about("Append the statistics of today to the local history, e.g. from a daily cron job")
+            .arg(Arg::new("date")
+                .long("date")
+                .value_name("YYYY-MM-DD")
+                .help("Record another day than today")))
+        .subcommand(ClapCommand::new("prompt")
+            .about("Print the gitnapped percentage of today for a shell prompt, from a cache refreshed in the background")
+            .arg(Arg::new("format")
+                .long("format")
+                .value_name("FORMAT")
+                .help("Status printed, with the {icon}, {gitnapped}, {commits} and {out_of_hours} placeholders")
+                .default_value("{icon} {gitnapped}%"))
+            .arg(Arg::new("json")
+                .long("json")
+                .help("Print the status as JSON")
+                .action(clap::ArgAction::SetTrue))
+            .arg(Arg::new("budget")
+                .long("budget")
+                .value_name("MILLISECONDS")
+                .help("Longest time waited for a refresh before printing the cached status")
+                .value_parser(clap::value_parser!(u64))
+                .default_value("50"))
+            .arg(Arg::new("max-age")
+                .long("max-age")
+                .value_name("SECONDS")
+                .help("Age from which the cached status is refreshed")
+                .value_parser(clap::value_parser!(u64))
+                .default_value("60"))
+            .arg(Arg::new("refresh")
+                .long("refresh")
+                .help("Compute the status and cache it, as run in the background")
+                .hide(true)
+                .action(clap::ArgAction::SetTrue)))
+        .subcommand(ClapCommand::new("serve")
+            .about("Serve a web dashboard of the heatmap, the top projects and the gitnapped trends")
+            .arg(Arg::new("port")
+                .long("port")
+                .value_name("PORT")
+                .help("Port the dashboard is served on")
+                .value_parser(clap::value_parser!(u16))
+                .default_value("8080"))
+            .arg(Arg::new("bind")
+                .long("bind")
+                .value_name("ADDRESS")
+                .help("Address listened on, e.g. 0.0.0.0 to reach the dashboard from other machines")
+                .default_value("127.0.0.1")))
+        .subcommand(ClapCommand::new("trend")
+            .about("Plot the gitnapped percentage and the commits of the recorded history")
+            .arg(Arg::new("by")
+                .long("by")
+                .value_name("PERIOD")
+                .help("Sum the recorded days by week or by month")
+                .value_parser(["week", "month"])
+                .default_value("week"))
+            .arg(Arg::new("last")
+                .long("last")
+                .value_name("COUNT")
+                .help("How many of the latest weeks or months are plotted")
+                .value_parser(clap::value_parser!(usize))
+                .default_value("12")))
+        .subcommand(ClapCommand::new("time-entries")
+            .about("Turn the work sessions of the period into time entries of Toggl Track or Clockify, one per session")
+            .arg(Arg::new("csv")
+                .long("csv")
+                .value_name("FORMAT")
+                .help("Print the entries as a CSV to import, or write them to --output")
+                .value_parser(["toggl", "clockify"]))
+            .arg(Arg::new("output")
+                .short('o')
+                .long("output")
+                .value_name("FILE")
+                .help("File the CSV of --csv is written to instead of standard output")
+                .requires("csv"))
+            .arg(Arg::new("toggl")
+                .long("toggl")
+                .value_name("WORKSPACE_ID")
+                .help("Create the entries in a Toggl Track workspace, with the API token in $TOGGL_API_TOKEN"))
+            .arg(Arg::new("clockify")
+                .long("clockify")
+                .value_name("WORKSPACE_ID")
+                .help("Create the entries in a Clockify workspace, with the API key in $CLOCKIFY_API_KEY"))
+            .group(ArgGroup::new("destination")
+                .args(["csv", "toggl", "clockify"])
+                .required(true)))
+        .subcommand(ClapCommand::new("config")
+            .about("Work with the config file")
+            .subcommand_required(true)
+            .subcommand(ClapCommand::new("validate")
+                .about("Check the config file without analyzing anything: paths, labels, duplicates and settings")
+                .arg(Arg::new("file")
+                    .value_name("FILE")
+                    .help("Config file to check, the one given with -c or found by default when missing"))))
+}
+
+/// Loads the config of the subcommands run from Git hooks: the one given with
+/// `-c` or found by default, the default working hours applying without one.
+fn hook_config(matches: &ArgMatches) -> Result<Config, String> {
+    let config_path = matches
+        .get_one::<String>("config")
+        .cloned()
+        .unwrap_or_else(default_config_path);
+    let profile = matches.get_one::<String>("profile").map(String::as_str);
+    if Path::new(&config_path).exists() {
+        load_config(&config_path, profile)
+    } else {
+        Ok(Config::default())
+    }
+}
+
+/// Loads the config of the subcommands analyzing the period on their own, e.g.
+/// from a cron job: the one given with `-c` or found by default, the current
+/// directory being analyzed without one.
+fn standalone_config(matches: &ArgMatches) -> Result<Config, String> {
+    let config_path = matches
+        .get_one::<String>("config")
+        .cloned()
+        .unwrap_or_else(default_config_path);
+    let profile = matches.get_one::<String>("profile").map(String::as_str);
+    if Path::new(&config_path).exists() {
+        load_config(&config_path, profile)
+    } else {
+        push_to_empty_config(".", None)
+    }
+}
+
+/// Returns the options choosing the config, `-c` with an absolute path and
+/// `--profile`, for gitnapped to be run again with the same config from
+/// another directory.
+fn config_args(matches: &ArgMatches) -> Vec<String> {
+    let mut args = Vec::new();
+    if let Some(config) = matches.get_one::<String>("config") {
+        let config = std::fs::canonicalize(config)
+            .map(|path| path.to_string_lossy().into_owned())
+            .unwrap_or_else(|_| config.clone());
+        args.extend([String::from("-c"), config]);
+    }
+    if let Some(profile) = matches.get_one::<String>("profile") {
+        args.extend([String::from("--profile"), profile.clone()]);
+    }
+    args
+}
+
+/// Builds the command line the post-commit hook runs gitnapped with: this very
+/// binary, with the config and profile it was installed with, as hooks do not
+/// run in the directory gitnapped was run from.
+fn hook_command(matches: &ArgMatches) -> String {
+    let quote = |value: &str| format!("'{}'", value.replace('\'', "'\\''"));
+    let binary = std::env::current_exe()
+        .map(|path| path.to_string_lossy().into_owned())
+        .unwrap_or_else(|_| String::from("gitnapped"));
+    let mut command = quote(&binary);
+    for arg in config_args(matches) {
+        command.push(' ');
+        command.push_str(&quote(&arg));
+    }
+    command
+}
+
+/// Prints the status of today for a shell prompt. The cached status is
+/// printed unless it is too old, in which case a background process refreshes
+/// it, waited for no longer than the budget: a prompt is never held up by the
+/// analysis, showing the previous status, or nothing on the first run of the
+/// day, until the refresh is done.
+///
+/// # Arguments
+/// * `matches` - The parsed command line
+/// * `prompt` - The options of the `prompt` subcommand
+fn print_prompt(matches: &ArgMatches, prompt: &ArgMatches) {
+    let config_path = matches
+        .get_one::<String>("config")
+        .cloned()
+        .unwrap_or_else(default_config_path);
+    // Without a config, the status is the one of the current directory
+    let mut key = config_args(matches).join(" ");
+    if !Path::new(&config_path).exists() {
+        let dir = std::fs::canonicalize(".").unwrap_or_default();
+        key.push_str(&format!(" {}", dir.display()));
+    }
+
+    if prompt.get_flag("refresh") {
+        let today = Local::now().date_naive();
+        let result = standalone_config(matches)
+            .and_then(|config| {
+                // The status only tells the commits, read again from where the last refresh left off
+                Analyzer::new(config)
+                    .without_files()
+                    .cached()
+                    .incremental()
+                    .run(today..=today)
+            })
+            .and_then(|report| write_prompt_status(&key, &PromptStatus::of(today, &report)));
+        unlock_refresh(&key);
+        if let Err(err) = result {
+            eprintln!("{}: {}", "Error".bright_red(), err);
+            process::exit(1);
+        }
+        return;
+    }
+
+    let max_age =
+        std::time::Duration::from_secs(prompt.get_one::<u64>("max-age").copied().unwrap_or(60));
+    let budget =
+        std::time::Duration::from_millis(prompt.get_one::<u64>("budget").copied().unwrap_or(50));
+    let mut status = read_prompt_status(&key);
+    if !status
+        .as_ref()
+        .is_some_and(|status| status.is_fresh(max_age))
+        && lock_refresh(&key)
+    {
+        let started = Instant::now();
+        let child = std::env::current_exe().and_then(|binary| {
+            process::Command::new(binary)
+                .args(config_args(matches))
+                .args(["prompt", "--refresh"])
+                .stdin(process::Stdio::null())
+                .stdout(process::Stdio::null())
+                .stderr(process::Stdio::null())
+                .spawn()
+        });
+        match child {
+            Ok(mut child) => {
+                while started.elapsed() < budget {
+                    if let Ok(Some(_)) = child.try_wait() {
+                        status = read_prompt_status(&key);
+                        break;
+                    }
+                    std::thread::sleep(std::time::Duration::from_millis(5));
+                }
+            }
+            Err(err) => {
+                debug(&format!("Failed to refresh the prompt status: {}", err));
+                unlock_refresh(&key);
+            }
+        }
+    }
+
+    // The status of another day would be misleading
+    let today = Local::now().format("%Y-%m-%d").to_string();
+    let Some(status) = status.filter(|status| status.date == today) else {
+        return;
+    };
+    if prompt.get_flag("json") {
+        println!("{}", serde_json::to_string(&status).unwrap_or_default());
+    } else {
+        let format = prompt
+            .get_one::<String>("format")
+            .map(String::as_str)
+            .unwrap_or("{icon} {gitnapped}%");
+        println!("{}", status.render(format));
+    }
+}
+
+/// Options that cannot be given a default in the config, as they choose it.
+const NO_CONFIG_DEFAULT: [&str; 3] = ["config", "profile", "dir"];
+
+/// Options winning over others without conflicting with them, whose defaults
+/// are dropped when the others are given.
+const OVERRIDDEN_BY: [(&str, &[&str]); 1] = [("period", &["since", "until"])];
+
+/// The command line, with the `defaults` of the config file standing in for
+/// the options it leaves out. Options are read through it like through the
+/// `ArgMatches` of the command line, which it derefs to for the subcommands.
+struct Options {
+    /// The command line as given
+    matches: ArgMatches,
+    /// The defaults that apply, by option id, each parsed on its own
+    defaults: HashMap<String, ArgMatches>,
+    /// Why defaults of the config were left out, reported once the output is set up
+    warnings: Vec<String>,
+}
+
+impl Options {
+    /// Returns the matches an option is read from: its default in the config
+    /// when the command line leaves it out, else the command line.
+    fn source(&self, id: &str) -> &ArgMatches {
+        match self.matches.value_source(id) {
+            None | Some(ValueSource::DefaultValue) => {
+                self.defaults.get(id).unwrap_or(&self.matches)
+            }
+            _ => &self.matches,
+        }
+    }
+
+    fn get_flag(&self, id: &str) -> bool {
+        self.source(id).get_flag(id)
+    }
+
+    fn get_one<T: Any + Clone + Send + Sync + 'static>(&self, id: &str) -> Option<&T> {
+        self.source(id).get_one::<T>(id)
+    }
+
+    fn get_many<T: Any + Clone + Send + Sync + 'static>(
+        &self,
+        id: &str,
+    ) -> Option<ValuesRef<'_, T>> {
+        self.source(id).get_many::<T>(id)
+    }
+
+    fn value_source(&self, id: &str) -> Option<ValueSource> {
+        self.source(id).value_source(id)
+    }
+}
+
+impl Deref for Options {
+    type Target = ArgMatches;
+
+    fn deref(&self) -> &ArgMatches {
+        &self.matches
+    }
+}
+
+/// Parses the `defaults` of the config file, every one of them on its own as
+/// if it was given on the command line, so they go through the checks of the
+/// options they are the defaults of. Flags are turned on with `true`.
+///
+/// # Arguments
+/// * `defaults` - The defaults, by the long name of their option, `_` for `-`
+///
+/// # Returns
+/// * `(Vec<(String, ArgMatches)>, Vec<String>)` - The parsed defaults by option id,
+///   and what is wrong with the others
+fn parse_config_defaults(
+    defaults: &BTreeMap<String, serde_yaml::Value>,
+) -> (Vec<(String, ArgMatches)>, Vec<String>) {
+    let command = cli();
+    let mut parsed = Vec::new();
+    let mut problems = Vec::new();
+    for (name, value) in defaults {
+        let long = name.replace('_', "-");
+        let Some(arg) = command
+            .get_arguments()
+            .find(|arg| arg.get_long() == Some(long.as_str()))
+        else {
+            problems.push(format!("Unknown option '{}' in defaults", name));
+            continue;
+        };
+        let id = arg.get_id().to_string();
+
+        let values: Option<Vec<String>> = match value {
+            serde_yaml::Value::Sequence(values) => values.iter().map(default_value).collect(),
+            value => default_value(value).map(|value| vec![value]),
+        };
+        let args: Option<Vec<String>> = values
+            .filter(|_| !NO_CONFIG_DEFAULT.contains(&id.as_str()))
+            .and_then(|values| {
+                if arg.get_action().takes_values() {
+                    Some(
+                        values
+                            .iter()
+                            .map(|value| format!("--{}={}", long, value))
+                            .collect(),
+                    )
+                } else {
+                    match values.as_slice() {
+                        [flag] if flag == "true" => Some(vec![format!("--{}", long)]),
+                        [flag] if flag == "false" => Some(Vec::new()),
+                        _ => None,
+                    }
+                }
+            });
+        let Some(args) = args else {
+            problems.push(format!("Invalid default for '{}' in defaults", name));
+            continue;
+        };
+        match cli().try_get_matches_from(std::iter::once(String::from("gitnapped")).chain(args)) {
+            Ok(matches) => parsed.push((id, matches)),
+            Err(err) => {
+                let reason = err.to_string();
+                let reason = reason.lines().next().unwrap_or_default();
+                problems.push(format!(
+                    "Invalid default for '{}' in defaults: {}",
+                    name,
+                    reason.trim_start_matches("error: ")
+                ));
+            }
+        }
+    }
+    (parsed, problems)
+}
+
+/// Checks if two options cannot be given together, whichever of them declares
+/// it, or as the only ones of a group, or as an option winning over the other.
+fn conflicting(command: &ClapCommand, option: &Arg, other: &Arg) -> bool {
+    let (id, other_id) = (option.get_id(), other.get_id());
+    command
+        .get_arg_conflicts_with(option)
+        .iter()
+        .any(|arg| arg.get_id() == other_id)
+        || command
+            .get_arg_conflicts_with(other)
+            .iter()
+            .any(|arg| arg.get_id() == id)
+        || command.get_groups().any(|group| {
+            !group.clone().is_multiple()
+                && group.get_args().any(|arg| arg == id)
+                && group.get_args().any(|arg| arg == other_id)
+        })
+        || OVERRIDDEN_BY
+            .iter()
+            .any(|(option, others)| *option == id.as_str() && others.contains(&other_id.as_str()))
+}
+
+/// Fills in the options the command line leaves out with the `defaults` of
+/// the config file, so options given on the command line still win, and so
+/// do the ones conflicting with a default, e.g. `--since` or `--today` with a
+/// default `period`.
+///
+/// # Arguments
+/// * `matches` - The command line
+///
+/// # Returns
+/// * `Options` - The command line with the defaults that apply
+fn with_config_defaults(matches: ArgMatches) -> Options {
+    let mut options = Options {
+        matches,
+        defaults: HashMap::new(),
+        warnings: Vec::new(),
+    };
+    // The config is bypassed with -d, and reported when it cannot be read later on
+    if options.matches.contains_id("dir") {
+        return options;
+    }
+    let config_path = options
+        .matches
+        .get_one::<String>("config")
+        .cloned()
+        .unwrap_or_else(default_config_path);
+    let profile = options
+        .matches
+        .get_one::<String>("profile")
+        .map(String::as_str);
+    let Ok(config) = read_config(&config_path, profile) else {
+        return options;
+    };
+    if config.defaults.is_empty() {
+        return options;
+    }
+
+    let (defaults, problems) = parse_config_defaults(&config.defaults);
+    options.warnings = problems
+        .into_iter()
+        .map(|problem| format!("{}: {}", config_path, problem))
+        .collect();
+    let command = cli();
+    let given: Vec<&Arg> = command
+        .get_arguments()
+        .filter(|arg| {
+            options.matches.value_source(arg.get_id().as_str()) == Some(ValueSource::CommandLine)
+        })
+        .collect();
+    for (id, default) in defaults {
+        let Some(arg) = command
+            .get_arguments()
+            .find(|arg| arg.get_id() == id.as_str())
+        else {
+            continue;
+        };
+        if given
+            .iter()
+            .any(|other| other.get_id() == arg.get_id() || conflicting(&command, arg, other))
+        {
+            debug(&format!(
+                "Not using the default of {}, given on the command line",
+                id
+            ));
+            continue;
+        }
+        options.defaults.insert(id, default);
+    }
+    options
+}
+
+/// Turns a value of the `defaults` of the config into a command line value.
+fn default_value(value: &serde_yaml::Value) -> Option<String> {
+    match value {
+        serde_yaml::Value::Bool(flag) => Some(flag.to_string()),
+        serde_yaml::Value::Number(number) => Some(number.to_string()),
+        serde_yaml::Value::String(text) => Some(text.clone()),
+        _ => None,
+    }
+}
+
+fn main() {
+    let matches = with_config_defaults(cli().get_matches());
 
     let default_dir = String::from("");
     let dir = matches.get_one::<String>("dir").unwrap_or(&default_dir);
@@ -137,21 +1054,447 @@ fn main() {
     let by_categories = matches.get_flag("categories");
     let by_projects = matches.get_flag("projects");
     let show_repo_details = matches.get_flag("repo-details");
-    let show_filetypes = matches.get_flag("filetypes");
+    let filetypes = matches
+        .get_one::<String>("filetypes")
+        .and_then(|name| FileTypeOrder::from_name(name));
+    let touched_only = matches.get_flag("touched-only");
+    let backend_name = matches
+        .get_one::<String>("backend")
+        .map(String::as_str)
+        .unwrap_or("git");
+    let Some(backend) = Backend::from_name(backend_name) else {
+        eprintln!(
+            "{}: gitnapped was built without the {} backend, rebuild it with --features {}",
+            "Error".bright_red(),
+            backend_name,
+            backend_name
+        );
+        process::exit(1);
+    };
+    let mut history = HistoryFilter {
+        merges: if matches.get_flag("no-merges") {
+            MergeFilter::Exclude
+        } else if matches.get_flag("merges-only") {
+            MergeFilter::Only
+        } else {
+            MergeFilter::Include
+        },
+        first_parent: matches.get_flag("first-parent"),
+        since_tag: matches.get_one::<String>("since-tag").cloned(),
+        until_tag: matches.get_one::<String>("until-tag").cloned(),
+        pathspecs: matches
+            .get_many::<String>("pathspec")
+            .into_iter()
+            .flatten()
+            .cloned()
+            .collect(),
+        backend,
+        incremental: matches.get_flag("incremental"),
+        journal: matches.get_flag("journal"),
+        forges: BTreeMap::new(),
+        changes: false,
+    };
     let show_most_active_day = matches.get_flag("most-active-day");
+    let show_heatmap = matches.get_flag("heatmap");
+    let show_by_hour = matches.get_flag("by-hour");
+    let show_punch_card = matches.get_flag("punchcard");
+    let show_by_author = matches.get_flag("by-author");
+    let show_leaderboard = matches.get_flag("leaderboard");
+    let show_commit_sizes = matches.get_flag("commit-sizes");
+    let show_trend = matches.get_flag("trend");
+    let diff_last = matches.get_flag("diff-last");
+    let anonymize = matches
+        .get_one::<String>("anonymize")
+        .and_then(|name| Anonymize::from_name(name));
+    let as_table = matches.get_flag("table");
     let hide_gitnapped_stats = matches.get_flag("ungitnapped");
     let debug_mode = matches.get_flag("debug");
     let silent_mode = matches.get_flag("silent");
+    let pretty = matches.get_flag("pretty");
+    let template_path = matches.get_one::<String>("template").cloned();
+    let chart_format = matches.get_one::<String>("chart").cloned();
+    let email = matches.get_flag("email");
+    // --json, --report and --pretty predate --format and are kept as shorthands
+    let output_format = if matches.get_flag("json") {
+        OutputFormat::Json
+    } else if matches.get_flag("summary") {
+        OutputFormat::Summary
+    } else if let Some(report_format) = matches.get_one::<String>("report") {
+        OutputFormat::from_name(report_format).unwrap_or(OutputFormat::Html)
+    } else {
+        match matches
+            .get_one::<String>("format")
+            .and_then(|name| OutputFormat::from_name(name))
+            .unwrap_or(OutputFormat::Text)
+        {
+            // An email is an HTML page unless another format is asked for
+            OutputFormat::Text if email => OutputFormat::Html,
+            OutputFormat::Text if pretty => OutputFormat::Pretty,
+            format => format,
+        }
+    };
+    let notify = matches.subcommand_matches("notify");
+    let tmux_format = matches.get_flag("tmux").then(|| {
+        matches
+            .get_one::<String>("tmux-format")
+            .map(String::as_str)
+            .unwrap_or(DEFAULT_TMUX_FORMAT)
+            .to_string()
+    });
+    // Notifications and the tmux line are rendered from a report like the machine readable formats
+    let machine_output = !output_format.is_text()
+        || template_path.is_some()
+        || notify.is_some()
+        || tmux_format.is_some();
+    let influx_url = matches.get_one::<String>("influx-url").cloned();
+    if influx_url.is_some() && (output_format != OutputFormat::Influx || template_path.is_some()) {
+        eprintln!(
+            "{}: --influx-url writes the points of --format influx",
+            "Error".bright_red()
+        );
+        process::exit(1);
+    }
+    let count_files = matches.get_flag("show-total-stats")
+        || filetypes.is_some()
+        || show_repo_details
+        || touched_only
+        || as_table
+        || (machine_output
+            && notify.is_none()
+            && tmux_format.is_none()
+            && !matches!(output_format, OutputFormat::Summary | OutputFormat::Influx))
+        || matches!(sort_by.as_str(), "files" | "lines");
+    history.changes = count_files
+        || machine_output
+        || show_commit_sizes
+        || show_trend
+        || matches!(matches.subcommand_name(), Some("compare" | "record"));
+    let file_scope = match (count_files, touched_only) {
+        (false, _) => FileScope::Skip,
+        (true, false) => FileScope::All,
+        (true, true) => FileScope::Touched,
+    };
     let most_active_repos_count = matches
         .get_one::<String>("most-active-repos")
         .and_then(|s| s.parse::<usize>().ok())
         .unwrap_or(5);
+    let session_gap = matches.get_one::<i64>("session-gap").copied().unwrap_or(45);
+    let short_sleep_before = matches
+        .get_one::<u32>("short-sleep-before")
+        .copied()
+        .unwrap_or(9);
+    let min_gap_days = matches.get_one::<u64>("min-gap").copied().unwrap_or(7) as usize;
 
     let mut mandatory_author = false; // An author is mandatory if a directory is provided
     let mut bypass_config = false; // Config is bypassed if a directory is provided
 
     init_debug_mode(debug_mode);
-    init_silent_mode(silent_mode);
+    // Machine readable output must not be mixed with the human readable log lines
+    let csv_entries = matches
+        .subcommand_matches("time-entries")
+        .and_then(|entries| entries.get_one::<String>("csv"))
+        .cloned();
+    let timesheet_format = matches.get_one::<String>("timesheet").cloned();
+    init_silent_mode(
+        silent_mode || machine_output || csv_entries.is_some() || timesheet_format.is_some(),
+    );
+    // config validate reports the defaults as problems of its own
+    for warning in matches
+        .warnings
+        .iter()
+        .filter(|_| matches.subcommand_name() != Some("config"))
+    {
+        log(&format!("{}", format!("Warning: {}", warning).bright_red()));
+    }
+    // Time entries are made of the real projects, commit subjects and email
+    if anonymize.is_some() && matches.subcommand_name() == Some("time-entries") {
+        eprintln!(
+            "{}: --anonymize cannot hide the projects, commit subjects and email time-entries exports",
+            "Error".bright_red()
+        );
+        process::exit(1);
+    }
+    // Debug messages are printed too often for a progress bar to be of any help
+    let show_progress = !(silent_mode || machine_output || debug_mode);
+    if matches.get_flag("timing") {
+        timing::enable();
+    }
+
+    // Writing a starter config analyzes nothing
+    if let Some(init) = matches.subcommand_matches("init") {
+        let output = init
+            .get_one::<String>("output")
+            .map(|s| s.as_str())
+            .unwrap_or("gitnapped.yaml");
+        let result = write_starter_config(
+            init.get_one::<String>("root")
+                .map(|s| s.as_str())
+                .unwrap_or("."),
+            output,
+            init.get_one::<usize>("depth").copied().unwrap_or(3),
+            init.get_flag("interactive"),
+            init.get_flag("force"),
+        );
+        match result {
+            Ok(count) => log(&format!(
+                "{} {} {} {}",
+                "Wrote".bright_yellow(),
+                count.to_string().bright_cyan(),
+                "repositories to".bright_yellow(),
+                output.bright_cyan()
+            )),
+            Err(err) => {
+                eprintln!("{}: {}", "Error".bright_red(), err);
+                process::exit(1);
+            }
+        }
+        return;
+    }
+
+    // Checking the time of a commit is run from hooks, so it skips the analysis altogether
+    if let Some(check) = matches.subcommand_matches("check") {
+        let config = hook_config(&matches);
+        let at = match check.get_one::<String>("at") {
+            Some(at) => NaiveDateTime::parse_from_str(at, "%Y-%m-%d %H:%M")
+                .ok()
+                .and_then(|at| at.and_local_timezone(Local).single())
+                .ok_or_else(|| format!("Invalid --at time '{}', expected YYYY-MM-DD HH:MM", at)),
+            None => Ok(Local::now()),
+        };
+        let repo = check
+            .get_one::<String>("repo")
+            .map(String::as_str)
+            .unwrap_or(".");
+        let result = config.and_then(|config| {
+            at.and_then(|at| check_commit_time(&config, repo, at.fixed_offset()))
+        });
+        match result {
+            Ok(check) if check.within_hours => log(&format!(
+                "{} {} ({})",
+                "Within working hours:".bright_green(),
+                check.time.format("%a %H:%M").to_string().bright_cyan(),
+                check.schedule
+            )),
+            Ok(check) => {
+                log(&format!(
+                    "{} {} {} ({}). {}",
+                    "Out of hours:".bright_red(),
+                    check.time.format("%a %H:%M").to_string().bright_cyan(),
+                    "is outside the working hours".bright_yellow(),
+                    check.schedule,
+                    "Maybe it can wait?".bright_yellow()
+                ));
+                process::exit(1);
+            }
+            Err(err) => {
+                eprintln!("{}: {}", "Error".bright_red(), err);
+                process::exit(1);
+            }
+        }
+        return;
+    }
+
+    match matches
+        .subcommand_matches("hook")
+        .and_then(|hook| hook.subcommand())
+    {
+        Some(("install", install)) => {
+            let repos = if install.get_flag("all") {
+                let config_path = matches
+                    .get_one::<String>("config")
+                    .cloned()
+                    .unwrap_or_else(default_config_path);
+                let profile = matches.get_one::<String>("profile").map(String::as_str);
+                match load_config(&config_path, profile) {
+                    Ok(config) => {
+                        let mut seen = HashSet::new();
+                        parse_repos_from_config(&config)
+                            .into_iter()
+                            .map(|repo| repo.path)
+                            .filter(|path| {
+                                seen.insert(
+                                    std::fs::canonicalize(path).unwrap_or_else(|_| path.into()),
+                                )
+                            })
+                            .collect()
+                    }
+                    Err(err) => {
+                        eprintln!("{}: {}", "Error".bright_red(), err);
+                        process::exit(1);
+                    }
+                }
+            } else {
+                install
+                    .get_one::<String>("repo")
+                    .cloned()
+                    .into_iter()
+                    .collect::<Vec<String>>()
+            };
+
+            let command = hook_command(&matches);
+            let mut failed = false;
+            for repo in &repos {
+                match install_hook(repo, &command, install.get_flag("force")) {
+                    Ok(path) => log(&format!(
+                        "{} {}",
+                        "Installed".bright_yellow(),
+                        path.display().to_string().bright_cyan()
+                    )),
+                    Err(err) => {
+                        eprintln!("{}: {}", "Error".bright_red(), err);
+                        failed = true;
+                    }
+                }
+            }
+            if failed {
+                process::exit(1);
+            }
+            return;
+        }
+        Some(("record", record)) => {
+            let repo = record
+                .get_one::<String>("repo")
+                .map(String::as_str)
+                .unwrap_or(".");
+            match hook_config(&matches).and_then(|config| record_commit(&config, repo)) {
+                Ok(entry) => log(&format!(
+                    "{} {} {}",
+                    "Recorded".bright_yellow(),
+                    entry.hash.get(..7).unwrap_or(&entry.hash).bright_cyan(),
+                    if entry.within_hours {
+                        "within working hours".bright_green()
+                    } else {
+                        "out of hours".bright_red()
+                    }
+                )),
+                Err(err) => {
+                    eprintln!("{}: {}", "Error".bright_red(), err);
+                    process::exit(1);
+                }
+            }
+            return;
+        }
+        _ => {}
+    }
+
+    // Recording a day analyzes it with the settings of the config alone
+    if let Some(record) = matches.subcommand_matches("record") {
+        let date = match record.get_one::<String>("date") {
+            Some(date) => NaiveDate::parse_from_str(date, "%Y-%m-%d")
+                .map_err(|_| format!("Invalid --date '{}', expected YYYY-MM-DD", date)),
+            None => Ok(Local::now().date_naive()),
+        };
+        // Without a config, the current directory is recorded as for a report
+        let config = standalone_config(&matches);
+        let result = date.and_then(|date| {
+            let report = config.and_then(|config| Analyzer::new(config).run(date..=date))?;
+            let snapshot = Snapshot::of(date, &report);
+            append_snapshot(&snapshot).map(|path| (snapshot, path))
+        });
+        match result {
+            Ok((snapshot, path)) => log(&format!(
+                "{} {} {} {} {} {}",
+                "Recorded".bright_yellow(),
+                snapshot.date.bright_cyan(),
+                "with".bright_yellow(),
+                format!(
+                    "{} commits, {}% gitnapped",
+                    snapshot.commits, snapshot.gitnapped_percentage
+                )
+                .bright_cyan(),
+                "in".bright_yellow(),
+                path.display().to_string().bright_cyan()
+            )),
+            Err(err) => {
+                eprintln!("{}: {}", "Error".bright_red(), err);
+                process::exit(1);
+            }
+        }
+        return;
+    }
+
+    if let Some(prompt) = matches.subcommand_matches("prompt") {
+        print_prompt(&matches, prompt);
+        return;
+    }
+
+    if let Some(serve) = matches.subcommand_matches("serve") {
+        let address = format!(
+            "{}:{}",
+            serve
+                .get_one::<String>("bind")
+                .map(String::as_str)
+                .unwrap_or("127.0.0.1"),
+            serve.get_one::<u16>("port").copied().unwrap_or(8080)
+        );
+        if let Err(err) = serve_dashboard(&address, &|| standalone_config(&matches)) {
+            eprintln!("{}: {}", "Error".bright_red(), err);
+            process::exit(1);
+        }
+        return;
+    }
+
+    if let Some(trend) = matches.subcommand_matches("trend") {
+        let unit = trend
+            .get_one::<String>("by")
+            .map(String::as_str)
+            .unwrap_or("week");
+        let last = trend.get_one::<usize>("last").copied().unwrap_or(12);
+        let periods = snapshot_periods(&read_snapshots(), unit == "month");
+        if periods.is_empty() {
+            eprintln!(
+                "{}: No recorded history yet, record days with gitnapped record",
+                "Error".bright_red()
+            );
+            process::exit(1);
+        }
+        print_snapshot_trend(&periods[periods.len().saturating_sub(last)..], unit);
+        return;
+    }
+
+    if let Some(("validate", validate)) = matches
+        .subcommand_matches("config")
+        .and_then(|config| config.subcommand())
+    {
+        let default_config = default_config_path();
+        let config_path = validate
+            .get_one::<String>("file")
+            .or(matches.get_one::<String>("config"))
+            .unwrap_or(&default_config);
+        let mut issues = validate_config(config_path);
+        // Only the command line knows which options can be given a default
+        if let Ok(config) = read_config(config_path, None) {
+            let (_, problems) = parse_config_defaults(&config.defaults);
+            issues.extend(problems.into_iter().map(|message| Issue {
+                file: config_path.to_string(),
+                line: None,
+                message,
+            }));
+        }
+        for issue in &issues {
+            println!("{}: {}", "Error".bright_red(), issue);
+        }
+        if !issues.is_empty() {
+            eprintln!(
+                "{} {} {}",
+                issues.len().to_string().bright_red(),
+                if issues.len() == 1 {
+                    "problem found in"
+                } else {
+                    "problems found in"
+                }
+                .bright_yellow(),
+                config_path.bright_cyan()
+            );
+            process::exit(1);
+        }
+        log(&format!(
+            "{} {}",
+            config_path.bright_cyan(),
+            "is valid".bright_green()
+        ));
+        return;
+    }
 
     // If a directory is provided, we need to
     if !dir.is_empty() {
@@ -162,13 +1505,14 @@ fn main() {
 
     // Initialize config variable outside of if/else blocks
     let config = if !bypass_config {
-        let default_config = String::from("gitnapped.yaml");
+        let default_config = default_config_path();
         let config_path = matches
             .get_one::<String>("config")
             .unwrap_or(&default_config);
 
         // Try to load config, if it doesn't exist, use current directory
-        match load_config(config_path) {
+        let profile = matches.get_one::<String>("profile").map(String::as_str);
+        match load_config(config_path, profile) {
             Ok(config) => {
                 debug(&format!("Loaded config from {}", config_path));
                 log(&format!(
@@ -178,11 +1522,16 @@ fn main() {
                 ));
                 config
             }
+            // A profile only makes sense with the config file it belongs to
+            Err(err) if profile.is_some() => {
+                eprintln!("{}: {}", "Error".bright_red(), err);
+                process::exit(1);
+            }
             Err(_err) => {
-                debug(&format!("Using current directory as fallback"));
-                match push_to_empty_config(".") {
+                debug("Using current directory as fallback");
+                match push_to_empty_config(".", None) {
                     Ok(config) => {
-                        debug(&format!("Loaded empty config"));
+                        debug("Loaded empty config");
                         log(&format!(
                             "{}",
                             "Analyzing current directory".bright_yellow()
@@ -197,10 +1546,14 @@ fn main() {
             }
         }
     } else {
-        debug(&format!("Loading empty config"));
-        match push_to_empty_config(&dir) {
+        debug("Loading empty config");
+        let scan_depth = matches
+            .get_flag("recursive")
+            .then(|| matches.get_one::<usize>("max-depth").copied())
+            .flatten();
+        match push_to_empty_config(dir, scan_depth) {
             Ok(config) => {
-                debug(&format!("Loaded empty config"));
+                debug("Loaded empty config");
                 log(&format!(
                     "{} {}",
                     "Analyzing directory:".bright_yellow(),
@@ -215,12 +1568,33 @@ fn main() {
         }
     };
 
-    let config_author = config.author.clone();
-    let cli_author = matches.get_one::<String>("author").cloned();
+    history.forges = config.forges.clone();
+    if email && config.email.is_none() {
+        eprintln!(
+            "{}: --email needs an email section with the SMTP settings in the config",
+            "Error".bright_red()
+        );
+        process::exit(1);
+    }
+
+    let config_author = config.authors();
+    let mut cli_author: Vec<String> = matches
+        .get_many::<String>("author")
+        .map(|authors| authors.cloned().collect())
+        .unwrap_or_default();
     let mut all_authors = matches.get_flag("all-authors");
 
-    if mandatory_author {
-        if cli_author.is_none() {
+    if mandatory_author && cli_author.is_empty() && !all_authors {
+        // Default to the identity git commits with in that directory
+        if let Some(git_author) = git_config_author(dir) {
+            log(&format!(
+                "{} {} {}",
+                "No author provided, using git config identity".bright_yellow(),
+                git_author.green(),
+                "(use --all-authors for everyone)".bright_yellow()
+            ));
+            cli_author = vec![git_author];
+        } else {
             log(&format!(
                 "{}",
                 "Warning: No author provided, assuming all-authors mode".bright_yellow()
@@ -231,19 +1605,19 @@ fn main() {
 
     // Priority: 1) all-authors flag, 2) author CLI arg, 3) config file author
     let author_filter = if all_authors {
-        None // Don't filter by author, show commits from everyone
-    } else if let Some(a) = cli_author {
-        Some(a) // Use the author specified on the command line
+        Vec::new() // Don't filter by author, show commits from everyone
+    } else if !cli_author.is_empty() {
+        cli_author // Use the authors specified on the command line
     } else {
-        config_author // Use the author from the config file (could be None)
+        config_author // Use the authors from the config file (could be empty)
     };
 
-    // Display information about the author name being used as a filter
-    if let Some(a) = &author_filter {
+    // Display information about the author names being used as a filter
+    if !author_filter.is_empty() {
         log(&format!(
             "{}: {}",
             "Author filter".bright_yellow(),
-            a.green()
+            author_filter.join(", ").green()
         ));
     } else {
         log(&format!(
@@ -252,7 +1626,111 @@ fn main() {
         ));
     }
 
-    if let Some(period) = matches.get_one::<String>("period") {
+    let excluded_authors: Vec<String> = config
+        .exclude_authors
+        .iter()
+        .cloned()
+        .chain(
+            matches
+                .get_many::<String>("exclude-author")
+                .into_iter()
+                .flatten()
+                .cloned(),
+        )
+        .collect();
+    if !excluded_authors.is_empty() {
+        log(&format!(
+            "{}: {}",
+            "Excluded authors".bright_yellow(),
+            excluded_authors.join(", ").green()
+        ));
+    }
+    let mut author_filter = match AuthorFilter::new(author_filter, &excluded_authors) {
+        Ok(author_filter) => author_filter,
+        Err(err) => {
+            eprintln!("{}: {}", "Error".bright_red(), err);
+            process::exit(1);
+        }
+    };
+    author_filter.source = matches
+        .get_one::<String>("date-source")
+        .and_then(|name| DateSource::from_name(name))
+        .unwrap_or_default();
+
+    // A named period, either as a flag of its own or as --period value
+    let period_preset = PERIOD_PRESETS
+        .iter()
+        .map(|(name, _)| *name)
+        .find(|name| matches.get_flag(name))
+        .or_else(|| {
+            matches
+                .get_one::<String>("period")
+                .map(|period| period.as_str())
+                .filter(|period| PERIOD_PRESETS.iter().any(|(name, _)| name == period))
+        });
+    let week_start = match config.week_start.as_deref().map(str::parse::<Weekday>) {
+        None => Weekday::Mon,
+        Some(Ok(weekday)) => weekday,
+        Some(Err(_)) => {
+            eprintln!(
+                "{}: Unknown weekday '{}' in week_start",
+                "Error".bright_red(),
+                config.week_start.as_deref().unwrap_or_default()
+            );
+            process::exit(1);
+        }
+    };
+
+    // A range of whole days: the week or month anchored to a date, or a named period
+    let day_range = if let Some(date) = matches.get_one::<String>("week-of") {
+        match NaiveDate::parse_from_str(date, "%Y-%m-%d") {
+            Ok(date) => Some(week_range(date, week_start)),
+            Err(_) => {
+                eprintln!(
+                    "{}: Invalid --week-of date '{}', expected YYYY-MM-DD",
+                    "Error".bright_red(),
+                    date
+                );
+                process::exit(1);
+            }
+        }
+    } else if let Some(month) = matches.get_one::<String>("month-of") {
+        match month_range(month) {
+            Some(range) => Some(range),
+            None => {
+                eprintln!(
+                    "{}: Invalid --month-of month '{}', expected YYYY-MM",
+                    "Error".bright_red(),
+                    month
+                );
+                process::exit(1);
+            }
+        }
+    } else if let Some(fiscal_year @ ("FY" | "last-FY")) = matches
+        .get_one::<String>("period")
+        .map(|period| period.as_str())
+    {
+        let start = config.fiscal_year_start.as_deref().unwrap_or("01-01");
+        let Some(start) = parse_fiscal_year_start(start) else {
+            eprintln!(
+                "{}: Invalid fiscal_year_start '{}', expected MM-DD like 04-01",
+                "Error".bright_red(),
+                start
+            );
+            process::exit(1);
+        };
+        fiscal_year_range(Local::now().date_naive(), start, fiscal_year == "last-FY")
+    } else {
+        period_preset
+            .and_then(|preset| period_preset_range(preset, Local::now().date_naive(), week_start))
+    };
+
+    if let Some((first_day, last_day)) = day_range {
+        since = format!("{} 00:00:00", first_day.format("%Y-%m-%d"));
+        until = format!("{} 23:59:59", last_day.format("%Y-%m-%d"));
+
+        debug(&format!("Using period from {} to {}", since, until));
+    } else if let Some(period) = matches.get_one::<String>("period") {
         // Parse relative time period
         if let Some(start_date) = parse_period(period) {
             let now = Local::now();
@@ -269,7 +1747,7 @@ fn main() {
                 "{} '{}' - {}",
                 "Warning: Invalid period format".bright_red(),
                 period,
-                "Expected format like 6M, 2Y, 5D, 12H".yellow()
+                "Expected format like 6M, 2Y, 5D, 12H or a name like this-week".yellow()
             ));
 
             let now = Local::now();
@@ -297,51 +1775,374 @@ fn main() {
             .unwrap_or_else(|| now.format("%Y-%m-%d").to_string());
     }
 
-    log(&format!(
-        "{} {} {} {}",
-        "Analyzing repos from".bright_yellow(),
-        since.bright_cyan(),
-        "to".bright_yellow(),
-        until.bright_cyan()
-    ));
+    // Give bare dates an explicit time, otherwise git uses the current time of day
+    let until_inclusive = matches
+        .get_one::<bool>("until-inclusive")
+        .copied()
+        .unwrap_or(true);
+    let since = with_time_of_day(&since, "00:00:00");
+    let until = with_time_of_day(
+        &until,
+        if until_inclusive {
+            "23:59:59"
+        } else {
+            "00:00:00"
+        },
+    );
+
+    update_clones(&config);
+    if matches.get_flag("fetch") || matches.get_flag("pull") {
+        fetch_repositories(&parse_repos_from_config(&config), matches.get_flag("pull"));
+    }
+
+    // Dates we do not parse ourselves (2.weeks.ago, noon yesterday) are resolved once
+    // by git, so every repository is analyzed over the same period
+    let since_bound = parse_range_bound(&since).or_else(|| resolve_git_date(&since));
+    let until_bound = parse_range_bound(&until).or_else(|| resolve_git_date(&until));
+
+    // Reject ranges git would silently turn into an empty report
+    let (since, until) = match (since_bound, until_bound) {
+        (Some(start), Some(end)) if start <= end => (
+            start.format("%Y-%m-%d %H:%M:%S").to_string(),
+            end.format("%Y-%m-%d %H:%M:%S").to_string(),
+        ),
+        (start, end) => {
+            let err = match (start, end) {
+                (None, _) => format!("Invalid --since date '{}'", since),
+                (_, None) => format!("Invalid --until date '{}'", until),
+                _ => format!(
+                    "The start of the period ({}) is after its end ({})",
+                    since, until
+                ),
+            };
+            eprintln!(
+                "{}: {} - {}",
+                "Error".bright_red(),
+                err,
+                "Expected dates like 2025-04-29, 2025-04-29 18:30:00 or 2.weeks.ago, with --since before --until"
+                    .yellow()
+            );
+            process::exit(1);
+        }
+    };
+
+    // The compare subcommand analyzes its own periods
+    if matches.subcommand_name() != Some("compare") {
+        log(&format!(
+            "{} {} {} {}",
+            "Analyzing repos from".bright_yellow(),
+            since.bright_cyan(),
+            "to".bright_yellow(),
+            until.bright_cyan()
+        ));
+    }
+    for (bound, tag) in [("since", &history.since_tag), ("until", &history.until_tag)] {
+        if let Some(tag) = tag {
+            log(&format!(
+                "{} {} {}",
+                format!("Using {} tag", bound).bright_yellow(),
+                tag.bright_cyan(),
+                "in repositories that have it".bright_yellow()
+            ));
+        }
+    }
 
     // Parse repository info to use for both categories and projects
-    let repo_infos = parse_repos_from_config(&config);
+    let mut repo_infos = parse_repos_from_config(&config);
 
     // Create a mapping between original strings and clean paths
-    let repo_path_map = create_repo_path_map(&config);
 
-    let working_time = matches.get_one::<String>("working-time").unwrap();
-    let working_hours = if let Some((start_hour, start_min, end_hour, end_min)) =
-        parse_working_time(working_time)
-    {
-        debug(&format!(
-            "Working hours: {:02}:{:02}-{:02}:{:02}",
-            start_hour, start_min, end_hour, end_min
-        ));
-        Some((start_hour, start_min, end_hour, end_min))
+    // Priority: 1) working-time CLI arg, 2) config file working_time, 3) default
+    let working_time = match (matches.value_source("working-time"), &config.working_time) {
+        (Some(ValueSource::CommandLine), _) | (_, None) => matches
+            .get_one::<String>("working-time")
+            .cloned()
+            .unwrap_or_else(|| DEFAULT_WORKING_TIME.to_string()),
+        (_, Some(spec)) => spec.as_spec(),
+    };
+    let default_hours = if let Some(working_hours) = WorkingHours::parse(&working_time) {
+        working_hours
     } else {
         log(&format!(
             "{} '{}' - {}",
             "Warning: Invalid working time format".bright_red(),
             working_time,
-            "Expected format like 09:00-17:00 or 9AM-5PM".yellow()
+            "Expected format like 09:00-17:00, 9AM-5PM or 09:00-12:30,14:00-18:00".yellow()
         ));
         process::exit(1);
     };
+    let schedule = Schedule::with_overrides(default_hours, &config.working_time_by_day).and_then(
+        |mut schedule| {
+            schedule.timezone = match matches.get_one::<String>("timezone") {
+                Some(name) => CommitTimezone::named(name)?,
+                None => matches
+                    .get_one::<String>("commit-timezone")
+                    .and_then(|name| CommitTimezone::from_name(name))
+                    .unwrap_or_default(),
+            };
+            if matches.get_flag("all-days") {
+                schedule.workdays = [true; 7];
+            } else if let Some(workdays) = &config.workdays {
+                schedule.set_workdays(workdays)?;
+            }
+            Ok(schedule)
+        },
+    );
+    let schedules = schedule.and_then(|schedule| parse_schedules_from_config(&config, schedule));
+    let working_hours = match schedules {
+        Ok(schedules) => schedules,
+        Err(err) => {
+            eprintln!("{}: {}", "Error".bright_red(), err);
+            process::exit(1);
+        }
+    };
+    debug(&format!("Working hours: {}", working_hours.global));
+    for (path, schedule) in &working_hours.repos {
+        debug(&format!("Working hours of {}: {}", path, schedule));
+    }
+    for member in &working_hours.team {
+        debug(&format!(
+            "Working hours of team member {}: {}",
+            member.pattern, member.schedule
+        ));
+    }
+
+    let test_patterns = match &config.test_patterns {
+        Some(patterns) => match TestPatterns::new(patterns) {
+            Ok(patterns) => patterns,
+            Err(err) => {
+                eprintln!("{}: {}", "Error".bright_red(), err);
+                process::exit(1);
+            }
+        },
+        None => TestPatterns::default(),
+    };
+    // Statistics of unchanged repositories are reused across runs with the same options
+    let cache = if matches.get_flag("no-cache") {
+        None
+    } else {
+        ResultCache::open()
+    };
+
+    // Every repository of the config, once however many times it is listed
+    let repo_count = repo_infos
+        .iter()
+        .map(RepoInfo::canonical_key)
+        .collect::<HashSet<_>>()
+        .len();
+
+    if let Some(compare) = matches.subcommand_matches("compare") {
+        if show_progress {
+            progress::start(repo_count * 2);
+        }
+        let mut periods = Vec::new();
+        for name in ["a", "b"] {
+            let value = compare.get_one::<String>(name).map(|s| s.as_str());
+            let Some((first_day, last_day)) = value.and_then(parse_day_range) else {
+                eprintln!(
+                    "{}: Invalid --{} period '{}', expected YYYY-MM, YYYY-MM-DD or YYYY-MM-DD..YYYY-MM-DD",
+                    "Error".bright_red(),
+                    name,
+                    value.unwrap_or_default()
+                );
+                process::exit(1);
+            };
+            let since = format!("{} 00:00:00", first_day.format("%Y-%m-%d"));
+            let until = format!("{} 23:59:59", last_day.format("%Y-%m-%d"));
+            // Repositories are analyzed once, even when listed in several projects
+            let (_, repo_stats) = analyze_all_categories(
+                &config,
+                &author_filter,
+                &since,
+                &until,
+                false,
+                false,
+                None,
+                FileScope::Skip,
+                &history,
+                Some(&working_hours),
+                session_gap,
+                &test_patterns,
+                cache.as_ref(),
+                &mut |_, _| {},
+            );
+            let projects = analyze_all_projects(
+                &repo_infos,
+                &repo_stats.iter().cloned().collect(),
+                &author_filter,
+                &since,
+                &until,
+                false,
+                false,
+                None,
+                FileScope::Skip,
+                &history,
+                Some(&working_hours),
+                session_gap,
+                &test_patterns,
+                cache.as_ref(),
+            );
+            let stats: Vec<RepoStats> = repo_stats.into_iter().map(|(_, stats)| stats).collect();
+            periods.push((since, until, projects, aggregate_stats(&stats)));
+        }
+        progress::finish();
+        let (a_since, a_until, a_projects, a_total) = &periods[0];
+        let (b_since, b_until, b_projects, b_total) = &periods[1];
+
+        let mut names: Vec<&String> = a_projects.iter().map(|project| &project.name).collect();
+        names.sort();
+        let stats_of = |projects: &[ProjectStats], name: &str| {
+            projects
+                .iter()
+                .find(|project| project.name == name)
+                .map(|project| project.stats.clone())
+                .unwrap_or_default()
+        };
+        let mut rows: Vec<(String, Trend)> = names
+            .into_iter()
+            .map(|name| {
+                let trend = Trend::of(
+                    &stats_of(b_projects, name),
+                    &stats_of(a_projects, name),
+                    (b_since, b_until),
+                    (a_since, a_until),
+                );
+                (name.clone(), trend)
+            })
+            .collect();
+        rows.push((
+            String::from("Total"),
+            Trend::of(b_total, a_total, (b_since, b_until), (a_since, a_until)),
+        ));
+
+        print_period_comparison((a_since, a_until), (b_since, b_until), &rows);
+        timing::print_timings();
+        return;
+    }
+
+    if let Some(entries) = matches.subcommand_matches("time-entries") {
+        let sessions = project_sessions(
+            &repo_infos,
+            &author_filter,
+            &since,
+            &until,
+            &history,
+            session_gap,
+        );
+        let result = if let Some(format) = &csv_entries {
+            // Both formats ask whose entries they are
+            let email = author_email(&author_filter.include)
+                .or_else(|| git_config_author(".").filter(|author| author.contains('@')))
+                .unwrap_or_default();
+            time_entries_csv(&sessions, format, &email).and_then(|csv| {
+                write_output(
+                    &csv,
+                    entries.get_one::<String>("output").map(String::as_str),
+                )
+            })
+        } else {
+            let (tracker, created) = match entries.get_one::<String>("toggl") {
+                Some(workspace) => ("Toggl Track", push_to_toggl(&sessions, workspace)),
+                None => {
+                    let workspace = entries.get_one::<String>("clockify").map(String::as_str);
+                    (
+                        "Clockify",
+                        push_to_clockify(&sessions, workspace.unwrap_or_default()),
+                    )
+                }
+            };
+            created.map(|count| {
+                log(&format!(
+                    "{} {} {} {}",
+                    "Created".bright_yellow(),
+                    count.to_string().bright_cyan(),
+                    "time entries in".bright_yellow(),
+                    tracker.bright_cyan()
+                ));
+            })
+        };
+        if let Err(err) = result {
+            eprintln!("{}: {}", "Error".bright_red(), err);
+            process::exit(1);
+        }
+        timing::print_timings();
+        return;
+    }
+
+    // A timesheet replaces the report
+    if let Some(format) = &timesheet_format {
+        let mut days = timesheet(
+            &repo_infos,
+            &author_filter,
+            &since,
+            &until,
+            &history,
+            session_gap,
+            &working_hours,
+        );
+        // It has no authors, but its projects name the repositories --anonymize=all hides
+        if let Some(scope) = anonymize {
+            anonymize_projects(scope, days.iter_mut().map(|day| &mut day.project));
+        }
+        let content = match format.as_str() {
+            "csv" => timesheet_csv(&days),
+            _ => timesheet_markdown(&days, &since, &until),
+        };
+        let output_path = matches.get_one::<String>("output").map(String::as_str);
+        if let Err(err) = write_output(&content, output_path) {
+            eprintln!("{}: {}", "Error".bright_red(), err);
+            process::exit(1);
+        }
+        timing::print_timings();
+        return;
+    }
+
+    // In JSONL mode every repository is emitted as soon as it has been analyzed
+    let stream_jsonl = output_format == OutputFormat::Jsonl;
+    let mut emit_repo = |path: &str, stats: &RepoStats| {
+        if !stream_jsonl {
+            return;
+        }
+        let mut repo = repo_report(path, stats, &repo_infos);
+        if let Some(scope) = anonymize {
+            anonymize_stats(&mut repo.stats);
+            if scope.paths() {
+                repo.path = path_pseudonym(path);
+            }
+            anonymize_projects(scope, [&mut repo.name]);
+        }
+        if let Err(err) = print_jsonl_repo(&repo) {
+            eprintln!("{}: {}", "Error".bright_red(), err);
+            process::exit(1);
+        }
+    };
 
     // Analyze all categories
-    let (categories, all_repo_stats) = analyze_all_categories(
+    if show_progress {
+        progress::start(repo_count);
+    }
+    let (mut categories, mut all_repo_stats) = analyze_all_categories(
         &config,
-        &repo_path_map,
         &author_filter,
         &since,
         &until,
         active_only,
         show_repo_details,
-        show_filetypes,
-        working_hours,
+        filetypes,
+        file_scope,
+        &history,
+        Some(&working_hours),
+        session_gap,
+        &test_patterns,
+        cache.as_ref(),
+        &mut emit_repo,
     );
+    progress::finish();
+
+    if stream_jsonl {
+        timing::print_timings();
+        return;
+    }
 
     // Create a map of repo path to its statistics for reuse
     let mut repo_stats_map: HashMap<String, RepoStats> = HashMap::new();
@@ -358,14 +2159,63 @@ fn main() {
     // Aggregate stats for all repositories
     let mut total_stats = aggregate_stats(&repo_stats_only);
 
+    // Analyze the previous period of the same length to compare the totals with
+    let mut trend = if show_trend {
+        let Some((previous_since, previous_until)) = previous_period(&since, &until) else {
+            eprintln!(
+                "{}: Cannot compute the period before {} to {}",
+                "Error".bright_red(),
+                since,
+                until
+            );
+            process::exit(1);
+        };
+        debug(&format!(
+            "Using previous period from {} to {}",
+            previous_since, previous_until
+        ));
+        if show_progress {
+            progress::start(repo_count);
+        }
+        let (_, previous_repo_stats) = analyze_all_categories(
+            &config,
+            &author_filter,
+            &previous_since,
+            &previous_until,
+            false,
+            false,
+            None,
+            FileScope::Skip,
+            &history,
+            Some(&working_hours),
+            session_gap,
+            &test_patterns,
+            cache.as_ref(),
+            &mut |_, _| {},
+        );
+        progress::finish();
+        let previous_stats: Vec<RepoStats> = previous_repo_stats
+            .into_iter()
+            .map(|(_, stats)| stats)
+            .collect();
+        Some(Trend::of(
+            &total_stats,
+            &aggregate_stats(&previous_stats),
+            (&since, &until),
+            (&previous_since, &previous_until),
+        ))
+    } else {
+        None
+    };
+
     // Calculate the total number of active repositories
-    let mut total_active_repos = all_repo_stats
+    let total_active_repos = all_repo_stats
         .iter()
         .filter(|(_, stats)| is_repo_active(stats))
         .count();
 
     // Handle projects if requested
-    let projects = if by_projects {
+    let mut projects = if by_projects || machine_output || chart_format.is_some() || diff_last {
         // Analyze projects using the repo_stats_map for efficiency
         let project_list = analyze_all_projects(
             &repo_infos,
@@ -375,8 +2225,13 @@ fn main() {
             &until,
             active_only,
             show_repo_details,
-            show_filetypes,
-            working_hours,
+            filetypes,
+            file_scope,
+            &history,
+            Some(&working_hours),
+            session_gap,
+            &test_patterns,
+            cache.as_ref(),
         );
 
         // Debug: Print all projects and their active status
@@ -395,47 +2250,233 @@ fn main() {
         None
     };
 
+    // Tickets are read from the repositories, before their paths are hidden
+    let mut tickets = if matches.get_flag("tickets") {
+        let patterns = match matches.get_many::<String>("ticket-pattern") {
+            Some(patterns) => TicketPatterns::new(&patterns.collect::<Vec<_>>()),
+            None => match &config.ticket_patterns {
+                Some(patterns) => TicketPatterns::new(patterns),
+                None => Ok(TicketPatterns::default()),
+            },
+        };
+        let patterns = patterns.unwrap_or_else(|err| {
+            eprintln!("{}: {}", "Error".bright_red(), err);
+            process::exit(1);
+        });
+        let sessions = project_sessions(
+            &repo_infos,
+            &author_filter,
+            &since,
+            &until,
+            &history,
+            session_gap,
+        );
+        Some(Tickets::of(&sessions, &patterns))
+    } else {
+        None
+    };
+
+    // Hide identities and paths before anything is printed
+    let mut report_authors = author_filter.include.clone();
+    if let Some(scope) = anonymize {
+        anonymize_analysis(
+            scope,
+            &mut all_repo_stats,
+            &mut repo_stats_map,
+            &mut repo_infos,
+            &mut categories,
+            projects.as_mut(),
+            tickets
+                .iter_mut()
+                .flat_map(|tickets| &mut tickets.tickets)
+                .flat_map(|ticket| &mut ticket.projects),
+        );
+        anonymize_stats(&mut total_stats);
+        report_authors = report_authors
+            .iter()
+            .map(|author| author_pseudonym(author))
+            .collect();
+    }
+
+    let mut report = if machine_output || chart_format.is_some() || diff_last {
+        Some(build_report(
+            &since,
+            &until,
+            &report_authors,
+            &working_hours.global.to_string(),
+            &total_stats,
+            &all_repo_stats,
+            &repo_infos,
+            &categories,
+            projects.as_deref().unwrap_or_default(),
+            short_sleep_before,
+            min_gap_days,
+        ))
+    } else {
+        None
+    };
+    if let Some(report) = report.as_mut() {
+        report.trend = trend.take();
+    }
+
+    // Reviewing counts as work too, though it leaves no commit
+    let mut review_activity = if matches.get_flag("github-activity") {
+        match read_github_activity(&config.forges, &since, &until) {
+            Ok(mut activity) => {
+                if anonymize.is_some() {
+                    activity.login = author_pseudonym(&activity.login);
+                }
+                Some(ReviewActivity::of(&activity, &working_hours.global))
+            }
+            Err(err) => {
+                eprintln!("{}: {}", "Error".bright_red(), err);
+                process::exit(1);
+            }
+        }
+    } else {
+        None
+    };
+    if let Some(report) = report.as_mut() {
+        report.review_activity = review_activity.take();
+    }
+
+    // Editor time tells how much of the work the commit sessions miss
+    let mut editor_time = match matches.get_one::<String>("wakatime") {
+        Some(path) => match read_wakatime_summaries(path) {
+            Ok(editor) => {
+                let commits = session_minutes_by_day(&total_stats.commit_times, session_gap);
+                Some(EditorTime::of(&editor, &commits, &since, &until))
+            }
+            Err(err) => {
+                eprintln!("{}: {}", "Error".bright_red(), err);
+                process::exit(1);
+            }
+        },
+        None => None,
+    };
+    if let Some(report) = report.as_mut() {
+        report.editor_time = editor_time.take();
+        report.tickets = tickets.take();
+    }
+
+    // The snapshot of the period is compared with the last one recorded
+    if let (true, Some(report)) = (diff_last, report.as_mut()) {
+        match read_snapshots().last() {
+            Some(last) => {
+                let last_day = parse_range_bound(&until)
+                    .map(|until| until.date())
+                    .unwrap_or_else(|| Local::now().date_naive());
+                let current = Snapshot::of(last_day, report);
+                report.last_snapshot = Some(compare_snapshots(&current, last));
+            }
+            None => log(&format!(
+                "{}",
+                "Warning: No snapshot recorded yet to compare with, record one with gitnapped record"
+                    .bright_yellow()
+            )),
+        }
+    }
+
+    if let (Some(report), Some(_)) = (&report, &chart_format) {
+        let chart_dir = matches
+            .get_one::<String>("chart-dir")
+            .map(|s| s.as_str())
+            .unwrap_or(".");
+        match write_svg_charts(report, chart_dir) {
+            Ok(paths) => {
+                for path in paths {
+                    log(&format!(
+                        "{} {}",
+                        "Chart written to".bright_yellow(),
+                        path.bright_cyan()
+                    ));
+                }
+            }
+            Err(err) => {
+                eprintln!("{}: {}", "Error".bright_red(), err);
+                process::exit(1);
+            }
+        }
+    }
+
+    if let (Some(notify), Some(report)) = (notify, &report) {
+        let webhooks: [(&str, Box<dyn Renderer>); 2] = [
+            ("slack-webhook", Box::new(SlackRenderer)),
+            ("discord-webhook", Box::new(DiscordRenderer)),
+        ];
+        let mut failed = false;
+        for (name, renderer) in webhooks {
+            let Some(url) = notify.get_one::<String>(name) else {
+                continue;
+            };
+            let result = renderer
+                .render(report)
+                .and_then(|payload| post_webhook(url, &payload));
+            if let Err(err) = result {
+                eprintln!("{}: {}", "Error".bright_red(), err);
+                failed = true;
+            }
+        }
+        if failed {
+            process::exit(1);
+        }
+        timing::print_timings();
+        return;
+    }
+
+    if let (true, Some(report)) = (machine_output, &report) {
+        let renderer: Box<dyn Renderer> = match (&template_path, &tmux_format) {
+            (Some(path), _) => Box::new(TemplateRenderer { path: path.clone() }),
+            (None, Some(format)) => Box::new(TmuxRenderer {
+                format: format.clone(),
+            }),
+            (None, None) => renderer_for(output_format, pretty)
+                .expect("text and jsonl formats are printed without a renderer"),
+        };
+        let rendered = renderer.render(report);
+        let output_path = matches.get_one::<String>("output").map(|s| s.as_str());
+        let result = rendered.and_then(|content| match (&influx_url, &config.email) {
+            (Some(url), _) => write_points(url, &content),
+            (None, Some(settings)) if email => {
+                let day = |date: &str| date.get(..10).unwrap_or(date).to_string();
+                let subject = format!(
+                    "Gitnapped report {} – {}",
+                    day(&report.since),
+                    day(&report.until)
+                );
+                let html = output_format == OutputFormat::Html && template_path.is_none();
+                send_report(settings, &subject, content, html)
+            }
+            _ => write_output(&content, output_path),
+        });
+        if let Err(err) = result {
+            eprintln!("{}: {}", "Error".bright_red(), err);
+            process::exit(1);
+        }
+        timing::print_timings();
+        return;
+    }
+
     // Print appropriate output based on flags
     if by_categories {
         print_category_summary(
             &categories,
             sort_by,
-            show_filetypes,
-            matches.get_flag("pretty"),
+            filetypes,
+            output_format == OutputFormat::Pretty,
+            as_table,
         );
     } else if let Some(project_list) = &projects {
         // Print project statistics
-        print_projects_summary(project_list, sort_by, show_filetypes, show_repo_details);
-
-        // Calculate overall stats for projects
-        total_active_repos = project_list
-            .iter()
-            .flat_map(|project| project.repos.iter())
-            .filter(|repo_path| {
-                if let Some(stats) = repo_stats_map.get(*repo_path) {
-                    is_repo_active(stats)
-                } else {
-                    false
-                }
-            })
-            .count();
-
-        // Extract project stats into a vector for aggregation
-        let project_stats: Vec<RepoStats> = project_list
-            .iter()
-            .map(|project| project.stats.clone())
-            .collect();
-
-        // Aggregate stats for all projects
-        total_stats = aggregate_stats(&project_stats);
+        print_projects_summary(project_list, sort_by, filetypes, show_repo_details);
     } else {
         // Otherwise sort and print overall top repos
         if !all_repo_stats.is_empty() {
             let mut sorted_repos = all_repo_stats.clone();
             match sort_by.as_str() {
-                "commits" => sorted_repos.sort_by(|a, b| b.1.commit_count.cmp(&a.1.commit_count)),
-                "files" => sorted_repos.sort_by(|a, b| b.1.file_count.cmp(&a.1.file_count)),
-                "lines" => sorted_repos.sort_by(|a, b| b.1.line_count.cmp(&a.1.line_count)),
+                "commits" => sorted_repos.sort_by_key(|item| Reverse(item.1.commit_count)),
+                "files" => sorted_repos.sort_by_key(|item| Reverse(item.1.file_count)),
+                "lines" => sorted_repos.sort_by_key(|item| Reverse(item.1.line_count)),
                 _ => {}
             }
             if sorted_repos.len() > 1 {
@@ -446,60 +2487,86 @@ fn main() {
                     "active Repositories".bright_green(),
                     sort_by
                 ));
-                for (i, (repo, stats)) in sorted_repos
-                    .iter()
-                    .enumerate()
-                    .take(most_active_repos_count)
-                {
-                    if is_repo_active(stats) || sort_by != "commits" {
-                        if matches.get_flag("pretty") {
-                            // Get the vanity name from repo_infos
-                            let vanity_name = repo_infos
-                                .iter()
-                                .find(|info| info.path == *repo)
-                                .map(|info| info.vanity_name.clone())
-                                .unwrap_or_else(|| {
-                                    repo.split('/').last().unwrap_or(repo).to_string()
-                                });
-
-                            if stats.out_of_hours_commits > 0 {
-                                let percentage = if stats.commit_count > 0 {
-                                    (stats.out_of_hours_commits as f32 / stats.commit_count as f32
-                                        * 100.0) as u32
+                if as_table {
+                    let rows: Vec<(String, &RepoStats)> = sorted_repos
+                        .iter()
+                        .take(most_active_repos_count)
+                        .filter(|(_, stats)| is_repo_active(stats) || sort_by != "commits")
+                        .map(|(repo, stats)| {
+                            let name = if output_format == OutputFormat::Pretty {
+                                repo_infos
+                                    .iter()
+                                    .find(|info| info.key() == *repo)
+                                    .map(|info| info.vanity_name.clone())
+                                    .unwrap_or_else(|| repo.clone())
+                            } else {
+                                repo.clone()
+                            };
+                            (name, stats)
+                        })
+                        .collect();
+                    print_repo_table(&rows, 0);
+                } else {
+                    for (i, (repo, stats)) in sorted_repos
+                        .iter()
+                        .enumerate()
+                        .take(most_active_repos_count)
+                    {
+                        if is_repo_active(stats) || sort_by != "commits" {
+                            let activity = sparkline(&stats.commits_by_date, &since, &until);
+                            if output_format == OutputFormat::Pretty {
+                                // Get the vanity name from repo_infos
+                                let vanity_name = repo_infos
+                                    .iter()
+                                    .find(|info| info.key() == *repo)
+                                    .map(|info| info.vanity_name.clone())
+                                    .unwrap_or_else(|| {
+                                        repo.split('/').next_back().unwrap_or(repo).to_string()
+                                    });
+
+                                if stats.out_of_hours_commits > 0 {
+                                    let percentage = if stats.commit_count > 0 {
+                                        (stats.out_of_hours_commits as f32
+                                            / stats.commit_count as f32
+                                            * 100.0) as u32
+                                    } else {
+                                        0
+                                    };
+                                    log(&format!(
+                                        "{}. {} - {} commits {} [{}: {}% ({})]",
+                                        (i + 1).to_string().bright_yellow(),
+                                        vanity_name.green(),
+                                        stats.commit_count.to_string().cyan(),
+                                        activity.bright_blue(),
+                                        "Gitnapped for".yellow(),
+                                        percentage.to_string().red(),
+                                        stats.out_of_hours_commits.to_string().red()
+                                    ));
                                 } else {
-                                    0
-                                };
-                                log(&format!(
-                                    "{}. {} - {} commits [{}: {}% ({})]",
-                                    (i + 1).to_string().bright_yellow(),
-                                    vanity_name.green(),
-                                    stats.commit_count.to_string().cyan(),
-                                    "Gitnapped for".yellow(),
-                                    percentage.to_string().red(),
-                                    stats.out_of_hours_commits.to_string().red()
-                                ));
+                                    log(&format!(
+                                        "{}. {} - {} commits {}",
+                                        (i + 1).to_string().bright_yellow(),
+                                        vanity_name.green(),
+                                        stats.commit_count.to_string().cyan(),
+                                        activity.bright_blue()
+                                    ));
+                                }
                             } else {
                                 log(&format!(
-                                    "{}. {} - {} commits",
+                                    "{}. {} - {} commits{} {}",
                                     (i + 1).to_string().bright_yellow(),
-                                    vanity_name.green(),
-                                    stats.commit_count.to_string().cyan()
-                                ));
-                            }
-                        } else {
-                            log(&format!(
-                                "{}. {} - {} commits, {} files, {} lines",
-                                (i + 1).to_string().bright_yellow(),
-                                repo.green(),
-                                stats.commit_count.to_string().cyan(),
-                                stats.file_count.to_string().blue(),
-                                stats.line_count.to_string().magenta()
-                            ));
-                            if stats.out_of_hours_commits > 0 {
-                                log(&format!(
-                                    "   {} commits",
-                                    format!("Gitnapped for {}", stats.out_of_hours_commits).red()
+                                    repo.green(),
+                                    stats.commit_count.to_string().cyan(),
+                                    files_and_lines(stats),
+                                    activity.bright_blue()
                                 ));
+                                if stats.out_of_hours_commits > 0 {
+                                    log(&format!(
+                                        "   {} commits",
+                                        format!("Gitnapped for {}", stats.out_of_hours_commits)
+                                            .red()
+                                    ));
+                                }
                             }
                         }
                     }
@@ -520,9 +2587,97 @@ fn main() {
         &total_stats,
         total_active_repos,
         item_type,
-        show_filetypes,
+        &since,
+        &until,
+        filetypes,
         show_most_active_day,
         hide_gitnapped_stats,
         matches.get_flag("show-total-stats"),
+        short_sleep_before,
+        min_gap_days,
     );
+
+    // The trend moved into the report when one was built
+    if let Some(trend) = trend
+        .as_ref()
+        .or_else(|| report.as_ref().and_then(|report| report.trend.as_ref()))
+    {
+        print_trend(trend);
+    }
+
+    if let Some(comparison) = report
+        .as_ref()
+        .and_then(|report| report.last_snapshot.as_ref())
+    {
+        print_snapshot_comparison(comparison);
+    }
+
+    if let Some(activity) = review_activity.as_ref().or_else(|| {
+        report
+            .as_ref()
+            .and_then(|report| report.review_activity.as_ref())
+    }) {
+        print_review_activity(activity);
+    }
+
+    if let Some(editor_time) = editor_time.as_ref().or_else(|| {
+        report
+            .as_ref()
+            .and_then(|report| report.editor_time.as_ref())
+    }) {
+        print_editor_time(editor_time);
+    }
+
+    if let Some(tickets) = tickets
+        .as_ref()
+        .or_else(|| report.as_ref().and_then(|report| report.tickets.as_ref()))
+    {
+        print_tickets(tickets);
+    }
+
+    if show_heatmap {
+        print_heatmap(&total_stats, &since, &until, week_start);
+    }
+
+    if show_by_hour {
+        print_hour_histogram(&total_stats, Some(&working_hours.global));
+    }
+
+    if show_punch_card {
+        print_punch_card(&total_stats, Some(&working_hours.global), week_start);
+    }
+
+    if show_by_author || show_commit_sizes {
+        let repos: Vec<(String, &RepoStats)> = all_repo_stats
+            .iter()
+            .map(|(repo, stats)| {
+                let name = if output_format == OutputFormat::Pretty {
+                    repo_infos
+                        .iter()
+                        .find(|info| info.key() == *repo)
+                        .map(|info| info.vanity_name.clone())
+                        .unwrap_or_else(|| repo.clone())
+                } else {
+                    repo.clone()
+                };
+                (name, stats)
+            })
+            .collect();
+        if show_by_author {
+            print_author_breakdown(&repos, &total_stats);
+        }
+        if show_commit_sizes {
+            print_commit_sizes(&repos, &total_stats);
+        }
+    }
+
+    if show_leaderboard {
+        let min_commits = matches
+            .get_one::<usize>("min-commits")
+            .copied()
+            .unwrap_or(5);
+        print_leaderboard(&total_stats, min_commits);
+    }
+
+    timing::print_timings();
 }
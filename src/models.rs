@@ -1,32 +1,268 @@
-use serde::Deserialize;
-use std::collections::HashMap;
+use crate::lines::LineCounts;
+use crate::schedule::WorkingTimeSpec;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+use std::fs;
 
 /// Configuration structure for the application.
 /// This structure represents the contents of the gitnapped.yaml configuration file.
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Default, Deserialize)]
 pub struct Config {
-    /// Optional author name to filter commits
+    /// Other config files merged into this one, paths relative to it. Later
+    /// files override the keys of earlier ones, and this file all of them
+    #[serde(default)]
+    pub include: Vec<String>,
+    /// Optional author name to filter commits, comma separate several names/emails
     pub author: Option<String>,
-    /// Map of category names to lists of repository paths
+    /// Patterns of authors whose commits are ignored, e.g. bots
+    #[serde(default)]
+    pub exclude_authors: Vec<String>,
+    /// Map of category names to lists of repositories
+    #[serde(default)]
+    pub repos: HashMap<String, Vec<RepoEntry>>,
+    /// Optional working hours, overridden by the --working-time flag
+    pub working_time: Option<WorkingTimeSpec>,
+    /// Optional per-weekday working hours, `off` for days off
+    #[serde(default)]
+    pub working_time_by_day: HashMap<String, WorkingTimeSpec>,
+    /// Optional days of the week that are worked, Monday to Friday when missing
+    pub workdays: Option<Vec<String>>,
+    /// Map of category names to their working hours, overriding the global ones
+    #[serde(default)]
+    pub working_time_by_category: HashMap<String, WorkingTimeSpec>,
+    /// Map of repository paths to their working hours, overriding the category ones
     #[serde(default)]
-    pub repos: HashMap<String, Vec<String>>,
+    pub working_time_by_repo: HashMap<String, WorkingTimeSpec>,
+    /// Optional first day of the week for the week presets, Monday when missing
+    pub week_start: Option<String>,
+    /// Optional first day of the fiscal year (MM-DD) for `--period FY`, January 1 when missing
+    pub fiscal_year_start: Option<String>,
+    /// Team members evaluated against their own working hours and timezone
+    #[serde(default)]
+    pub team: Vec<TeamMemberConfig>,
+    /// Optional path patterns of test files, replacing the default ones
+    pub test_patterns: Option<Vec<String>>,
+    /// Optional regular expressions of the ticket IDs in commit subjects, replacing the default ones
+    pub ticket_patterns: Option<Vec<String>>,
+    /// Named sets of settings merged over the others with `--profile`, see `apply_profile`
+    #[serde(default)]
+    pub profiles: HashMap<String, serde_yaml::Value>,
+    /// Default values of command line options by their long name, e.g.
+    /// `sort_by: lines` or `pretty: true`, overridden by the command line
+    #[serde(default)]
+    pub defaults: BTreeMap<String, serde_yaml::Value>,
+    /// Optional SMTP server and recipients the report is sent to with `--email`
+    pub email: Option<EmailConfig>,
+    /// How the forges of remote repositories are reached, by host, e.g. `gitlab.example.com`
+    #[serde(default)]
+    pub forges: BTreeMap<String, ForgeConfig>,
+}
+
+impl Config {
+    /// Returns the author patterns of the config, split at the commas.
+    pub fn authors(&self) -> Vec<String> {
+        self.author
+            .iter()
+            .flat_map(|author| author.split(','))
+            .map(|author| author.trim().to_string())
+            .filter(|author| !author.is_empty())
+            .collect()
+    }
+}
+
+/// A repository as written in the `repos` section of the config file.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(untagged)]
+pub enum RepoEntry {
+    /// `path [Category][Name]`, as parsed by `parse_repo_string`
+    Line(String),
+    /// A repository with its settings spelled out
+    Structured(RepoConfig),
+    /// Every repository found under a directory, e.g. `scan: ~/code`
+    Scan(ScanConfig),
+}
+
+/// A repository written as an object in the config file, the structured
+/// alternative to the `path [Category][Name]` strings.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[serde(deny_unknown_fields)]
+pub struct RepoConfig {
+    /// Path to the repository, optionally followed by `:` and pathspecs
+    pub path: String,
+    /// Optional display name, the path when missing
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+    /// Optional group/category name
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub category: Option<String>,
+    /// Free labels shown in the reports
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub tags: Vec<String>,
+    /// Optional working hours, overriding the global and category ones
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub working_hours: Option<WorkingTimeSpec>,
+    /// Paths inside the repository whose changes are not counted, e.g. `vendor/`
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub exclude: Vec<String>,
+}
+
+/// A directory whose Git repositories are all analyzed, so that new ones are
+/// picked up without editing the config. The repositories are looked for when
+/// the config is loaded.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ScanConfig {
+    /// Directory to search, `~` standing for the home directory
+    pub scan: String,
+    /// How many directory levels below it are searched, `DEFAULT_SCAN_DEPTH` when missing
+    pub depth: Option<usize>,
+}
+
+/// How the API of a forge is reached, as written in the `forges` section of the config.
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct ForgeConfig {
+    /// Optional base URL of the API, the usual one of the forge when missing, e.g. `http://gitlab.lan:8080`
+    pub url: Option<String>,
+    /// Optional environment variable holding the API token, the usual one of the forge when missing
+    pub token_env: Option<String>,
+}
+
+/// How the report is sent by email, as written in the `email` section of the config file.
+#[derive(Debug, Deserialize)]
+pub struct EmailConfig {
+    /// Host name of the SMTP server
+    pub smtp_host: String,
+    /// Optional port of the SMTP server, the usual one of the `tls` mode when missing
+    pub smtp_port: Option<u16>,
+    /// Optional encryption: `starttls` (the default), `tls` or `none`
+    pub tls: Option<String>,
+    /// Optional user name to log in with
+    pub username: Option<String>,
+    /// Optional environment variable holding the password, `GITNAPPED_SMTP_PASSWORD` when missing
+    pub password_env: Option<String>,
+    /// Sender of the email, e.g. `Gitnapped <me@example.com>`
+    pub from: String,
+    /// Recipients of the email
+    pub to: Vec<String>,
+    /// Optional subject, `Gitnapped report <since> – <until>` when missing
+    pub subject: Option<String>,
+}
+
+/// Working hours of a team member, as written in the `team` section of the config file.
+#[derive(Debug, Deserialize)]
+pub struct TeamMemberConfig {
+    /// Pattern matched against `Name <email>` of the member's commits
+    pub author: String,
+    /// Optional working hours, the global ones when missing
+    pub working_time: Option<WorkingTimeSpec>,
+    /// Optional timezone (`local`, `author`, `utc` or a name like `Europe/Rome`)
+    pub timezone: Option<String>,
+    /// Optional days of the week that are worked, the global ones when missing
+    pub workdays: Option<Vec<String>>,
+}
+
+/// Hour at which a day ends when looking for its first and last commit, so
+/// that working past midnight still counts as the same day.
+pub const DAY_END_HOUR: i64 = 5;
+
+/// Files and lines of a repository, as counted by `count_files_and_lines`.
+#[derive(Debug, Default)]
+pub struct FileCounts {
+    /// Number of files
+    pub file_count: usize,
+    /// Lines split into code, comments and blank lines
+    pub lines: LineCounts,
+    /// Map of languages to their file counts
+    pub file_types: BTreeMap<String, usize>,
+    /// Map of languages to their lines
+    pub lines_by_type: BTreeMap<String, LineCounts>,
+    /// Number of binary files, left out of the line counts
+    pub binary_files: usize,
+    /// Total size of the binary files in bytes
+    pub binary_size: u64,
+    /// Number of test files
+    pub test_files: usize,
+    /// Number of lines in the test files
+    pub test_lines: usize,
 }
 
 /// Statistics for a single repository or aggregated repositories.
-#[derive(Debug, Default, Clone)]
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
 pub struct RepoStats {
     /// Total number of commits
     pub commit_count: usize,
     /// Number of commits made outside working hours
     pub out_of_hours_commits: usize,
+    /// Estimated minutes worked outside working hours, out of the work sessions
+    pub out_of_hours_minutes: usize,
+    /// Number of merge commits, included in `commit_count`
+    pub merge_commits: usize,
+    /// Whether the files and lines have been counted, as they are skipped when not shown
+    #[serde(skip)]
+    pub files_counted: bool,
     /// Total number of files
     pub file_count: usize,
     /// Total number of lines of code
     pub line_count: usize,
+    /// Lines of code split into code, comments and blank lines
+    pub line_kinds: LineCounts,
+    /// Map of languages to their lines split into code, comments and blank lines
+    pub line_kinds_by_type: BTreeMap<String, LineCounts>,
+    /// Number of binary files, included in `file_count` but not in the line counts
+    pub binary_files: usize,
+    /// Total size of the binary files in bytes
+    pub binary_size: u64,
+    /// Number of test files, included in `file_count`
+    pub test_files: usize,
+    /// Number of lines in the test files, included in `line_count`
+    pub test_lines: usize,
+    /// Number of commits touching test files
+    pub test_commits: usize,
+    /// Number of out-of-hours commits touching test files
+    pub out_of_hours_test_commits: usize,
     /// Map of dates to number of commits on that date
-    pub commits_by_date: HashMap<String, usize>,
-    /// Map of file extensions to number of files with that extension
-    pub file_types: HashMap<String, usize>,
+    pub commits_by_date: BTreeMap<String, usize>,
+    /// Map of dates to number of out-of-hours commits on that date
+    pub out_of_hours_by_date: BTreeMap<String, usize>,
+    /// Lines added by the commits of the period
+    pub lines_added: usize,
+    /// Lines removed by the commits of the period
+    pub lines_removed: usize,
+    /// Map of dates to lines added on that date
+    pub lines_added_by_date: BTreeMap<String, usize>,
+    /// Map of dates to lines removed on that date
+    pub lines_removed_by_date: BTreeMap<String, usize>,
+    /// Map of days to the time of their first commit, in minutes since midnight.
+    /// Days end at `DAY_END_HOUR`, so a commit at 01:30 is the 1530th minute of the day before
+    pub first_commit_by_date: BTreeMap<String, u32>,
+    /// Map of days to the time of their last commit, as in `first_commit_by_date`
+    pub last_commit_by_date: BTreeMap<String, u32>,
+    /// Unix timestamps of every commit, used for the time between commits
+    #[serde(skip)]
+    pub commit_times: Vec<i64>,
+    /// Lines changed (added plus removed) by every commit
+    pub commit_sizes: Vec<usize>,
+    /// Lines changed by every out-of-hours commit
+    pub out_of_hours_commit_sizes: Vec<usize>,
+    /// Number of commits made in each hour of the day (0-23)
+    pub commits_by_hour: [usize; 24],
+    /// Commits per weekday (Monday first) and hour of the day
+    pub punch_card: [[usize; 24]; 7],
+    /// Map of languages (or unknown file extensions) to number of files in that language
+    pub file_types: BTreeMap<String, usize>,
+    /// Map of authors (`Name <email>`) to their commits
+    pub authors: BTreeMap<String, AuthorStats>,
+}
+
+/// Commits of a single author.
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
+pub struct AuthorStats {
+    /// Number of commits
+    pub commit_count: usize,
+    /// Number of commits made outside working hours
+    pub out_of_hours_commits: usize,
+    /// Estimated minutes worked outside working hours, out of the work sessions
+    pub out_of_hours_minutes: usize,
 }
 
 /// Information about a repository, including its path and categorization.
@@ -34,10 +270,45 @@ pub struct RepoStats {
 pub struct RepoInfo {
     /// Path to the repository
     pub path: String,
+    /// Pathspecs restricting the analysis to parts of the repository, empty for all of it
+    pub pathspecs: Vec<String>,
     /// Optional group/category name
     pub group: Option<String>,
     /// Display name for the repository
     pub vanity_name: String,
+    /// Free labels from the structured config
+    pub tags: Vec<String>,
+}
+
+impl RepoInfo {
+    /// Returns the key the statistics of the repository are stored under: its
+    /// path, followed by its pathspecs for the parts of a monorepo, e.g.
+    /// `/path/to/monorepo:apps/web`, so every part keeps statistics of its own.
+    pub fn key(&self) -> String {
+        if self.pathspecs.is_empty() {
+            self.path.clone()
+        } else {
+            format!("{}:{}", self.path, self.pathspecs.join(","))
+        }
+    }
+
+    /// Returns the key telling whether two entries of the config are the same
+    /// repository, or the same part of a monorepo, however their paths are
+    /// written, e.g. with a trailing slash or through a symbolic link.
+    pub fn canonical_key(&self) -> String {
+        let path = fs::canonicalize(&self.path)
+            .map(|path| path.to_string_lossy().into_owned())
+            .unwrap_or_else(|_| self.path.clone());
+        let mut pathspecs = self.pathspecs.clone();
+        pathspecs.sort();
+        pathspecs.dedup();
+
+        if pathspecs.is_empty() {
+            path
+        } else {
+            format!("{}:{}", path, pathspecs.join(","))
+        }
+    }
 }
 
 /// Statistics for a category of repositories.
@@ -0,0 +1,162 @@
+use crate::display::{gitnapped_hours, Renderer};
+use crate::report::{ProjectReport, Report};
+use crate::utils::debug;
+use serde_json::json;
+
+/// How many projects the notifications list, the most active first.
+const TOP_PROJECTS: usize = 3;
+
+/// Renders the report as the message of a Slack incoming webhook: the
+/// commits, the gitnapped percentage, the top projects and the most active
+/// day, in Slack's `mrkdwn`.
+pub struct SlackRenderer;
+
+impl Renderer for SlackRenderer {
+    fn render(&self, report: &Report) -> Result<String, String> {
+        let totals = &report.totals;
+        let mut lines = vec![
+            format!("*Gitnapped report* {} → {}", report.since, report.until),
+            format!(
+                "• *{}* commits in {} active repos",
+                totals.stats.commit_count, totals.active_repos
+            ),
+            format!(
+                "• Gitnapped: *{}%* ({} commits outside {}, {})",
+                totals.gitnapped_percentage,
+                totals.stats.out_of_hours_commits,
+                report.working_time,
+                gitnapped_hours(totals.stats.out_of_hours_minutes)
+            ),
+        ];
+        let projects: Vec<String> = top_projects(report)
+            .iter()
+            .map(|project| {
+                format!(
+                    "{} ({} commits, {}%)",
+                    slack_escape(&project.name),
+                    project.stats.commit_count,
+                    project.gitnapped_percentage
+                )
+            })
+            .collect();
+        if !projects.is_empty() {
+            lines.push(format!("• Top projects: {}", projects.join(", ")));
+        }
+        if let Some(day) = &totals.most_active_day {
+            lines.push(format!(
+                "• Most active day: {} ({} commits)",
+                day.date, day.commits
+            ));
+        }
+        Ok(json!({ "text": lines.join("\n") }).to_string())
+    }
+}
+
+/// Renders the report as the message of a Discord webhook: an embed with
+/// the same summary as for Slack, colored by the health score of the period.
+pub struct DiscordRenderer;
+
+impl Renderer for DiscordRenderer {
+    fn render(&self, report: &Report) -> Result<String, String> {
+        let totals = &report.totals;
+        let mut fields = vec![
+            json!({
+                "name": "Commits",
+                "value": format!(
+                    "{} in {} active repos",
+                    totals.stats.commit_count, totals.active_repos
+                ),
+                "inline": true,
+            }),
+            json!({
+                "name": "Gitnapped",
+                "value": format!(
+                    "{}% ({} commits, {})",
+                    totals.gitnapped_percentage,
+                    totals.stats.out_of_hours_commits,
+                    gitnapped_hours(totals.stats.out_of_hours_minutes)
+                ),
+                "inline": true,
+            }),
+        ];
+        let projects: Vec<String> = top_projects(report)
+            .iter()
+            .map(|project| {
+                format!(
+                    "**{}**: {} commits, {}%",
+                    project.name, project.stats.commit_count, project.gitnapped_percentage
+                )
+            })
+            .collect();
+        if !projects.is_empty() {
+            fields.push(json!({ "name": "Top projects", "value": projects.join("\n") }));
+        }
+        if let Some(day) = &totals.most_active_day {
+            fields.push(json!({
+                "name": "Most active day",
+                "value": format!("{} ({} commits)", day.date, day.commits),
+            }));
+        }
+
+        // Green for a healthy period, amber then red as the score drops
+        let color = match totals.health.score {
+            80.. => 0x2ecc71,
+            50..=79 => 0xf1c40f,
+            _ => 0xe74c3c,
+        };
+        Ok(json!({
+            "embeds": [{
+                "title": "Gitnapped report",
+                "description": format!(
+                    "{} → {}, working hours {}",
+                    report.since, report.until, report.working_time
+                ),
+                "color": color,
+                "fields": fields,
+                "footer": { "text": format!("Health score: {}/100", totals.health.score) },
+            }]
+        })
+        .to_string())
+    }
+}
+
+/// Returns the projects with commits, the most active first, at most `TOP_PROJECTS`.
+fn top_projects(report: &Report) -> Vec<&ProjectReport> {
+    let mut projects: Vec<&ProjectReport> = report
+        .projects
+        .iter()
+        .filter(|project| project.stats.commit_count > 0)
+        .collect();
+    projects.sort_by(|a, b| {
+        b.stats
+            .commit_count
+            .cmp(&a.stats.commit_count)
+            .then_with(|| a.name.cmp(&b.name))
+    });
+    projects.truncate(TOP_PROJECTS);
+    projects
+}
+
+/// Escapes the characters Slack reads as markup in a message.
+fn slack_escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+}
+
+/// Posts a JSON payload to a webhook.
+///
+/// # Arguments
+/// * `url` - The URL of the webhook
+/// * `payload` - The JSON message, as rendered for the service of the webhook
+///
+/// # Returns
+/// * `Result<(), String>` - An error telling why the message was not posted
+pub fn post_webhook(url: &str, payload: &str) -> Result<(), String> {
+    debug(&format!("Posting {} to the webhook", payload));
+    ureq::post(url)
+        .header("Content-Type", "application/json")
+        .send(payload)
+        .map(|_| ())
+        .map_err(|err| format!("Failed to post to the webhook: {}", err))
+}
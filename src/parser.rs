@@ -1,20 +1,28 @@
-use crate::models::RepoInfo;
+use crate::clones::url_prefix_length;
+use crate::models::{RepoConfig, RepoEntry, RepoInfo, ScanConfig};
+use crate::remote::remote_prefix_length;
 use crate::utils::debug;
 use std::collections::HashMap;
+use std::ops::Range;
 
 /// Parses a repository string in the format "path \[category\]\[name\]" or "path \[name\]".
 ///
+/// The path may be followed by `:` and comma separated pathspecs (e.g.
+/// `path/to/monorepo:src/backend`) so only the commits touching them are counted.
+///
 /// # Arguments
 /// * `input` - The repository string to parse
 ///
 /// # Returns
 /// * `RepoInfo` - Structure containing:
 ///   - path: The repository path
+///   - pathspecs: The pathspecs inside the repository, if any
 ///   - group: Optional category name (if provided)
 ///   - vanity_name: Display name for the repository
 ///
 /// # Examples
 /// ```
+/// # use gitnapped::parser::parse_repo_string;
 /// // With category
 /// let info = parse_repo_string("/path/to/repo [Category][Project]");
 /// assert_eq!(info.path, "/path/to/repo");
@@ -26,6 +34,21 @@ use std::collections::HashMap;
 /// assert_eq!(info.path, "/path/to/repo");
 /// assert_eq!(info.group, None);
 /// assert_eq!(info.vanity_name, "Project");
+///
+/// // Part of a monorepo
+/// let info = parse_repo_string("/path/to/monorepo:src/backend [Backend]");
+/// assert_eq!(info.path, "/path/to/monorepo");
+/// assert_eq!(info.pathspecs, vec!["src/backend".to_string()]);
+///
+/// // Read through the GitHub API
+/// let info = parse_repo_string("github:org/repo [Project]");
+/// assert_eq!(info.path, "github:org/repo");
+/// assert!(info.pathspecs.is_empty());
+///
+/// // Cloned from a URL
+/// let info = parse_repo_string("https://example.com:8443/repo.git:src [Project]");
+/// assert_eq!(info.path, "https://example.com:8443/repo.git");
+/// assert_eq!(info.pathspecs, vec!["src".to_string()]);
 /// ```
 pub fn parse_repo_string(input: &str) -> RepoInfo {
     debug(&format!("Parsing repo string: '{}'", input));
@@ -35,12 +58,14 @@ pub fn parse_repo_string(input: &str) -> RepoInfo {
         debug("Empty input string");
         return RepoInfo {
             path: input.trim().to_string(),
+            pathspecs: Vec::new(),
             group: None,
             vanity_name: input.trim().to_string(),
+            tags: Vec::new(),
         };
     }
 
-    let path = parts[0].trim().to_string();
+    let (path, pathspecs) = split_pathspecs(parts[0].trim());
 
     let mut labels = Vec::new();
     for part in parts.iter().skip(1) {
@@ -49,27 +74,169 @@ pub fn parse_repo_string(input: &str) -> RepoInfo {
         }
     }
 
-    debug(&format!("Extracted path: '{}', labels: {:?}", path, labels));
+    debug(&format!(
+        "Extracted path: '{}', pathspecs: {:?}, labels: {:?}",
+        path, pathspecs, labels
+    ));
 
     match labels.len() {
         2 => RepoInfo {
             path,
+            pathspecs,
             group: Some(labels[0].clone()),
             vanity_name: labels[1].clone(),
+            tags: Vec::new(),
         },
         1 => RepoInfo {
             path,
+            pathspecs,
             group: None,
             vanity_name: labels[0].clone(),
+            tags: Vec::new(),
         },
         _ => RepoInfo {
             path: path.clone(),
+            pathspecs,
             group: None,
             vanity_name: path,
+            tags: Vec::new(),
         },
     }
 }
 
+/// Parses a repository of the config, written either in the string format of
+/// `parse_repo_string` or as an object of the structured format.
+///
+/// The paths a structured entry excludes become `:(exclude)` pathspecs, so the
+/// commits only touching them are not counted, like for the parts of a monorepo.
+///
+/// # Arguments
+/// * `entry` - The repository as written in the config
+///
+/// # Returns
+/// * `RepoInfo` - Structure containing the path, pathspecs, group, name and tags
+///
+/// # Examples
+/// ```
+/// # use gitnapped::models::{RepoConfig, RepoEntry};
+/// # use gitnapped::parser::parse_repo_entry;
+/// // path: /path/to/repo
+/// // name: Project
+/// // category: Category
+/// // exclude: [vendor]
+/// # let entry = RepoEntry::Structured(RepoConfig {
+/// #     path: "/path/to/repo".to_string(),
+/// #     name: Some("Project".to_string()),
+/// #     category: Some("Category".to_string()),
+/// #     tags: Vec::new(),
+/// #     working_hours: None,
+/// #     exclude: vec!["vendor".to_string()],
+/// # });
+/// let info = parse_repo_entry(&entry);
+/// assert_eq!(info.path, "/path/to/repo");
+/// assert_eq!(info.pathspecs, vec![":(exclude)vendor".to_string()]);
+/// assert_eq!(info.vanity_name, "Project");
+/// ```
+pub fn parse_repo_entry(entry: &RepoEntry) -> RepoInfo {
+    let repo = match entry {
+        RepoEntry::Line(line) => return parse_repo_string(line),
+        RepoEntry::Structured(repo) => repo,
+        // Scans are replaced by the repositories they find when the config is loaded
+        RepoEntry::Scan(scan) => return parse_repo_string(&scan.scan),
+    };
+
+    let (path, mut pathspecs) = split_pathspecs(repo.path.trim());
+    pathspecs.extend(
+        repo.exclude
+            .iter()
+            .map(|excluded| format!(":(exclude){}", excluded.trim())),
+    );
+
+    RepoInfo {
+        vanity_name: repo.name.clone().unwrap_or_else(|| path.clone()),
+        path,
+        pathspecs,
+        group: repo.category.clone(),
+        tags: repo.tags.clone(),
+    }
+}
+
+/// Splits `path:pathspec,pathspec` into the repository path and its pathspecs.
+///
+/// Only a colon followed by a relative path starts the pathspecs, so drive
+/// letters such as `C:/repo` stay part of the path.
+fn split_pathspecs(input: &str) -> (String, Vec<String>) {
+    let length = path_length(input);
+    if length == input.len() {
+        return (input.to_string(), Vec::new());
+    }
+
+    (
+        input[..length].trim().to_string(),
+        input[length + 1..]
+            .split(',')
+            .map(|pathspec| pathspec.trim().to_string())
+            .filter(|pathspec| !pathspec.is_empty())
+            .collect(),
+    )
+}
+
+/// Returns the length of the path at the start of `path:pathspec,pathspec`,
+/// the whole input when there are no pathspecs. The `:` of a remote
+/// repository, e.g. `github:org/repo`, or of a clone URL is part of its path.
+fn path_length(input: &str) -> usize {
+    let prefix = remote_prefix_length(input).max(url_prefix_length(input));
+    input
+        .match_indices(':')
+        .find(|(index, _)| {
+            let rest = &input[index + 1..];
+            *index >= prefix && !rest.is_empty() && !rest.starts_with(['/', '\\'])
+        })
+        .map_or(input.len(), |(index, _)| index)
+}
+
+/// Returns where the path of a `path:pathspecs [Category][Name]` string is.
+fn line_path_range(line: &str) -> Range<usize> {
+    let head = &line[..line.find('[').unwrap_or(line.len())];
+    let start = head.len() - head.trim_start().len();
+    let head = head.trim_end();
+    start..start + path_length(&head[start..])
+}
+
+/// Returns the path of a repository of the config as written, without its
+/// pathspecs and labels, e.g. `~/work/*/backend` for `~/work/*/backend [Work][API]`.
+pub fn repo_entry_path(entry: &RepoEntry) -> &str {
+    match entry {
+        RepoEntry::Line(line) => &line[line_path_range(line)],
+        RepoEntry::Structured(repo) => &repo.path[..path_length(&repo.path)],
+        RepoEntry::Scan(scan) => &scan.scan,
+    }
+}
+
+/// Returns a repository of the config with its path replaced, keeping its
+/// pathspecs, labels and settings.
+pub fn with_repo_entry_path(entry: &RepoEntry, path: &str) -> RepoEntry {
+    match entry {
+        RepoEntry::Line(line) => {
+            let range = line_path_range(line);
+            RepoEntry::Line(format!(
+                "{}{}{}",
+                &line[..range.start],
+                path,
+                &line[range.end..]
+            ))
+        }
+        RepoEntry::Structured(repo) => RepoEntry::Structured(RepoConfig {
+            path: format!("{}{}", path, &repo.path[path_length(&repo.path)..]),
+            ..repo.clone()
+        }),
+        RepoEntry::Scan(scan) => RepoEntry::Scan(ScanConfig {
+            scan: path.to_string(),
+            ..scan.clone()
+        }),
+    }
+}
+
 /// Groups repositories by their vanity names.
 ///
 /// # Arguments
@@ -0,0 +1,64 @@
+use indicatif::{ProgressBar, ProgressDrawTarget, ProgressStyle};
+use std::sync::Mutex;
+
+/// Progress bar of the running analysis, None when no bar is shown.
+static PROGRESS: Mutex<Option<ProgressBar>> = Mutex::new(None);
+
+/// Returns the progress bar being shown, if any.
+fn current() -> Option<ProgressBar> {
+    PROGRESS.lock().ok().and_then(|progress| progress.clone())
+}
+
+/// Shows a progress bar on standard error while repositories are analyzed, so
+/// long runs over big configs do not look hung. Nothing is shown for a single
+/// repository or when standard error is not a terminal.
+///
+/// # Arguments
+/// * `total` - Number of repositories about to be analyzed
+pub fn start(total: usize) {
+    if total < 2 {
+        return;
+    }
+    let bar = ProgressBar::with_draw_target(Some(total as u64), ProgressDrawTarget::stderr());
+    bar.set_style(
+        ProgressStyle::with_template("{bar:30.cyan/blue} {pos}/{len} repos, ETA {eta} {wide_msg}")
+            .unwrap_or_else(|_| ProgressStyle::default_bar()),
+    );
+    if let Ok(mut progress) = PROGRESS.lock() {
+        *progress = Some(bar);
+    }
+}
+
+/// Shows the repository being analyzed.
+pub fn analyzing(repo: &str) {
+    if let Some(bar) = current() {
+        bar.set_message(repo.to_string());
+    }
+}
+
+/// Counts a repository as analyzed.
+pub fn analyzed() {
+    if let Some(bar) = current() {
+        bar.inc(1);
+    }
+}
+
+/// Removes the progress bar once the analysis is over.
+pub fn finish() {
+    if let Some(bar) = PROGRESS
+        .lock()
+        .ok()
+        .and_then(|mut progress| progress.take())
+    {
+        bar.finish_and_clear();
+    }
+}
+
+/// Runs a function printing to the terminal with the progress bar hidden, so
+/// the output does not get mixed with the bar.
+pub fn suspend<F: FnOnce()>(print: F) {
+    match current() {
+        Some(bar) => bar.suspend(print),
+        None => print(),
+    }
+}
@@ -0,0 +1,174 @@
+use crate::display::Renderer;
+use crate::report::Report;
+use crate::utils::{debug, key_file_name, user_dir};
+use chrono::{Local, NaiveDate};
+use serde::{Deserialize, Serialize};
+use std::fs::{self, OpenOptions};
+use std::path::PathBuf;
+use std::time::{Duration, SystemTime};
+
+/// A refresh holding the lock for this long is considered dead, so that a
+/// killed one does not keep the status from being refreshed again.
+const LOCK_TIMEOUT: Duration = Duration::from_secs(120);
+
+/// Line printed by `--tmux` when `--tmux-format` is not given.
+pub const DEFAULT_TMUX_FORMAT: &str = "{icon} {commits} commits {gitnapped}%";
+
+/// The statistics of today shown in a shell prompt, as cached between prompts.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PromptStatus {
+    /// The day the statistics are of (YYYY-MM-DD)
+    pub date: String,
+    /// When the statistics were computed, as a Unix timestamp
+    pub updated_at: i64,
+    /// Number of commits of the day
+    pub commits: usize,
+    /// Number of commits made outside working hours
+    pub out_of_hours_commits: usize,
+    /// Percentage of commits made outside working hours
+    pub gitnapped_percentage: f64,
+}
+
+impl PromptStatus {
+    /// Builds the status of a day out of the report analyzing it.
+    pub fn of(date: NaiveDate, report: &Report) -> PromptStatus {
+        let stats = &report.totals.stats;
+        PromptStatus {
+            date: date.format("%Y-%m-%d").to_string(),
+            updated_at: Local::now().timestamp(),
+            commits: stats.commit_count,
+            out_of_hours_commits: stats.out_of_hours_commits,
+            gitnapped_percentage: report.totals.gitnapped_percentage,
+        }
+    }
+
+    /// Checks if the status is of today and was computed less than `max_age` ago.
+    pub fn is_fresh(&self, max_age: Duration) -> bool {
+        let now = Local::now();
+        self.date == now.format("%Y-%m-%d").to_string()
+            && now.timestamp() - self.updated_at < max_age.as_secs() as i64
+    }
+
+    /// Fills a format string with the status, see `fill_status`.
+    pub fn render(&self, format: &str) -> String {
+        fill_status(
+            format,
+            self.commits,
+            self.out_of_hours_commits,
+            self.gitnapped_percentage,
+        )
+    }
+}
+
+/// Renders the totals of the report as the single line of a tmux status bar,
+/// through a format string. The line holds no ANSI color codes, which tmux
+/// shows as garbage, the format string being free to use tmux's own
+/// `#[fg=red]` styles instead.
+pub struct TmuxRenderer {
+    /// The format string, see `fill_status`
+    pub format: String,
+}
+
+impl Renderer for TmuxRenderer {
+    fn render(&self, report: &Report) -> Result<String, String> {
+        let stats = &report.totals.stats;
+        let line = fill_status(
+            &self.format,
+            stats.commit_count,
+            stats.out_of_hours_commits,
+            report.totals.gitnapped_percentage,
+        );
+        // tmux shows the first line of the output only
+        Ok(line.replace('\n', " "))
+    }
+}
+
+/// Fills a format string with statistics: `{commits}`, `{gitnapped}` (the
+/// percentage), `{out_of_hours}` and `{icon}`, a moon once a commit was made
+/// out of hours and a sun before.
+///
+/// # Arguments
+/// * `format` - The format string, e.g. `{icon} {gitnapped}%`
+/// * `commits` - Number of commits
+/// * `out_of_hours` - Number of commits made outside working hours
+/// * `percentage` - Percentage of commits made outside working hours
+///
+/// # Returns
+/// * `String` - The format string with its placeholders replaced
+pub fn fill_status(format: &str, commits: usize, out_of_hours: usize, percentage: f64) -> String {
+    let icon = if out_of_hours > 0 { "🌙" } else { "☀️" };
+    format
+        .replace("{commits}", &commits.to_string())
+        .replace("{gitnapped}", &percentage.round().to_string())
+        .replace("{out_of_hours}", &out_of_hours.to_string())
+        .replace("{icon}", icon)
+}
+
+/// Returns the path of the cached status of a config,
+/// `$XDG_CACHE_HOME/gitnapped/prompt-<hash>.json` (`~/.cache/gitnapped/...`).
+///
+/// # Arguments
+/// * `key` - What the status depends on, e.g. the config file and the profile
+pub fn prompt_status_path(key: &str) -> Option<PathBuf> {
+    user_dir("XDG_CACHE_HOME", ".cache")
+        .map(|dir| dir.join(format!("prompt-{}", key_file_name(key))))
+}
+
+/// Reads the cached status of a config, None if there is none yet.
+pub fn read_prompt_status(key: &str) -> Option<PromptStatus> {
+    let content = fs::read_to_string(prompt_status_path(key)?).ok()?;
+    serde_json::from_str(&content)
+        .map_err(|err| debug(&format!("Ignoring the cached prompt status: {}", err)))
+        .ok()
+}
+
+/// Caches the status of a config for the next prompts.
+///
+/// # Returns
+/// * `Result<(), String>` - An error telling why the status was not written
+pub fn write_prompt_status(key: &str, status: &PromptStatus) -> Result<(), String> {
+    let path = prompt_status_path(key).ok_or("No directory to cache the prompt status in")?;
+    let content = serde_json::to_string(status).map_err(|err| err.to_string())?;
+    if let Some(dir) = path.parent() {
+        let _ = fs::create_dir_all(dir);
+    }
+    // Written aside then renamed, so that a prompt never reads half a file
+    let partial = path.with_extension("tmp");
+    fs::write(&partial, content)
+        .and_then(|_| fs::rename(&partial, &path))
+        .map_err(|err| format!("Failed to write {}: {}", path.display(), err))
+}
+
+/// Takes the lock of the refresh of a status, so that prompts drawn while
+/// the status is refreshed do not start other refreshes.
+///
+/// # Returns
+/// * `bool` - Whether the lock was taken, false while another refresh holds it
+pub fn lock_refresh(key: &str) -> bool {
+    let Some(path) = prompt_status_path(key).map(|path| path.with_extension("lock")) else {
+        return false;
+    };
+    if let Some(dir) = path.parent() {
+        let _ = fs::create_dir_all(dir);
+    }
+    let stale = fs::metadata(&path)
+        .and_then(|metadata| metadata.modified())
+        .ok()
+        .and_then(|modified| SystemTime::now().duration_since(modified).ok())
+        .is_some_and(|age| age > LOCK_TIMEOUT);
+    if stale {
+        let _ = fs::remove_file(&path);
+    }
+    OpenOptions::new()
+        .write(true)
+        .create_new(true)
+        .open(&path)
+        .is_ok()
+}
+
+/// Releases the lock taken by `lock_refresh`.
+pub fn unlock_refresh(key: &str) {
+    if let Some(path) = prompt_status_path(key).map(|path| path.with_extension("lock")) {
+        let _ = fs::remove_file(path);
+    }
+}
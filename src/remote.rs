@@ -0,0 +1,1015 @@
+use crate::analyzer::{HistoryFilter, LoggedCommit, MergeFilter};
+use crate::authors::{AuthorFilter, DateSource};
+use crate::models::ForgeConfig;
+use crate::utils::{bound_timestamp, debug, log};
+use chrono::{DateTime, Duration, FixedOffset, Local, SecondsFormat};
+use colored::Colorize;
+use serde::de::DeserializeOwned;
+use serde::Deserialize;
+use serde_json::json;
+use std::collections::BTreeMap;
+use std::env;
+
+/// Prefixes of the repositories of the config read through the API of a
+/// forge rather than from a clone, e.g. `github:org/repo`.
+pub const REMOTE_PREFIXES: [&str; 5] = ["github:", "gitlab:", "bitbucket:", "gitea:", "forgejo:"];
+
+/// Environment variable holding the token sent to the GitHub API, if any.
+pub const GITHUB_TOKEN_VARIABLE: &str = "GITHUB_TOKEN";
+
+/// Environment variable holding the token sent to the GitLab API, if any.
+pub const GITLAB_TOKEN_VARIABLE: &str = "GITLAB_TOKEN";
+
+/// Environment variable holding the access token sent to the Bitbucket API, if any.
+pub const BITBUCKET_TOKEN_VARIABLE: &str = "BITBUCKET_TOKEN";
+
+/// Environment variable holding the token sent to the Gitea and Forgejo APIs, if any.
+pub const GITEA_TOKEN_VARIABLE: &str = "GITEA_TOKEN";
+
+/// Environment variable holding the URL of the GitHub API, e.g. the one of a
+/// GitHub Enterprise server, as set in GitHub Actions.
+pub const GITHUB_API_VARIABLE: &str = "GITHUB_API_URL";
+
+/// Environment variable holding the URL of the GitHub GraphQL API, e.g. the
+/// one of a GitHub Enterprise server, as set in GitHub Actions.
+pub const GITHUB_GRAPHQL_VARIABLE: &str = "GITHUB_GRAPHQL_URL";
+
+/// Longest period GitHub tells the contributions of a user over at once.
+const CONTRIBUTIONS_SPAN_DAYS: i64 = 365;
+
+/// Number of commits asked for per page of the API.
+const PAGE_SIZE: usize = 100;
+
+/// The forge hosting a remote repository.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Forge {
+    /// github.com, or the GitHub Enterprise server of `$GITHUB_API_URL`
+    GitHub,
+    /// gitlab.com or a self-hosted GitLab instance
+    GitLab,
+    /// Bitbucket Cloud
+    Bitbucket,
+    /// A Gitea or Forgejo instance, e.g. codeberg.org
+    Gitea,
+}
+
+impl Forge {
+    /// Returns the base URL of the API of a host, unless the config sets one.
+    fn default_url(&self, host: &str) -> String {
+        match self {
+            Forge::GitHub => {
+                env::var(GITHUB_API_VARIABLE).unwrap_or_else(|_| "https://api.github.com".into())
+            }
+            Forge::GitLab | Forge::Gitea => format!("https://{}", host),
+            Forge::Bitbucket => String::from("https://api.bitbucket.org"),
+        }
+    }
+
+    /// Tells whether the API keeps the offset commits were made with, rather
+    /// than giving their dates in UTC.
+    fn keeps_offsets(&self) -> bool {
+        match self {
+            Forge::GitHub | Forge::Bitbucket => false,
+            Forge::GitLab | Forge::Gitea => true,
+        }
+    }
+
+    /// Returns the environment variable holding the token, unless the config names one.
+    fn token_variable(&self) -> &'static str {
+        match self {
+            Forge::GitHub => GITHUB_TOKEN_VARIABLE,
+            Forge::GitLab => GITLAB_TOKEN_VARIABLE,
+            Forge::Bitbucket => BITBUCKET_TOKEN_VARIABLE,
+            Forge::Gitea => GITEA_TOKEN_VARIABLE,
+        }
+    }
+}
+
+/// A repository of the config read through the API of its forge.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RemoteRepo {
+    /// The forge hosting it
+    pub forge: Forge,
+    /// The host of the forge, e.g. `gitlab.com`, which the `forges` settings of the config are looked up by
+    pub host: String,
+    /// Its full name on the forge, e.g. `org/repo` or `group/subgroup/project`
+    pub name: String,
+}
+
+impl RemoteRepo {
+    /// Parses the path of a repository of the config written as a remote one:
+    /// `github:org/repo`, `bitbucket:workspace/repo`, `gitlab:group/project`
+    /// for gitlab.com, `gitlab:host/group/project` for a self-hosted
+    /// instance, its host told apart by its dot, or `gitea:host/owner/repo`
+    /// (`forgejo:host/owner/repo`) for a Gitea or Forgejo instance.
+    ///
+    /// # Returns
+    /// * `Option<RemoteRepo>` - The remote repository, None for a local path
+    pub fn parse(path: &str) -> Option<RemoteRepo> {
+        let (forge, name) = if let Some(name) = path.strip_prefix("github:") {
+            (Forge::GitHub, name)
+        } else if let Some(name) = path.strip_prefix("bitbucket:") {
+            (Forge::Bitbucket, name)
+        } else if let Some(name) = path.strip_prefix("gitea:") {
+            (Forge::Gitea, name)
+        } else if let Some(name) = path.strip_prefix("forgejo:") {
+            (Forge::Gitea, name)
+        } else {
+            (Forge::GitLab, path.strip_prefix("gitlab:")?)
+        };
+        let name = name.trim_matches('/');
+        let segments: Vec<&str> = name.split('/').collect();
+        if segments.iter().any(|segment| segment.is_empty()) {
+            return None;
+        }
+
+        let (host, name) = match forge {
+            Forge::GitHub if segments.len() == 2 => ("github.com", name),
+            Forge::GitLab if segments.len() > 2 && segments[0].contains('.') => {
+                (segments[0], &name[segments[0].len() + 1..])
+            }
+            Forge::GitLab if segments.len() >= 2 => ("gitlab.com", name),
+            Forge::Bitbucket if segments.len() == 2 => ("bitbucket.org", name),
+            Forge::Gitea if segments.len() == 3 => (segments[0], &name[segments[0].len() + 1..]),
+            _ => return None,
+        };
+        Some(RemoteRepo {
+            forge,
+            host: host.to_string(),
+            name: name.to_string(),
+        })
+    }
+}
+
+/// Returns the length of the remote prefix a path of the config starts with,
+/// 0 for a local path, so that its `:` is not taken for the start of pathspecs.
+pub fn remote_prefix_length(path: &str) -> usize {
+    REMOTE_PREFIXES
+        .iter()
+        .find(|prefix| path.starts_with(**prefix))
+        .map_or(0, |prefix| prefix.len())
+}
+
+/// Reads the commits of a remote repository committed in a period, as
+/// `read_log` does for a clone.
+///
+/// The API is reached at the `url` of the `forges` settings of its host, with
+/// the token of the variable they name, else the defaults of the forge. When
+/// the API does not tell the timezone commits were made in, as GitHub's and
+/// Bitbucket's, their dates are read in the timezone of the machine. Mailmaps and `--first-parent`
+/// do not apply, and pathspecs only match paths and the directories holding
+/// them.
+///
+/// # Arguments
+/// * `remote` - The remote repository
+/// * `authors` - Authors whose commits are counted
+/// * `since` - Start of the period, as `YYYY-MM-DD HH:MM:SS` or ISO 8601
+/// * `until` - End of the period, as `YYYY-MM-DD HH:MM:SS` or ISO 8601
+/// * `history` - Which part of the history is analyzed
+///
+/// # Returns
+/// * `Option<Vec<LoggedCommit>>` - The commits, newest first, None if the history could not be read
+pub fn read_remote_log(
+    remote: &RemoteRepo,
+    authors: &AuthorFilter,
+    since: &str,
+    until: &str,
+    history: &HistoryFilter,
+) -> Option<Vec<LoggedCommit>> {
+    let to_utc = |value: &str| {
+        let timestamp = bound_timestamp(value)?;
+        DateTime::from_timestamp(timestamp, 0)
+            .map(|date| date.to_rfc3339_opts(SecondsFormat::Secs, true))
+    };
+    let (Some(since), Some(until)) = (to_utc(since), to_utc(until)) else {
+        debug(&format!("Invalid period {} - {}", since, until));
+        return None;
+    };
+    if history.first_parent {
+        debug(&format!("--first-parent does not apply to {}", remote.name));
+    }
+
+    let settings = history.forges.get(&remote.host);
+    let api = settings
+        .and_then(|settings| settings.url.clone())
+        .unwrap_or_else(|| remote.forge.default_url(&remote.host));
+    let api = api.trim_end_matches('/');
+    let token_variable = settings
+        .and_then(|settings| settings.token_env.as_deref())
+        .unwrap_or(remote.forge.token_variable());
+    let token = env::var(token_variable).ok();
+    // Pathspecs filter the commits by the files they changed
+    let changes = history.changes || !history.pathspecs.is_empty();
+    let commits = match remote.forge {
+        Forge::GitHub => {
+            github_commits(api, token.as_deref(), &remote.name, &since, &until, changes)
+        }
+        Forge::GitLab => {
+            gitlab_commits(api, token.as_deref(), &remote.name, &since, &until, changes)
+        }
+        Forge::Bitbucket => bitbucket_commits(api, token.as_deref(), &remote.name, &since, &until),
+        Forge::Gitea => gitea_commits(api, token.as_deref(), &remote.name, &since, &until),
+    };
+    let commits = commits
+        .map_err(|err| {
+            log(&format!(
+                "{}",
+                format!(
+                    "Warning: cannot read the history of {}: {}",
+                    remote.name, err
+                )
+                .bright_red()
+            ))
+        })
+        .ok()?;
+
+    let committer = authors.source == DateSource::Committer;
+    Some(
+        commits
+            .into_iter()
+            .filter(|commit| match history.merges {
+                MergeFilter::Include => true,
+                MergeFilter::Exclude => !commit.merge,
+                MergeFilter::Only => commit.merge,
+            })
+            .filter(|commit| matches_pathspecs(&commit.paths, &history.pathspecs))
+            .filter_map(|commit| {
+                let committed = DateTime::parse_from_rfc3339(&commit.committer_date)
+                    .map(|date| date.timestamp())
+                    .unwrap_or_default();
+                let (identity, date) = if committer {
+                    (commit.committer, commit.committer_date)
+                } else {
+                    (commit.author, commit.author_date)
+                };
+                if !authors.is_included(&identity) || authors.is_excluded(&identity) {
+                    debug(&format!("Skipping commit {} of {}", commit.hash, identity));
+                    return None;
+                }
+                let date = DateTime::parse_from_rfc3339(&date).ok()?;
+                let date = if remote.forge.keeps_offsets() {
+                    date
+                } else {
+                    date.with_timezone(&Local).fixed_offset()
+                }
+                .to_rfc3339_opts(SecondsFormat::Secs, false);
+                let short_hash = commit.hash.get(..7).unwrap_or(&commit.hash);
+                Some(LoggedCommit {
+                    author: identity,
+                    line: format!("{} {} {}", short_hash, date, commit.subject),
+                    committed,
+                    lines_added: commit.lines_added,
+                    lines_removed: commit.lines_removed,
+                    paths: commit.paths,
+                    merge: commit.merge,
+                })
+            })
+            .collect(),
+    )
+}
+
+/// A commit as read from the API of a forge, before it is filtered.
+struct RemoteCommit {
+    hash: String,
+    author: String,
+    author_date: String,
+    committer: String,
+    committer_date: String,
+    subject: String,
+    merge: bool,
+    lines_added: usize,
+    lines_removed: usize,
+    paths: Vec<String>,
+}
+
+/// Checks if a commit touching some paths is kept by pathspecs: one of the
+/// paths is, or is inside, an included pathspec and none of the excluded ones
+/// (`:(exclude)path`, `:!path` or `:^path`).
+fn matches_pathspecs(paths: &[String], pathspecs: &[String]) -> bool {
+    if pathspecs.is_empty() {
+        return true;
+    }
+    let (excluded, included): (Vec<&str>, Vec<&str>) = pathspecs
+        .iter()
+        .map(String::as_str)
+        .partition(|pathspec| pathspec.starts_with(':'));
+    let excluded: Vec<&str> = excluded
+        .iter()
+        .filter_map(|pathspec| {
+            ["(exclude)", "!", "^"]
+                .iter()
+                .find_map(|magic| pathspec[1..].strip_prefix(magic))
+        })
+        .collect();
+    let inside = |path: &str, pathspec: &str| {
+        let pathspec = pathspec.trim_end_matches('/');
+        path == pathspec || path.starts_with(&format!("{}/", pathspec))
+    };
+
+    paths.iter().any(|path| {
+        (included.is_empty() || included.iter().any(|pathspec| inside(path, pathspec)))
+            && !excluded.iter().any(|pathspec| inside(path, pathspec))
+    })
+}
+
+/// Sends a GET request to the API of a forge and parses its JSON response.
+///
+/// # Arguments
+/// * `url` - The URL, query included
+/// * `authorization` - Optional header authenticating the request, e.g. `Authorization`
+fn get_json<T: DeserializeOwned>(
+    url: &str,
+    authorization: Option<(&str, &str)>,
+) -> Result<T, String> {
+    let mut request = ureq::get(url).header("User-Agent", "gitnapped");
+    if let Some((header, value)) = authorization {
+        request = request.header(header, value);
+    }
+    debug(&format!("Requesting {}", url));
+    let mut response = request
+        .config()
+        .http_status_as_error(false)
+        .build()
+        .call()
+        .map_err(|err| err.to_string())?;
+    let header = |name: &str| {
+        response
+            .headers()
+            .get(name)
+            .and_then(|value| value.to_str().ok())
+            .map(str::to_string)
+    };
+    if !response.status().is_success() {
+        // Every further request would be refused too until the limit resets
+        if header("X-RateLimit-Remaining").as_deref() == Some("0") {
+            let reset = header("X-RateLimit-Reset")
+                .and_then(|reset| reset.parse::<i64>().ok())
+                .and_then(|reset| DateTime::from_timestamp(reset, 0))
+                .map(|reset| format!(" until {}", reset.with_timezone(&Local).format("%H:%M")))
+                .unwrap_or_default();
+            return Err(format!(
+                "API rate limit exceeded{} requesting {}",
+                reset, url
+            ));
+        }
+        return Err(format!("http status: {}", response.status().as_u16()));
+    }
+    let body = response
+        .body_mut()
+        .read_to_string()
+        .map_err(|err| err.to_string())?;
+    serde_json::from_str(&body).map_err(|err| format!("Unexpected response of {}: {}", url, err))
+}
+
+/// A commit of the GitHub API, which the Gitea API mirrors.
+#[derive(Deserialize)]
+struct GitHubCommit {
+    sha: String,
+    commit: GitHubCommitData,
+    parents: Vec<serde_json::Value>,
+    /// Only in the response of a single commit, or of a Gitea list
+    stats: Option<GitHubStats>,
+    /// Only in the response of a single commit, or of a Gitea list
+    #[serde(default)]
+    files: Vec<GitHubFile>,
+}
+
+#[derive(Deserialize)]
+struct GitHubCommitData {
+    author: GitHubSignature,
+    committer: GitHubSignature,
+    message: String,
+}
+
+#[derive(Deserialize)]
+struct GitHubSignature {
+    name: String,
+    email: String,
+    date: String,
+}
+
+#[derive(Deserialize)]
+struct GitHubStats {
+    additions: usize,
+    deletions: usize,
+}
+
+#[derive(Deserialize)]
+struct GitHubFile {
+    filename: String,
+}
+
+/// Reads the commits of a GitHub repository committed in a period, every one
+/// fetched again for the lines and files it changed when they are needed.
+///
+/// # Arguments
+/// * `api` - Base URL of the API
+/// * `token` - Optional token sent with the requests
+/// * `name` - The repository, e.g. `org/repo`
+/// * `since` - Start of the period, ISO 8601
+/// * `until` - End of the period, ISO 8601
+/// * `changes` - Whether the lines and files changed by every commit are fetched
+fn github_commits(
+    api: &str,
+    token: Option<&str>,
+    name: &str,
+    since: &str,
+    until: &str,
+    changes: bool,
+) -> Result<Vec<RemoteCommit>, String> {
+    let bearer = token.map(|token| format!("Bearer {}", token));
+    let authorization = bearer.as_deref().map(|bearer| ("Authorization", bearer));
+
+    let mut listed: Vec<GitHubCommit> = Vec::new();
+    for page in 1.. {
+        let url = format!(
+            "{}/repos/{}/commits?since={}&until={}&per_page={}&page={}",
+            api, name, since, until, PAGE_SIZE, page
+        );
+        let commits: Vec<GitHubCommit> = get_json(&url, authorization)?;
+        let last_page = commits.len() < PAGE_SIZE;
+        listed.extend(commits);
+        if last_page {
+            break;
+        }
+    }
+    debug(&format!("Listed {} commits of {}", listed.len(), name));
+
+    listed
+        .into_iter()
+        .map(|commit| {
+            // The list leaves out the changes of every commit
+            let (stats, files) = if changes {
+                let url = format!("{}/repos/{}/commits/{}", api, name, commit.sha);
+                let detail: GitHubCommit = get_json(&url, authorization)?;
+                (detail.stats, detail.files)
+            } else {
+                (None, Vec::new())
+            };
+            let data = commit.commit;
+            Ok(RemoteCommit {
+                author: format!("{} <{}>", data.author.name, data.author.email),
+                author_date: data.author.date,
+                committer: format!("{} <{}>", data.committer.name, data.committer.email),
+                committer_date: data.committer.date,
+                subject: data.message.lines().next().unwrap_or_default().to_string(),
+                merge: commit.parents.len() > 1,
+                lines_added: stats.as_ref().map_or(0, |stats| stats.additions),
+                lines_removed: stats.as_ref().map_or(0, |stats| stats.deletions),
+                paths: files.into_iter().map(|file| file.filename).collect(),
+                hash: commit.sha,
+            })
+        })
+        .collect()
+}
+
+/// A commit of the GitLab API, listed with its stats.
+#[derive(Deserialize)]
+struct GitLabCommit {
+    id: String,
+    title: String,
+    author_name: String,
+    author_email: String,
+    authored_date: String,
+    committer_name: String,
+    committer_email: String,
+    committed_date: String,
+    #[serde(default)]
+    parent_ids: Vec<String>,
+    stats: Option<GitLabStats>,
+}
+
+#[derive(Deserialize)]
+struct GitLabStats {
+    additions: usize,
+    deletions: usize,
+}
+
+/// A file changed by a commit of the GitLab API.
+#[derive(Deserialize)]
+struct GitLabDiff {
+    new_path: String,
+}
+
+/// Reads the commits of a GitLab project committed in a period, every one's
+/// diff fetched for the files it changed when they are needed.
+///
+/// # Arguments
+/// * `api` - Base URL of the instance, e.g. `https://gitlab.com`
+/// * `token` - Optional token sent with the requests
+/// * `name` - The project, e.g. `group/subgroup/project`
+/// * `since` - Start of the period, ISO 8601
+/// * `until` - End of the period, ISO 8601
+/// * `changes` - Whether the files changed by every commit are fetched
+fn gitlab_commits(
+    api: &str,
+    token: Option<&str>,
+    name: &str,
+    since: &str,
+    until: &str,
+    changes: bool,
+) -> Result<Vec<RemoteCommit>, String> {
+    let authorization = token.map(|token| ("PRIVATE-TOKEN", token));
+    let project = format!("{}/api/v4/projects/{}", api, name.replace('/', "%2F"));
+
+    let mut listed: Vec<GitLabCommit> = Vec::new();
+    for page in 1.. {
+        let url = format!(
+            "{}/repository/commits?since={}&until={}&with_stats=true&per_page={}&page={}",
+            project, since, until, PAGE_SIZE, page
+        );
+        let commits: Vec<GitLabCommit> = get_json(&url, authorization)?;
+        let last_page = commits.len() < PAGE_SIZE;
+        listed.extend(commits);
+        if last_page {
+            break;
+        }
+    }
+    debug(&format!("Listed {} commits of {}", listed.len(), name));
+
+    Ok(listed
+        .into_iter()
+        .map(|commit| {
+            // The list leaves out the files of every commit, and the diff is paginated too
+            let mut paths = Vec::new();
+            if changes {
+                for page in 1.. {
+                    let url = format!(
+                        "{}/repository/commits/{}/diff?per_page={}&page={}",
+                        project, commit.id, PAGE_SIZE, page
+                    );
+                    let diffs = get_json::<Vec<GitLabDiff>>(&url, authorization)
+                        .map_err(|err| debug(&format!("No files for {}: {}", commit.id, err)))
+                        .unwrap_or_default();
+                    let last_page = diffs.len() < PAGE_SIZE;
+                    paths.extend(diffs.into_iter().map(|diff| diff.new_path));
+                    if last_page {
+                        break;
+                    }
+                }
+            }
+            RemoteCommit {
+                author: format!("{} <{}>", commit.author_name, commit.author_email),
+                author_date: commit.authored_date,
+                committer: format!("{} <{}>", commit.committer_name, commit.committer_email),
+                committer_date: commit.committed_date,
+                subject: commit.title,
+                merge: commit.parent_ids.len() > 1,
+                lines_added: commit.stats.as_ref().map_or(0, |stats| stats.additions),
+                lines_removed: commit.stats.as_ref().map_or(0, |stats| stats.deletions),
+                paths,
+                hash: commit.id,
+            }
+        })
+        .collect())
+}
+
+/// A page of the Bitbucket API, the URL of the next one included.
+#[derive(Deserialize)]
+struct BitbucketPage<T> {
+    values: Vec<T>,
+    next: Option<String>,
+}
+
+/// A commit of the Bitbucket API, which only knows its author.
+#[derive(Deserialize)]
+struct BitbucketCommit {
+    hash: String,
+    date: String,
+    message: String,
+    author: BitbucketAuthor,
+    #[serde(default)]
+    parents: Vec<serde_json::Value>,
+}
+
+#[derive(Deserialize)]
+struct BitbucketAuthor {
+    /// `Name <email>` as written in the commit
+    raw: String,
+}
+
+/// The lines changed in a file by a commit of the Bitbucket API.
+#[derive(Deserialize)]
+struct BitbucketDiffstat {
+    lines_added: usize,
+    lines_removed: usize,
+    new: Option<BitbucketPath>,
+    old: Option<BitbucketPath>,
+}
+
+#[derive(Deserialize)]
+struct BitbucketPath {
+    path: String,
+}
+
+/// Reads the commits of a Bitbucket Cloud repository made in a period, every
+/// one's diffstat fetched for the lines and files it changed. The API cannot
+/// filter commits by date, so pages are read until one ends before the period.
+///
+/// # Arguments
+/// * `api` - Base URL of the API, e.g. `https://api.bitbucket.org`
+/// * `token` - Optional access token sent with the requests
+/// * `name` - The repository, e.g. `workspace/repo`
+/// * `since` - Start of the period, ISO 8601
+/// * `until` - End of the period, ISO 8601
+fn bitbucket_commits(
+    api: &str,
+    token: Option<&str>,
+    name: &str,
+    since: &str,
+    until: &str,
+) -> Result<Vec<RemoteCommit>, String> {
+    let bearer = token.map(|token| format!("Bearer {}", token));
+    let authorization = bearer.as_deref().map(|bearer| ("Authorization", bearer));
+    let timestamp = |date: &str| DateTime::parse_from_rfc3339(date).map(|date| date.timestamp());
+    let period = timestamp(since).and_then(|since| Ok(since..=timestamp(until)?));
+    let period = period.map_err(|err| format!("Invalid period: {}", err))?;
+    let repository = format!("{}/2.0/repositories/{}", api, name);
+
+    let mut listed: Vec<BitbucketCommit> = Vec::new();
+    let mut next = Some(format!("{}/commits?pagelen={}", repository, PAGE_SIZE));
+    while let Some(url) = next {
+        let page: BitbucketPage<BitbucketCommit> = get_json(&url, authorization)?;
+        // Newest first, so a page ending before the period is the last one read
+        let before = page
+            .values
+            .last()
+            .is_none_or(|commit| timestamp(&commit.date).is_ok_and(|date| date < *period.start()));
+        next = page.next.filter(|_| !before);
+        listed.extend(
+            page.values
+                .into_iter()
+                .filter(|commit| timestamp(&commit.date).is_ok_and(|date| period.contains(&date))),
+        );
+    }
+    debug(&format!("Listed {} commits of {}", listed.len(), name));
+
+    Ok(listed
+        .into_iter()
+        .map(|commit| {
+            let mut lines_added = 0;
+            let mut lines_removed = 0;
+            let mut paths = Vec::new();
+            let mut next = Some(format!(
+                "{}/diffstat/{}?pagelen={}",
+                repository, commit.hash, PAGE_SIZE
+            ));
+            while let Some(url) = next {
+                let page: BitbucketPage<BitbucketDiffstat> = match get_json(&url, authorization) {
+                    Ok(page) => page,
+                    Err(err) => {
+                        debug(&format!("No changes for {}: {}", commit.hash, err));
+                        break;
+                    }
+                };
+                for file in page.values {
+                    lines_added += file.lines_added;
+                    lines_removed += file.lines_removed;
+                    // Deleted files only have their old path
+                    if let Some(path) = file.new.or(file.old) {
+                        paths.push(path.path);
+                    }
+                }
+                next = page.next;
+            }
+            RemoteCommit {
+                author: commit.author.raw.clone(),
+                committer: commit.author.raw,
+                author_date: commit.date.clone(),
+                committer_date: commit.date,
+                subject: commit
+                    .message
+                    .lines()
+                    .next()
+                    .unwrap_or_default()
+                    .to_string(),
+                merge: commit.parents.len() > 1,
+                lines_added,
+                lines_removed,
+                paths,
+                hash: commit.hash,
+            }
+        })
+        .collect())
+}
+
+/// Reads the commits of a Gitea or Forgejo repository committed in a period,
+/// listed with the lines and files they changed.
+///
+/// # Arguments
+/// * `api` - Base URL of the instance, e.g. `https://codeberg.org`
+/// * `token` - Optional token sent with the requests
+/// * `name` - The repository, e.g. `owner/repo`
+/// * `since` - Start of the period, ISO 8601
+/// * `until` - End of the period, ISO 8601
+fn gitea_commits(
+    api: &str,
+    token: Option<&str>,
+    name: &str,
+    since: &str,
+    until: &str,
+) -> Result<Vec<RemoteCommit>, String> {
+    let header = token.map(|token| format!("token {}", token));
+    let authorization = header.as_deref().map(|header| ("Authorization", header));
+
+    let mut listed: Vec<GitHubCommit> = Vec::new();
+    for page in 1.. {
+        let url = format!(
+            "{}/api/v1/repos/{}/commits?since={}&until={}&stat=true&files=true&limit={}&page={}",
+            api, name, since, until, PAGE_SIZE, page
+        );
+        // Instances cap the size of the pages, so they are read up to an empty one
+        let commits: Vec<GitHubCommit> = get_json(&url, authorization)?;
+        if commits.is_empty() {
+            break;
+        }
+        listed.extend(commits);
+    }
+    debug(&format!("Listed {} commits of {}", listed.len(), name));
+
+    Ok(listed
+        .into_iter()
+        .map(|commit| {
+            let data = commit.commit;
+            RemoteCommit {
+                author: format!("{} <{}>", data.author.name, data.author.email),
+                author_date: data.author.date,
+                committer: format!("{} <{}>", data.committer.name, data.committer.email),
+                committer_date: data.committer.date,
+                subject: data.message.lines().next().unwrap_or_default().to_string(),
+                merge: commit.parents.len() > 1,
+                lines_added: commit.stats.as_ref().map_or(0, |stats| stats.additions),
+                lines_removed: commit.stats.as_ref().map_or(0, |stats| stats.deletions),
+                paths: commit.files.into_iter().map(|file| file.filename).collect(),
+                hash: commit.sha,
+            }
+        })
+        .collect())
+}
+
+/// The pull requests and reviews of the GitHub user of a token over a period.
+#[derive(Debug, Default)]
+pub struct GitHubActivity {
+    /// The login of the user
+    pub login: String,
+    /// When the pull requests were opened
+    pub pull_requests: Vec<DateTime<FixedOffset>>,
+    /// When the reviews were submitted
+    pub reviews: Vec<DateTime<FixedOffset>>,
+    /// When the comments of the reviews were written, possibly long before
+    /// their review was submitted
+    pub review_comments: Vec<DateTime<FixedOffset>>,
+}
+
+/// Pull requests opened by the user of the token, from `from` to `to`.
+const PULL_REQUESTS_QUERY: &str = "query($from: DateTime!, $to: DateTime!, $after: String) {
+  viewer {
+    login
+    contributionsCollection(from: $from, to: $to) {
+      pullRequestContributions(first: 100, after: $after) {
+        pageInfo { hasNextPage endCursor }
+        nodes { occurredAt }
+      }
+    }
+  }
+}";
+
+/// Reviews submitted by the user of the token, from `from` to `to`, with their comments.
+const REVIEWS_QUERY: &str = "query($from: DateTime!, $to: DateTime!, $after: String) {
+  viewer {
+    login
+    contributionsCollection(from: $from, to: $to) {
+      pullRequestReviewContributions(first: 100, after: $after) {
+        pageInfo { hasNextPage endCursor }
+        nodes {
+          occurredAt
+          pullRequestReview { comments(first: 100) { nodes { createdAt } } }
+        }
+      }
+    }
+  }
+}";
+
+#[derive(Deserialize)]
+struct GraphQlResponse<T> {
+    data: Option<T>,
+    #[serde(default)]
+    errors: Vec<GraphQlError>,
+}
+
+#[derive(Deserialize)]
+struct GraphQlError {
+    message: String,
+}
+
+#[derive(Deserialize)]
+struct ViewerData {
+    viewer: Viewer,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct Viewer {
+    login: String,
+    contributions_collection: ContributionsCollection,
+}
+
+/// The contributions of a user, only the connection asked for being set.
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct ContributionsCollection {
+    pull_request_contributions: Option<Contributions>,
+    pull_request_review_contributions: Option<Contributions>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct Contributions {
+    page_info: PageInfo,
+    nodes: Vec<Contribution>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct PageInfo {
+    has_next_page: bool,
+    end_cursor: Option<String>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct Contribution {
+    occurred_at: String,
+    /// Only for review contributions
+    pull_request_review: Option<ReviewComments>,
+}
+
+#[derive(Deserialize)]
+struct ReviewComments {
+    comments: ReviewCommentNodes,
+}
+
+#[derive(Deserialize)]
+struct ReviewCommentNodes {
+    nodes: Vec<ReviewComment>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct ReviewComment {
+    created_at: String,
+}
+
+/// Reads the pull requests opened, the reviews submitted and the review
+/// comments written by the GitHub user of the token over a period, through
+/// the GraphQL API of github.com, or of `$GITHUB_GRAPHQL_URL`, or the one
+/// next to the API set for github.com in the `forges` section.
+///
+/// # Arguments
+/// * `forges` - The `forges` settings of the config, for the URL and the token
+/// * `since` - Start of the period, as for `git log --since`
+/// * `until` - End of the period, as for `git log --until`
+///
+/// # Returns
+/// * `Result<GitHubActivity, String>` - The activity, dates in the timezone of the machine, or an error telling why it was not read
+pub fn read_github_activity(
+    forges: &BTreeMap<String, ForgeConfig>,
+    since: &str,
+    until: &str,
+) -> Result<GitHubActivity, String> {
+    let settings = forges.get("github.com");
+    let token_variable = settings
+        .and_then(|settings| settings.token_env.as_deref())
+        .unwrap_or(GITHUB_TOKEN_VARIABLE);
+    let token = env::var(token_variable)
+        .map_err(|_| format!("The GitHub activity needs a token in ${}", token_variable))?;
+    let url = match (
+        env::var(GITHUB_GRAPHQL_VARIABLE),
+        settings.and_then(|s| s.url.as_deref()),
+    ) {
+        (Ok(url), _) => url,
+        // GitHub Enterprise serves its REST API at /api/v3 and GraphQL at /api/graphql
+        (_, Some(api)) => match api.trim_end_matches('/').strip_suffix("/v3") {
+            Some(base) => format!("{}/graphql", base),
+            None => format!("{}/graphql", api.trim_end_matches('/')),
+        },
+        _ => String::from("https://api.github.com/graphql"),
+    };
+
+    let bound = |value: &str| {
+        bound_timestamp(value)
+            .and_then(|timestamp| DateTime::from_timestamp(timestamp, 0))
+            .ok_or_else(|| format!("Invalid period {} - {}", since, until))
+    };
+    let (mut from, end) = (bound(since)?, bound(until)?);
+    let local = |date: &str| {
+        DateTime::parse_from_rfc3339(date)
+            .map(|date| date.with_timezone(&Local).fixed_offset())
+            .map_err(|err| debug(&format!("Invalid date {}: {}", date, err)))
+            .ok()
+    };
+
+    let mut activity = GitHubActivity::default();
+    // GitHub answers for a year at most, so longer periods are read a year at a time
+    while from <= end {
+        let to = end.min(from + Duration::days(CONTRIBUTIONS_SPAN_DAYS) - Duration::seconds(1));
+        let span = (
+            from.to_rfc3339_opts(SecondsFormat::Secs, true),
+            to.to_rfc3339_opts(SecondsFormat::Secs, true),
+        );
+
+        let (login, pull_requests) =
+            github_contributions(&url, &token, PULL_REQUESTS_QUERY, &span)?;
+        activity.login = login;
+        activity
+            .pull_requests
+            .extend(pull_requests.iter().filter_map(|pr| local(&pr.occurred_at)));
+
+        let (_, reviews) = github_contributions(&url, &token, REVIEWS_QUERY, &span)?;
+        for review in reviews {
+            activity.reviews.extend(local(&review.occurred_at));
+            let comments = review
+                .pull_request_review
+                .map(|review| review.comments.nodes)
+                .unwrap_or_default();
+            activity.review_comments.extend(
+                comments
+                    .iter()
+                    .filter_map(|comment| local(&comment.created_at)),
+            );
+        }
+
+        from = to + Duration::seconds(1);
+    }
+    debug(&format!(
+        "{} opened {} pull requests and submitted {} reviews with {} comments",
+        activity.login,
+        activity.pull_requests.len(),
+        activity.reviews.len(),
+        activity.review_comments.len()
+    ));
+    Ok(activity)
+}
+
+/// Reads every page of the contributions a query asks for over a span of at
+/// most a year.
+///
+/// # Arguments
+/// * `url` - URL of the GraphQL API
+/// * `token` - The token, whose user the contributions are of
+/// * `query` - `PULL_REQUESTS_QUERY` or `REVIEWS_QUERY`
+/// * `span` - Start and end of the span, ISO 8601
+///
+/// # Returns
+/// * `Result<(String, Vec<Contribution>), String>` - The login of the user and the contributions
+fn github_contributions(
+    url: &str,
+    token: &str,
+    query: &str,
+    span: &(String, String),
+) -> Result<(String, Vec<Contribution>), String> {
+    let mut contributions = Vec::new();
+    let mut after: Option<String> = None;
+    loop {
+        let payload = json!({
+            "query": query,
+            "variables": { "from": span.0, "to": span.1, "after": after },
+        });
+        debug(&format!(
+            "Requesting the contributions of {} to {}",
+            span.0, span.1
+        ));
+        let mut response = ureq::post(url)
+            .header("User-Agent", "gitnapped")
+            .header("Authorization", &format!("Bearer {}", token))
+            .header("Content-Type", "application/json")
+            .send(payload.to_string())
+            .map_err(|err| err.to_string())?;
+        let body = response
+            .body_mut()
+            .read_to_string()
+            .map_err(|err| err.to_string())?;
+        let response: GraphQlResponse<ViewerData> = serde_json::from_str(&body)
+            .map_err(|err| format!("Unexpected response of {}: {}", url, err))?;
+        if let Some(error) = response.errors.first() {
+            return Err(error.message.clone());
+        }
+        let viewer = response
+            .data
+            .ok_or_else(|| format!("Unexpected response of {}: no data", url))?
+            .viewer;
+
+        let collection = viewer.contributions_collection;
+        let Some(page) = collection
+            .pull_request_contributions
+            .or(collection.pull_request_review_contributions)
+        else {
+            return Ok((viewer.login, contributions));
+        };
+        contributions.extend(page.nodes);
+        if !page.page_info.has_next_page || page.page_info.end_cursor.is_none() {
+            return Ok((viewer.login, contributions));
+        }
+        after = page.page_info.end_cursor;
+    }
+}
@@ -0,0 +1,753 @@
+use crate::health::{health_score, HealthScore};
+use crate::models::{CategoryStats, ProjectStats, RepoInfo, RepoStats};
+use crate::remote::GitHubActivity;
+use crate::schedule::Schedule;
+use crate::tickets::TicketPatterns;
+use crate::timetracking::ProjectSession;
+use crate::utils::{
+    commit_intervals, commit_streaks, commits_per_day, get_max_commit_day, inactivity_gaps,
+    is_repo_active, parse_range_bound, short_sleep_days, typical_day,
+};
+use chrono::{DateTime, Datelike, FixedOffset, Local, NaiveDate, Timelike};
+use serde::Serialize;
+use std::collections::{BTreeMap, BTreeSet};
+
+/// A single repository as exposed in a report.
+#[derive(Debug, Serialize)]
+pub struct RepoReport {
+    /// Path to the repository
+    pub path: String,
+    /// Display name for the repository
+    pub name: String,
+    /// Optional group/category name
+    pub group: Option<String>,
+    /// Labels of the repository from the structured config
+    pub tags: Vec<String>,
+    /// Percentage of commits made outside working hours
+    pub gitnapped_percentage: f64,
+    /// Time between consecutive commits, if there are at least two
+    pub commit_interval: Option<CommitInterval>,
+    /// Statistics for the repository
+    pub stats: RepoStats,
+}
+
+/// A category of repositories as exposed in a report.
+#[derive(Debug, Serialize)]
+pub struct CategoryReport {
+    /// Name of the category
+    pub name: String,
+    /// Number of repositories with commits in the period
+    pub active_repos: usize,
+    /// Percentage of commits made outside working hours
+    pub gitnapped_percentage: f64,
+    /// Aggregated stats for all repositories in this category
+    pub total: RepoStats,
+    /// Repositories in this category
+    pub repos: Vec<RepoReport>,
+}
+
+/// A project (group of repositories sharing a vanity name) as exposed in a report.
+#[derive(Debug, Serialize)]
+pub struct ProjectReport {
+    /// Name of the project
+    pub name: String,
+    /// Optional group/category this project belongs to
+    pub group: Option<String>,
+    /// Paths of the repositories in this project
+    pub repos: Vec<String>,
+    /// Percentage of commits made outside working hours
+    pub gitnapped_percentage: f64,
+    /// Aggregated stats for all repositories in this project
+    pub stats: RepoStats,
+}
+
+/// The most active day of the analyzed period.
+#[derive(Debug, Serialize)]
+pub struct MostActiveDay {
+    /// Date in YYYY-MM-DD format
+    pub date: String,
+    /// Number of commits on that date
+    pub commits: usize,
+}
+
+/// Time between consecutive commits, in minutes.
+#[derive(Debug, Serialize)]
+pub struct CommitInterval {
+    /// Mean time between two commits
+    pub mean_minutes: u64,
+    /// Median time between two commits
+    pub median_minutes: u64,
+}
+
+impl CommitInterval {
+    /// Computes the time between the commits of the statistics, None with fewer than two commits.
+    fn of(stats: &RepoStats) -> Option<CommitInterval> {
+        commit_intervals(&stats.commit_times).map(|(mean_minutes, median_minutes)| CommitInterval {
+            mean_minutes,
+            median_minutes,
+        })
+    }
+}
+
+/// The typical working day of the analyzed period.
+#[derive(Debug, Serialize)]
+pub struct TypicalDay {
+    /// Average time of the first commit of the day (HH:MM)
+    pub first_commit: String,
+    /// Average time of the last commit of the day (HH:MM), late nights counting for the day before
+    pub last_commit: String,
+}
+
+/// Streaks of days with commits in the analyzed period.
+#[derive(Debug, Serialize)]
+pub struct Streaks {
+    /// Longest run of consecutive days with commits
+    pub longest_streak: usize,
+    /// Run of days with commits up to the end of the period
+    pub current_streak: usize,
+    /// Longest run of consecutive days without commits
+    pub longest_gap: usize,
+}
+
+/// A metric of the period compared with the same metric of the previous period.
+#[derive(Debug, Serialize)]
+pub struct TrendMetric {
+    /// Name of the metric, e.g. `commits`
+    pub name: String,
+    /// Value in the analyzed period
+    pub current: f64,
+    /// Value in the previous period
+    pub previous: f64,
+    /// Relative change in percent, None when the previous value is zero
+    pub change_percent: Option<f64>,
+    /// Whether the metric is a percentage, compared in percentage points
+    #[serde(skip)]
+    pub points: bool,
+    /// Whether a decrease is an improvement, e.g. for gitnapped commits
+    #[serde(skip)]
+    pub lower_is_better: bool,
+}
+
+/// The analyzed period compared with the previous period of the same length.
+#[derive(Debug, Serialize)]
+pub struct Trend {
+    /// Start of the previous period
+    pub since: String,
+    /// End of the previous period
+    pub until: String,
+    /// The compared metrics
+    pub metrics: Vec<TrendMetric>,
+}
+
+impl Trend {
+    /// Compares the statistics of a period with those of the previous period.
+    ///
+    /// # Arguments
+    /// * `current` - Statistics of the analyzed period
+    /// * `previous` - Statistics of the previous period
+    /// * `since` - Start of the analyzed period
+    /// * `until` - End of the analyzed period
+    /// * `previous_since` - Start of the previous period
+    /// * `previous_until` - End of the previous period
+    ///
+    /// # Returns
+    /// * `Trend` - The compared metrics
+    pub fn of(
+        current: &RepoStats,
+        previous: &RepoStats,
+        (since, until): (&str, &str),
+        (previous_since, previous_until): (&str, &str),
+    ) -> Trend {
+        let metric = TrendMetric::new;
+        let hours = |stats: &RepoStats| (stats.out_of_hours_minutes as f64 / 6.0).round() / 10.0;
+        let lines = |stats: &RepoStats| (stats.lines_added + stats.lines_removed) as f64;
+
+        Trend {
+            since: previous_since.to_string(),
+            until: previous_until.to_string(),
+            metrics: vec![
+                metric(
+                    "commits",
+                    current.commit_count as f64,
+                    previous.commit_count as f64,
+                    false,
+                    false,
+                ),
+                metric(
+                    "gitnapped",
+                    gitnapped_percentage(current),
+                    gitnapped_percentage(previous),
+                    true,
+                    true,
+                ),
+                metric(
+                    "gitnapped hours",
+                    hours(current),
+                    hours(previous),
+                    false,
+                    true,
+                ),
+                metric(
+                    "active days",
+                    current.commits_by_date.len() as f64,
+                    previous.commits_by_date.len() as f64,
+                    false,
+                    false,
+                ),
+                metric(
+                    "lines changed",
+                    lines(current),
+                    lines(previous),
+                    false,
+                    false,
+                ),
+                metric(
+                    "health score",
+                    health_score(current, since, until).score as f64,
+                    health_score(previous, previous_since, previous_until).score as f64,
+                    false,
+                    false,
+                ),
+            ],
+        }
+    }
+}
+
+/// How many times something was done on GitHub, and how many of them outside working hours.
+#[derive(Debug, Default, Serialize)]
+pub struct ActivityCount {
+    /// Number of times in the period
+    pub total: usize,
+    /// Number of times outside working hours
+    pub out_of_hours: usize,
+}
+
+/// The pull requests and reviews of the GitHub user of the token over the
+/// period, with `--github-activity`.
+#[derive(Debug, Serialize)]
+pub struct ReviewActivity {
+    /// The login of the user
+    pub login: String,
+    /// Pull requests opened
+    pub pull_requests: ActivityCount,
+    /// Reviews submitted
+    pub reviews: ActivityCount,
+    /// Comments written in the reviews
+    pub review_comments: ActivityCount,
+    /// Percentage of the pull requests, reviews and comments made outside working hours
+    pub gitnapped_percentage: f64,
+}
+
+impl ReviewActivity {
+    /// Checks the pull requests, reviews and review comments of a user against
+    /// working hours, like commits.
+    ///
+    /// # Arguments
+    /// * `activity` - The activity read from GitHub
+    /// * `schedule` - The working hours, and the timezone they apply in
+    ///
+    /// # Returns
+    /// * `ReviewActivity` - The counts
+    pub fn of(activity: &GitHubActivity, schedule: &Schedule) -> ReviewActivity {
+        let count = |dates: &[DateTime<FixedOffset>]| ActivityCount {
+            total: dates.len(),
+            out_of_hours: dates
+                .iter()
+                .map(|date| schedule.timezone.convert(date))
+                .filter(|time| {
+                    let weekday = time.weekday().num_days_from_monday() as usize;
+                    !schedule.contains(weekday, time.hour(), time.minute())
+                })
+                .count(),
+        };
+        let pull_requests = count(&activity.pull_requests);
+        let reviews = count(&activity.reviews);
+        let review_comments = count(&activity.review_comments);
+        let all = [&pull_requests, &reviews, &review_comments];
+
+        ReviewActivity {
+            login: activity.login.clone(),
+            gitnapped_percentage: gitnapped_percentage(&RepoStats {
+                commit_count: all.iter().map(|count| count.total).sum(),
+                out_of_hours_commits: all.iter().map(|count| count.out_of_hours).sum(),
+                ..RepoStats::default()
+            }),
+            pull_requests,
+            reviews,
+            review_comments,
+        }
+    }
+}
+
+/// The time spent in editors on a day, next to the one estimated out of the commits.
+#[derive(Debug, Serialize)]
+pub struct EditorTimeDay {
+    /// The day (YYYY-MM-DD)
+    pub date: String,
+    /// Minutes spent in editors, as tracked by WakaTime
+    pub editor_minutes: usize,
+    /// Minutes of the work sessions of the commits
+    pub commit_minutes: usize,
+}
+
+/// The time spent in editors over the period, as tracked by WakaTime, next
+/// to the one estimated out of the commits, with `--wakatime`.
+#[derive(Debug, Serialize)]
+pub struct EditorTime {
+    /// Minutes spent in editors
+    pub editor_minutes: usize,
+    /// Minutes of the work sessions of the commits
+    pub commit_minutes: usize,
+    /// The commit minutes as a percentage of the editor minutes, None without editor time
+    pub coverage_percentage: Option<f64>,
+    /// Every day with editor time or commits
+    pub days: Vec<EditorTimeDay>,
+}
+
+impl EditorTime {
+    /// Puts the editor time of the days of the period next to the commit estimate.
+    ///
+    /// # Arguments
+    /// * `editor` - Minutes spent in editors per day, possibly beyond the period
+    /// * `commits` - Minutes of the commit sessions per day
+    /// * `since` - Start of the period
+    /// * `until` - End of the period
+    ///
+    /// # Returns
+    /// * `EditorTime` - The totals and the days
+    pub fn of(
+        editor: &BTreeMap<NaiveDate, usize>,
+        commits: &BTreeMap<NaiveDate, usize>,
+        since: &str,
+        until: &str,
+    ) -> EditorTime {
+        let day = |bound: &str| parse_range_bound(bound).map(|bound| bound.date());
+        let (first, last) = (day(since), day(until));
+        let in_period = |date: &&NaiveDate| {
+            first.is_none_or(|first| **date >= first) && last.is_none_or(|last| **date <= last)
+        };
+        let dates: BTreeSet<&NaiveDate> = editor
+            .keys()
+            .chain(commits.keys())
+            .filter(in_period)
+            .collect();
+
+        let days: Vec<EditorTimeDay> = dates
+            .into_iter()
+            .map(|date| EditorTimeDay {
+                date: date.format("%Y-%m-%d").to_string(),
+                editor_minutes: editor.get(date).copied().unwrap_or_default(),
+                commit_minutes: commits.get(date).copied().unwrap_or_default(),
+            })
+            .collect();
+        let editor_minutes: usize = days.iter().map(|day| day.editor_minutes).sum();
+        let commit_minutes: usize = days.iter().map(|day| day.commit_minutes).sum();
+        EditorTime {
+            editor_minutes,
+            commit_minutes,
+            coverage_percentage: (editor_minutes > 0)
+                .then(|| (commit_minutes as f64 / editor_minutes as f64 * 10000.0).round() / 100.0),
+            days,
+        }
+    }
+}
+
+/// The commits of the period referring to an issue tracker ticket, and the
+/// time estimated for it.
+#[derive(Debug, Serialize)]
+pub struct TicketTotal {
+    /// The ticket ID, e.g. `PROJ-123` or `#456`
+    pub ticket: String,
+    /// Commits referring to the ticket
+    pub commits: usize,
+    /// Minutes of the work sessions spent on the ticket
+    pub minutes: usize,
+    /// Projects with commits referring to the ticket
+    pub projects: Vec<String>,
+    /// Day of the first session with a commit referring to the ticket (YYYY-MM-DD)
+    pub first_day: String,
+    /// Day of the last session with a commit referring to the ticket (YYYY-MM-DD)
+    pub last_day: String,
+}
+
+/// Commits and estimated time per issue tracker ticket, with `--tickets`.
+#[derive(Debug, Serialize)]
+pub struct Tickets {
+    /// Every ticket referred to, the longest first
+    pub tickets: Vec<TicketTotal>,
+    /// Commits referring to no ticket
+    pub untracked_commits: usize,
+    /// Minutes of the work sessions spent on commits referring to no ticket
+    pub untracked_minutes: usize,
+}
+
+impl Tickets {
+    /// Sums the commits and time of every ticket over work sessions: the time
+    /// of a session is split evenly among its commits, and the time of a
+    /// commit among the tickets it refers to.
+    ///
+    /// # Arguments
+    /// * `sessions` - The work sessions of the period
+    /// * `patterns` - Patterns of the ticket IDs in commit subjects
+    ///
+    /// # Returns
+    /// * `Tickets` - The totals of every ticket and of the other commits
+    pub fn of(sessions: &[ProjectSession], patterns: &TicketPatterns) -> Tickets {
+        let mut totals: BTreeMap<String, (usize, f64, BTreeSet<String>, NaiveDate, NaiveDate)> =
+            BTreeMap::new();
+        let (mut untracked_commits, mut untracked_seconds) = (0, 0.0);
+        for session in sessions {
+            let day = session.start.with_timezone(&Local).date_naive();
+            let commit_seconds = session.seconds() as f64 / session.subjects.len().max(1) as f64;
+            for subject in &session.subjects {
+                let tickets = patterns.tickets(subject);
+                if tickets.is_empty() {
+                    untracked_commits += 1;
+                    untracked_seconds += commit_seconds;
+                    continue;
+                }
+                for ticket in &tickets {
+                    let (commits, seconds, projects, first, last) = totals
+                        .entry(ticket.clone())
+                        .or_insert_with(|| (0, 0.0, BTreeSet::new(), day, day));
+                    *commits += 1;
+                    *seconds += commit_seconds / tickets.len() as f64;
+                    projects.insert(session.project.clone());
+                    *first = (*first).min(day);
+                    *last = (*last).max(day);
+                }
+            }
+        }
+
+        let mut tickets: Vec<TicketTotal> = totals
+            .into_iter()
+            .map(
+                |(ticket, (commits, seconds, projects, first, last))| TicketTotal {
+                    ticket,
+                    commits,
+                    minutes: (seconds / 60.0).round() as usize,
+                    projects: projects.into_iter().collect(),
+                    first_day: first.format("%Y-%m-%d").to_string(),
+                    last_day: last.format("%Y-%m-%d").to_string(),
+                },
+            )
+            .collect();
+        tickets.sort_by(|a, b| b.minutes.cmp(&a.minutes).then(b.commits.cmp(&a.commits)));
+        Tickets {
+            tickets,
+            untracked_commits,
+            untracked_minutes: (untracked_seconds / 60.0).round() as usize,
+        }
+    }
+}
+
+/// The analyzed period compared with the last snapshot recorded by `gitnapped record`.
+#[derive(Debug, Serialize)]
+pub struct SnapshotComparison {
+    /// The day of the snapshot (YYYY-MM-DD)
+    pub date: String,
+    /// The compared totals
+    pub metrics: Vec<TrendMetric>,
+    /// Repositories with commits in the period but not in the snapshot
+    pub new_active_repos: Vec<String>,
+    /// Repositories with commits in the snapshot but not in the period
+    pub inactive_repos: Vec<String>,
+    /// Map of the projects found in both to their gitnapped percentage compared
+    pub projects: BTreeMap<String, TrendMetric>,
+}
+
+impl TrendMetric {
+    /// Builds a metric out of its two values.
+    ///
+    /// # Arguments
+    /// * `name` - Name of the metric, e.g. `commits`
+    /// * `current` - Value in the analyzed period
+    /// * `previous` - Value it is compared with
+    /// * `points` - Whether the metric is a percentage, compared in percentage points
+    /// * `lower_is_better` - Whether a decrease is an improvement
+    pub fn new(
+        name: &str,
+        current: f64,
+        previous: f64,
+        points: bool,
+        lower_is_better: bool,
+    ) -> TrendMetric {
+        TrendMetric {
+            name: name.to_string(),
+            current,
+            previous,
+            change_percent: (previous != 0.0)
+                .then(|| ((current - previous) / previous * 10000.0).round() / 100.0),
+            points,
+            lower_is_better,
+        }
+    }
+
+    /// Formats the change of the metric, e.g. `+12%`, `-5.0pp` or `new`.
+    pub fn change(&self) -> String {
+        if self.points {
+            format!("{:+.1}pp", self.current - self.previous)
+        } else {
+            match self.change_percent {
+                Some(percent) => format!("{:+.0}%", percent),
+                None if self.current == 0.0 => String::from("+0%"),
+                None => String::from("new"),
+            }
+        }
+    }
+
+    /// Arrow pointing in the direction of the change.
+    pub fn arrow(&self) -> &'static str {
+        match self.current.partial_cmp(&self.previous) {
+            Some(std::cmp::Ordering::Greater) => "↑",
+            Some(std::cmp::Ordering::Less) => "↓",
+            _ => "→",
+        }
+    }
+
+    /// Checks if the metric got better, None when it did not change.
+    pub fn improved(&self) -> Option<bool> {
+        if self.current == self.previous {
+            return None;
+        }
+        Some((self.current < self.previous) == self.lower_is_better)
+    }
+}
+
+/// A stretch of days without any commit, e.g. a vacation.
+#[derive(Debug, Serialize)]
+pub struct InactivityGap {
+    /// First day without commits (YYYY-MM-DD)
+    pub from: String,
+    /// Last day without commits (YYYY-MM-DD)
+    pub to: String,
+    /// Length of the gap in days
+    pub days: usize,
+}
+
+/// Totals across every analyzed repository.
+#[derive(Debug, Serialize)]
+pub struct TotalReport {
+    /// Number of repositories with commits in the period
+    pub active_repos: usize,
+    /// Percentage of commits made outside working hours
+    pub gitnapped_percentage: f64,
+    /// Day with the highest number of commits, if any
+    pub most_active_day: Option<MostActiveDay>,
+    /// Average first and last commit of the day, if any
+    pub typical_day: Option<TypicalDay>,
+    /// Time between consecutive commits across all repositories, if there are at least two
+    pub commit_interval: Option<CommitInterval>,
+    /// Streaks of days with and without commits
+    pub streaks: Streaks,
+    /// Work-health score of the period
+    pub health: HealthScore,
+    /// Days that ended after midnight and were followed by an early start
+    pub short_sleep_days: Vec<String>,
+    /// Stretches of days without commits at least as long as `--min-gap`
+    pub inactivity_gaps: Vec<InactivityGap>,
+    /// Average number of commits per day of the period
+    pub commits_per_day: f64,
+    /// Average number of commits per day, leaving out the inactivity gaps
+    pub commits_per_day_excluding_gaps: f64,
+    /// Aggregated stats for all repositories
+    pub stats: RepoStats,
+}
+
+/// The complete result of an analysis run, used by machine-readable outputs.
+#[derive(Debug, Serialize)]
+pub struct Report {
+    /// Start of the analyzed period
+    pub since: String,
+    /// End of the analyzed period
+    pub until: String,
+    /// Author filter in use, if any, with multiple patterns comma separated
+    pub author: Option<String>,
+    /// Working hours the commits were checked against
+    pub working_time: String,
+    /// Totals across all repositories
+    pub totals: TotalReport,
+    /// Per-repository statistics
+    pub repos: Vec<RepoReport>,
+    /// Per-category statistics
+    pub categories: Vec<CategoryReport>,
+    /// Per-project statistics
+    pub projects: Vec<ProjectReport>,
+    /// Comparison with the previous period, with `--trend`
+    pub trend: Option<Trend>,
+    /// Comparison with the last snapshot recorded by `gitnapped record`, with `--diff-last`
+    pub last_snapshot: Option<SnapshotComparison>,
+    /// Pull requests and reviews on GitHub, with `--github-activity`
+    pub review_activity: Option<ReviewActivity>,
+    /// Time spent in editors next to the commit estimate, with `--wakatime`
+    pub editor_time: Option<EditorTime>,
+    /// Commits and estimated time per issue tracker ticket, with `--tickets`
+    pub tickets: Option<Tickets>,
+}
+
+/// Computes the percentage of out-of-hours commits, rounded to two decimals.
+///
+/// # Arguments
+/// * `stats` - The statistics to compute the percentage for
+///
+/// # Returns
+/// * `f64` - The gitnapped percentage, 0 when there are no commits
+pub fn gitnapped_percentage(stats: &RepoStats) -> f64 {
+    if stats.commit_count == 0 {
+        return 0.0;
+    }
+    let percentage = stats.out_of_hours_commits as f64 / stats.commit_count as f64 * 100.0;
+    (percentage * 100.0).round() / 100.0
+}
+
+/// Builds the report entry for a single repository.
+///
+/// # Arguments
+/// * `path` - Path to the repository
+/// * `stats` - Statistics of the repository
+/// * `repo_infos` - Repository information parsed from the config, used for names and groups
+///
+/// # Returns
+/// * `RepoReport` - The report entry
+pub fn repo_report(path: &str, stats: &RepoStats, repo_infos: &[RepoInfo]) -> RepoReport {
+    let info = repo_infos.iter().find(|info| info.key() == path);
+
+    RepoReport {
+        path: path.to_string(),
+        name: info
+            .map(|info| info.vanity_name.clone())
+            .unwrap_or_else(|| path.to_string()),
+        group: info.and_then(|info| info.group.clone()),
+        tags: info.map(|info| info.tags.clone()).unwrap_or_default(),
+        gitnapped_percentage: gitnapped_percentage(stats),
+        commit_interval: CommitInterval::of(stats),
+        stats: stats.clone(),
+    }
+}
+
+/// Assembles a full report out of the analysis results.
+///
+/// # Arguments
+/// * `since` - Start of the analyzed period
+/// * `until` - End of the analyzed period
+/// * `authors` - Author patterns in use, empty for all authors
+/// * `working_time` - Working hours the commits were checked against
+/// * `total_stats` - Aggregated stats for all repositories
+/// * `all_repo_stats` - Statistics of every analyzed repository
+/// * `repo_infos` - Repository information parsed from the config
+/// * `categories` - Category statistics
+/// * `projects` - Project statistics
+/// * `short_sleep_before` - Hour before which a start after a late night counts as short sleep
+/// * `min_gap_days` - Minimum length in days of an inactivity gap
+///
+/// # Returns
+/// * `Report` - The serializable report
+#[allow(clippy::too_many_arguments)]
+pub fn build_report(
+    since: &str,
+    until: &str,
+    authors: &[String],
+    working_time: &str,
+    total_stats: &RepoStats,
+    all_repo_stats: &[(String, RepoStats)],
+    repo_infos: &[RepoInfo],
+    categories: &[CategoryStats],
+    projects: &[ProjectStats],
+    short_sleep_before: u32,
+    min_gap_days: usize,
+) -> Report {
+    let repos = all_repo_stats
+        .iter()
+        .map(|(path, stats)| repo_report(path, stats, repo_infos))
+        .collect();
+
+    let categories = categories
+        .iter()
+        .map(|category| CategoryReport {
+            name: category.name.clone(),
+            active_repos: category
+                .repos
+                .iter()
+                .filter(|(_, stats)| is_repo_active(stats))
+                .count(),
+            gitnapped_percentage: gitnapped_percentage(&category.total),
+            total: category.total.clone(),
+            repos: category
+                .repos
+                .iter()
+                .map(|(path, stats)| repo_report(path, stats, repo_infos))
+                .collect(),
+        })
+        .collect();
+
+    let projects = projects
+        .iter()
+        .map(|project| ProjectReport {
+            name: project.name.clone(),
+            group: project.group.clone(),
+            repos: project.repos.clone(),
+            gitnapped_percentage: gitnapped_percentage(&project.stats),
+            stats: project.stats.clone(),
+        })
+        .collect();
+
+    let inactivity_gaps: Vec<InactivityGap> =
+        inactivity_gaps(&total_stats.commits_by_date, since, until, min_gap_days)
+            .into_iter()
+            .map(|(start, end)| InactivityGap {
+                from: start.format("%Y-%m-%d").to_string(),
+                to: end.format("%Y-%m-%d").to_string(),
+                days: (end - start).num_days() as usize + 1,
+            })
+            .collect();
+    let gap_days = inactivity_gaps.iter().map(|gap| gap.days).sum();
+
+    Report {
+        since: since.to_string(),
+        until: until.to_string(),
+        author: (!authors.is_empty()).then(|| authors.join(", ")),
+        working_time: working_time.to_string(),
+        totals: TotalReport {
+            active_repos: all_repo_stats
+                .iter()
+                .filter(|(_, stats)| is_repo_active(stats))
+                .count(),
+            gitnapped_percentage: gitnapped_percentage(total_stats),
+            most_active_day: get_max_commit_day(&total_stats.commits_by_date)
+                .map(|(date, commits)| MostActiveDay { date, commits }),
+            typical_day: typical_day(total_stats).map(|(first_commit, last_commit)| TypicalDay {
+                first_commit,
+                last_commit,
+            }),
+            commit_interval: CommitInterval::of(total_stats),
+            streaks: {
+                let (longest_streak, current_streak, longest_gap) =
+                    commit_streaks(&total_stats.commits_by_date, since, until);
+                Streaks {
+                    longest_streak,
+                    current_streak,
+                    longest_gap,
+                }
+            },
+            health: health_score(total_stats, since, until),
+            short_sleep_days: short_sleep_days(total_stats, short_sleep_before),
+            commits_per_day: commits_per_day(total_stats.commit_count, since, until, 0),
+            commits_per_day_excluding_gaps: commits_per_day(
+                total_stats.commit_count,
+                since,
+                until,
+                gap_days,
+            ),
+            inactivity_gaps,
+            stats: total_stats.clone(),
+        },
+        repos,
+        categories,
+        projects,
+        trend: None,
+        last_snapshot: None,
+        review_activity: None,
+        editor_time: None,
+        tickets: None,
+    }
+}
@@ -0,0 +1,534 @@
+use chrono::{
+    DateTime, Datelike, Duration, FixedOffset, Local, NaiveDateTime, Timelike, Utc, Weekday,
+};
+use chrono_tz::Tz;
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fmt;
+
+/// A span of working time within a day, in minutes since midnight.
+///
+/// When `end` is before `start` the window spans midnight (e.g. 22:00-06:00).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TimeWindow {
+    /// Start of the window, in minutes since midnight
+    pub start: u32,
+    /// End of the window (inclusive), in minutes since midnight
+    pub end: u32,
+}
+
+impl TimeWindow {
+    /// Builds a window from start and end hours and minutes.
+    pub fn new(start_hour: u32, start_min: u32, end_hour: u32, end_min: u32) -> TimeWindow {
+        TimeWindow {
+            start: start_hour * 60 + start_min,
+            end: end_hour * 60 + end_min,
+        }
+    }
+
+    /// Checks if the window spans midnight, its end falling on the next day.
+    pub fn is_overnight(&self) -> bool {
+        self.end < self.start
+    }
+
+    /// Checks if a given time of day falls inside the window.
+    pub fn contains(&self, hour: u32, minute: u32) -> bool {
+        let time = hour * 60 + minute;
+
+        // Overnight shift: in hours from the start until midnight and from midnight until the end
+        if self.end < self.start {
+            return time >= self.start || time <= self.end;
+        }
+
+        time >= self.start && time <= self.end
+    }
+}
+
+impl fmt::Display for TimeWindow {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{:02}:{:02}-{:02}:{:02}",
+            self.start / 60,
+            self.start % 60,
+            self.end / 60,
+            self.end % 60
+        )
+    }
+}
+
+/// Working hours as configured by the user: one or more windows per day.
+///
+/// Commits outside every window count as gitnapped, so a lunch break or a
+/// split shift is expressed as two windows (e.g. `09:00-12:30,14:00-18:00`).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct WorkingHours {
+    /// The windows of working time
+    pub windows: Vec<TimeWindow>,
+}
+
+impl WorkingHours {
+    /// Parses a comma separated list of working time windows, each in 24-hour
+    /// (HH:MM-HH:MM) or 12-hour (HAM-PM) format.
+    ///
+    /// # Returns
+    /// * `Option<WorkingHours>` - The parsed working hours, None if any window is invalid
+    pub fn parse(spec: &str) -> Option<WorkingHours> {
+        let windows = spec
+            .split(',')
+            .map(|window| {
+                parse_working_time(window.trim()).map(
+                    |(start_hour, start_min, end_hour, end_min)| {
+                        TimeWindow::new(start_hour, start_min, end_hour, end_min)
+                    },
+                )
+            })
+            .collect::<Option<Vec<TimeWindow>>>()?;
+
+        if windows.is_empty() {
+            return None;
+        }
+        Some(WorkingHours { windows })
+    }
+
+    /// Checks if a given time of day falls inside any of the working windows.
+    pub fn contains(&self, hour: u32, minute: u32) -> bool {
+        self.windows
+            .iter()
+            .any(|window| window.contains(hour, minute))
+    }
+}
+
+impl fmt::Display for WorkingHours {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.windows.is_empty() {
+            return write!(f, "off");
+        }
+        let windows: Vec<String> = self.windows.iter().map(|w| w.to_string()).collect();
+        write!(f, "{}", windows.join(","))
+    }
+}
+
+/// Timezone the commit timestamps are evaluated in, as chosen by `--commit-timezone`
+/// or `--timezone`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum CommitTimezone {
+    /// The timezone of the machine running the analysis
+    Local,
+    /// The offset recorded in the commit, i.e. where the author was
+    #[default]
+    Author,
+    /// Coordinated Universal Time
+    Utc,
+    /// A named IANA timezone, e.g. `Europe/Rome`
+    Named(Tz),
+}
+
+impl CommitTimezone {
+    /// Names accepted on the command line.
+    pub const NAMES: [&'static str; 3] = ["local", "author", "utc"];
+
+    /// Looks up a named IANA timezone such as `Europe/Rome`.
+    ///
+    /// # Returns
+    /// * `Result<CommitTimezone, String>` - The timezone, or an error for unknown names
+    pub fn named(name: &str) -> Result<CommitTimezone, String> {
+        name.parse::<Tz>().map(CommitTimezone::Named).map_err(|_| {
+            format!(
+                "Unknown timezone '{}', expected a name like Europe/Rome",
+                name
+            )
+        })
+    }
+
+    /// Looks up a commit timezone by its command line name.
+    pub fn from_name(name: &str) -> Option<CommitTimezone> {
+        match name {
+            "local" => Some(CommitTimezone::Local),
+            "author" => Some(CommitTimezone::Author),
+            "utc" => Some(CommitTimezone::Utc),
+            _ => None,
+        }
+    }
+
+    /// Converts a commit timestamp into the wall clock time of this timezone.
+    pub fn convert(&self, timestamp: &DateTime<FixedOffset>) -> NaiveDateTime {
+        match self {
+            CommitTimezone::Local => timestamp.with_timezone(&Local).naive_local(),
+            CommitTimezone::Author => timestamp.naive_local(),
+            CommitTimezone::Utc => timestamp.with_timezone(&Utc).naive_utc(),
+            CommitTimezone::Named(tz) => timestamp.with_timezone(tz).naive_local(),
+        }
+    }
+}
+
+/// Short names of the weekdays, Monday first.
+const WEEKDAY_NAMES: [&str; 7] = ["Mon", "Tue", "Wed", "Thu", "Fri", "Sat", "Sun"];
+
+/// Workdays used when none are configured: Monday to Friday.
+pub const DEFAULT_WORKDAYS: [bool; 7] = [true, true, true, true, true, false, false];
+
+/// Working hours of a day off: no window at all.
+static DAY_OFF: WorkingHours = WorkingHours {
+    windows: Vec::new(),
+};
+
+/// Working hours for every day of the week: a default plus per-weekday overrides.
+///
+/// A weekday without any window (e.g. `wednesday: off`) is a day off, so every
+/// commit made on it counts as gitnapped. Non-workdays are days off unless
+/// they have an override of their own.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Schedule {
+    /// Working hours used on workdays without an override
+    pub default: WorkingHours,
+    /// Per-weekday overrides, Monday first
+    pub weekdays: [Option<WorkingHours>; 7],
+    /// Days of the week that are worked, Monday first
+    pub workdays: [bool; 7],
+    /// Timezone the commits are evaluated in
+    pub timezone: CommitTimezone,
+}
+
+impl Schedule {
+    /// Builds a schedule using the same working hours on every workday from Monday to Friday.
+    pub fn uniform(default: WorkingHours) -> Schedule {
+        Schedule {
+            default,
+            weekdays: Default::default(),
+            workdays: DEFAULT_WORKDAYS,
+            timezone: CommitTimezone::default(),
+        }
+    }
+
+    /// Builds a schedule out of the default working hours and the per-weekday
+    /// overrides of the config file.
+    ///
+    /// # Arguments
+    /// * `default` - Working hours for weekdays without an override
+    /// * `overrides` - Map of weekday names (`friday`, `fri`, ...) to working time,
+    ///   `off` marking a day off
+    ///
+    /// # Returns
+    /// * `Result<Schedule, String>` - The schedule, or an error naming the invalid entry
+    pub fn with_overrides(
+        default: WorkingHours,
+        overrides: &HashMap<String, WorkingTimeSpec>,
+    ) -> Result<Schedule, String> {
+        let mut schedule = Schedule::uniform(default);
+
+        for (day, spec) in overrides {
+            let weekday: Weekday = day
+                .trim()
+                .parse()
+                .map_err(|_| format!("Unknown weekday '{}' in working_time_by_day", day))?;
+            let spec = spec.as_spec();
+            let hours = if is_day_off(&spec) {
+                WorkingHours {
+                    windows: Vec::new(),
+                }
+            } else {
+                WorkingHours::parse(&spec).ok_or_else(|| {
+                    format!(
+                        "Invalid working time '{}' for {} in working_time_by_day",
+                        spec, day
+                    )
+                })?
+            };
+            schedule.weekdays[weekday.num_days_from_monday() as usize] = Some(hours);
+        }
+
+        Ok(schedule)
+    }
+
+    /// Replaces the workdays of the schedule with the given weekday names.
+    ///
+    /// # Arguments
+    /// * `days` - Weekday names (`monday`, `mon`, ...) of the days that are worked
+    ///
+    /// # Returns
+    /// * `Result<(), String>` - An error naming the invalid weekday, if any
+    pub fn set_workdays(&mut self, days: &[String]) -> Result<(), String> {
+        let mut workdays = [false; 7];
+        for day in days {
+            let weekday: Weekday = day
+                .trim()
+                .parse()
+                .map_err(|_| format!("Unknown weekday '{}' in workdays", day))?;
+            workdays[weekday.num_days_from_monday() as usize] = true;
+        }
+        self.workdays = workdays;
+        Ok(())
+    }
+
+    /// Returns the working hours of a weekday (0 = Monday).
+    pub fn hours_for(&self, weekday: usize) -> &WorkingHours {
+        match self.weekdays.get(weekday) {
+            Some(Some(hours)) => hours,
+            _ if !self.workdays.get(weekday).copied().unwrap_or(true) => &DAY_OFF,
+            _ => &self.default,
+        }
+    }
+
+    /// Counts the minutes between two points in time that fall outside the working hours.
+    ///
+    /// # Arguments
+    /// * `start` - Start of the span
+    /// * `end` - End of the span (exclusive)
+    ///
+    /// # Returns
+    /// * `usize` - Number of minutes outside the working hours
+    pub fn minutes_outside(&self, start: NaiveDateTime, end: NaiveDateTime) -> usize {
+        let mut minutes = 0;
+        let mut time = start;
+        while time < end {
+            let weekday = time.weekday().num_days_from_monday() as usize;
+            if !self.contains(weekday, time.hour(), time.minute()) {
+                minutes += 1;
+            }
+            time += Duration::minutes(1);
+        }
+        minutes
+    }
+
+    /// Checks if a given time on a weekday (0 = Monday) falls inside the working hours.
+    ///
+    /// The hours after midnight of an overnight window belong to the shift
+    /// started the day before, so they follow the working hours of that day:
+    ///
+    /// ```
+    /// use gitnapped::schedule::{Schedule, WorkingHours};
+    ///
+    /// let nights = Schedule::uniform(WorkingHours::parse("22:00-06:00").unwrap());
+    /// // Saturday 03:00 ends the shift of Friday night
+    /// assert!(nights.contains(5, 3, 0));
+    /// // Monday 03:00 has no shift started on Sunday
+    /// assert!(!nights.contains(0, 3, 0));
+    /// // Monday 23:00 starts the shift of Monday night
+    /// assert!(nights.contains(0, 23, 0));
+    /// ```
+    pub fn contains(&self, weekday: usize, hour: u32, minute: u32) -> bool {
+        let time = hour * 60 + minute;
+        let started_today = self.hours_for(weekday).windows.iter().any(|window| {
+            if window.is_overnight() {
+                time >= window.start
+            } else {
+                window.contains(hour, minute)
+            }
+        });
+        let started_yesterday = self
+            .hours_for((weekday + 6) % 7)
+            .windows
+            .iter()
+            .any(|window| window.is_overnight() && time <= window.end);
+        started_today || started_yesterday
+    }
+}
+
+impl fmt::Display for Schedule {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.default)?;
+
+        if self.workdays != DEFAULT_WORKDAYS {
+            let workdays: Vec<&str> = self
+                .workdays
+                .iter()
+                .zip(WEEKDAY_NAMES)
+                .filter(|(worked, _)| **worked)
+                .map(|(_, name)| name)
+                .collect();
+            if workdays.len() == 7 {
+                write!(f, " every day")?;
+            } else {
+                write!(f, " on {}", workdays.join(","))?;
+            }
+        }
+
+        let overrides: Vec<String> = self
+            .weekdays
+            .iter()
+            .zip(WEEKDAY_NAMES)
+            .filter_map(|(hours, name)| hours.as_ref().map(|hours| format!("{} {}", name, hours)))
+            .collect();
+        if !overrides.is_empty() {
+            write!(f, " ({})", overrides.join(", "))?;
+        }
+
+        match self.timezone {
+            CommitTimezone::Local => write!(f, " local time"),
+            CommitTimezone::Author => Ok(()),
+            CommitTimezone::Utc => write!(f, " UTC"),
+            CommitTimezone::Named(tz) => write!(f, " {}", tz),
+        }
+    }
+}
+
+/// A team member whose commits are evaluated against their own schedule.
+#[derive(Debug, Clone)]
+pub struct TeamMember {
+    /// Case-insensitive pattern matched against `Name <email>` of the commit author
+    pub pattern: Regex,
+    /// Working hours and timezone of the member
+    pub schedule: Schedule,
+}
+
+/// The schedules every commit is evaluated against: a global schedule, the
+/// schedules of repositories with overridden working hours and the schedules
+/// of team members.
+#[derive(Debug, Clone)]
+pub struct Schedules {
+    /// Schedule of repositories without an override
+    pub global: Schedule,
+    /// Map of repository paths to their own schedule
+    pub repos: HashMap<String, Schedule>,
+    /// Team members, the first one matching an author wins
+    pub team: Vec<TeamMember>,
+}
+
+impl Schedules {
+    /// Returns the schedule a repository is evaluated against.
+    pub fn for_repo(&self, path: &str) -> &Schedule {
+        self.repos.get(path).unwrap_or(&self.global)
+    }
+
+    /// Returns the schedule a commit is evaluated against: the one of its
+    /// author when they are a team member, otherwise the one of its repository.
+    ///
+    /// # Arguments
+    /// * `path` - Path of the repository
+    /// * `author` - Author of the commit, as `Name <email>`
+    pub fn for_commit(&self, path: &str, author: &str) -> &Schedule {
+        self.team
+            .iter()
+            .find(|member| member.pattern.is_match(author))
+            .map(|member| &member.schedule)
+            .unwrap_or_else(|| self.for_repo(path))
+    }
+}
+
+/// Checks if a working time spec marks a day off (`off`, `none` or an empty list).
+fn is_day_off(spec: &str) -> bool {
+    matches!(spec.trim().to_lowercase().as_str(), "" | "off" | "none")
+}
+
+/// Working time as written in the config file: either a single string with
+/// comma separated windows or a list of windows.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[serde(untagged)]
+pub enum WorkingTimeSpec {
+    /// `working_time: "09:00-12:30,14:00-18:00"`
+    Text(String),
+    /// `working_time: ["09:00-12:30", "14:00-18:00"]`
+    List(Vec<String>),
+}
+
+impl WorkingTimeSpec {
+    /// Returns the spec in the comma separated form accepted by `WorkingHours::parse`.
+    pub fn as_spec(&self) -> String {
+        match self {
+            WorkingTimeSpec::Text(text) => text.clone(),
+            WorkingTimeSpec::List(windows) => windows.join(","),
+        }
+    }
+}
+
+/// Working hours of a config that sets none, as `--working-time` defaults to.
+pub const DEFAULT_WORKING_TIME: &str = "09:00-17:00";
+
+/// Parses a single working time window in either 24-hour (HH:MM-HH:MM) or 12-hour (HAM-PM) format.
+/// Returns a tuple of (start_hour, start_minute, end_hour, end_minute) in 24-hour format.
+/// The end may be before the start for working hours that span midnight (e.g. 22:00-06:00).
+///
+/// # Arguments
+/// * `time_str` - The working time string to parse
+///
+/// # Returns
+/// * `Option<(u32, u32, u32, u32)>` - The parsed times if successful, None if invalid
+///
+/// # Examples
+/// ```
+/// # use gitnapped::schedule::parse_working_time;
+/// // 24-hour format
+/// let times = parse_working_time("09:00-17:00");
+/// assert_eq!(times, Some((9, 0, 17, 0)));
+///
+/// // 12-hour format
+/// let times = parse_working_time("9AM-5PM");
+/// assert_eq!(times, Some((9, 0, 17, 0)));
+/// ```
+pub fn parse_working_time(time_str: &str) -> Option<(u32, u32, u32, u32)> {
+    // Try 24-hour format first (HH:MM-HH:MM)
+    if let Some((start, end)) = time_str.split_once('-') {
+        let start = start.trim();
+        let end = end.trim();
+
+        // Parse 24-hour format
+        if let (Some((start_hour, start_min)), Some((end_hour, end_min))) =
+            (parse_24h_time(start), parse_24h_time(end))
+        {
+            return Some((start_hour, start_min, end_hour, end_min));
+        }
+
+        // Try 12-hour format
+        if let (Some((start_hour, start_min)), Some((end_hour, end_min))) =
+            (parse_12h_time(start), parse_12h_time(end))
+        {
+            return Some((start_hour, start_min, end_hour, end_min));
+        }
+    }
+
+    None
+}
+
+/// Parses a time string in 24-hour format (HH:MM)
+fn parse_24h_time(time: &str) -> Option<(u32, u32)> {
+    let parts: Vec<&str> = time.split(':').collect();
+    if parts.len() != 2 {
+        return None;
+    }
+
+    let hour: u32 = parts[0].trim().parse().ok()?;
+    let minute: u32 = parts[1].trim().parse().ok()?;
+
+    if hour > 23 || minute > 59 {
+        return None;
+    }
+
+    Some((hour, minute))
+}
+
+/// Parses a time string in 12-hour format (HAM or H:MMAM)
+fn parse_12h_time(time: &str) -> Option<(u32, u32)> {
+    let time = time.to_uppercase();
+    let is_pm = time.contains("PM");
+    let time = time.replace("AM", "").replace("PM", "").trim().to_string();
+
+    let parts: Vec<&str> = time.split(':').collect();
+    let hour: u32 = parts[0].trim().parse().ok()?;
+    let minute: u32 = if parts.len() > 1 {
+        parts[1].trim().parse().ok()?
+    } else {
+        0
+    };
+
+    if hour > 12 || minute > 59 {
+        return None;
+    }
+
+    let hour_24 = if is_pm {
+        if hour == 12 {
+            12
+        } else {
+            hour + 12
+        }
+    } else {
+        if hour == 12 {
+            0
+        } else {
+            hour
+        }
+    };
+
+    Some((hour_24, minute))
+}
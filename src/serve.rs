@@ -0,0 +1,184 @@
+use crate::analyzer::Analyzer;
+use crate::models::Config;
+use crate::snapshots::{read_snapshots, snapshot_periods};
+use crate::utils::{debug, log};
+use askama::Template;
+use chrono::{Days, Local};
+use colored::Colorize;
+use serde_json::json;
+use std::io::{BufRead, BufReader, Write};
+use std::net::{TcpListener, TcpStream};
+
+/// Days analyzed by the dashboard unless the page asks for another count.
+const DEFAULT_DAYS: u64 = 30;
+
+/// Most days the dashboard analyzes at once.
+const MAX_DAYS: u64 = 366;
+
+/// The dashboard page, rendered from `templates/dashboard.html`. It reads its
+/// data from the API of the server, so it is the same for every request.
+#[derive(Template)]
+#[template(path = "dashboard.html")]
+struct DashboardPage<'a> {
+    version: &'a str,
+}
+
+/// A response of the server.
+struct Response {
+    status: &'static str,
+    content_type: &'static str,
+    body: String,
+}
+
+impl Response {
+    fn ok(content_type: &'static str, body: String) -> Response {
+        Response {
+            status: "200 OK",
+            content_type,
+            body,
+        }
+    }
+
+    fn error(status: &'static str, message: &str) -> Response {
+        Response {
+            status,
+            content_type: "application/json",
+            body: json!({ "error": message }).to_string(),
+        }
+    }
+}
+
+/// Serves the web dashboard and the JSON API it reads until the process is
+/// stopped. Requests are answered one at a time, every one analyzing the
+/// repositories again, so the page always shows the latest commits.
+///
+/// # Arguments
+/// * `address` - The address to listen on, e.g. `127.0.0.1:8080`
+/// * `config` - Reads the config for every request, so edits apply without a restart
+///
+/// # Returns
+/// * `Result<(), String>` - An error if the address cannot be listened on
+pub fn serve(address: &str, config: &dyn Fn() -> Result<Config, String>) -> Result<(), String> {
+    let listener = TcpListener::bind(address)
+        .map_err(|err| format!("Cannot listen on {}: {}", address, err))?;
+    log(&format!(
+        "{} {}",
+        "Dashboard served at".bright_yellow(),
+        format!("http://{}", address).bright_cyan()
+    ));
+
+    for stream in listener.incoming() {
+        let result = stream
+            .map_err(|err| err.to_string())
+            .and_then(|stream| handle(stream, config));
+        if let Err(err) = result {
+            debug(&format!("Failed to answer a request: {}", err));
+        }
+    }
+    Ok(())
+}
+
+/// Reads a request and writes its response.
+fn handle(
+    mut stream: TcpStream,
+    config: &dyn Fn() -> Result<Config, String>,
+) -> Result<(), String> {
+    let mut reader = BufReader::new(&stream);
+    let mut request_line = String::new();
+    reader
+        .read_line(&mut request_line)
+        .map_err(|err| err.to_string())?;
+    // The headers tell nothing the dashboard needs
+    let mut header = String::new();
+    while reader
+        .read_line(&mut header)
+        .map_err(|err| err.to_string())?
+        > 2
+    {
+        header.clear();
+    }
+
+    let mut parts = request_line.split_whitespace();
+    let (method, target) = (
+        parts.next().unwrap_or_default(),
+        parts.next().unwrap_or("/"),
+    );
+    debug(&format!("Answering {} {}", method, target));
+    let response = if method != "GET" {
+        Response::error("405 Method Not Allowed", "Only GET requests are answered")
+    } else {
+        route(target, config)
+    };
+
+    write!(
+        stream,
+        "HTTP/1.1 {}\r\nContent-Type: {}; charset=utf-8\r\nContent-Length: {}\r\nCache-Control: no-store\r\nConnection: close\r\n\r\n{}",
+        response.status,
+        response.content_type,
+        response.body.len(),
+        response.body
+    )
+    .map_err(|err| err.to_string())
+}
+
+/// Answers a GET request:
+/// * `/` - The dashboard page
+/// * `/api/report?days=30` - The report of the last days, as printed by `--json`
+/// * `/api/trend?by=week` - The recorded history by week or month, as plotted by `gitnapped trend`
+fn route(target: &str, config: &dyn Fn() -> Result<Config, String>) -> Response {
+    let (path, query) = target.split_once('?').unwrap_or((target, ""));
+    let parameter = |name: &str| {
+        query
+            .split('&')
+            .filter_map(|pair| pair.split_once('='))
+            .find(|(key, _)| *key == name)
+            .map(|(_, value)| value)
+    };
+
+    match path {
+        "/" => match (DashboardPage {
+            version: env!("CARGO_PKG_VERSION"),
+        })
+        .render()
+        {
+            Ok(page) => Response::ok("text/html", page),
+            Err(err) => Response::error("500 Internal Server Error", &err.to_string()),
+        },
+        "/api/report" => {
+            let days = parameter("days")
+                .and_then(|days| days.parse::<u64>().ok())
+                .unwrap_or(DEFAULT_DAYS)
+                .clamp(1, MAX_DAYS);
+            let today = Local::now().date_naive();
+            let first_day = today - Days::new(days - 1);
+            let report = config().and_then(|config| {
+                // Repositories without new commits are not read again
+                Analyzer::new(config).cached().run(first_day..=today)
+            });
+            match report.and_then(|report| {
+                serde_json::to_string(&report)
+                    .map_err(|err| format!("Failed to serialize report to JSON: {}", err))
+            }) {
+                Ok(report) => Response::ok("application/json", report),
+                Err(err) => Response::error("500 Internal Server Error", &err),
+            }
+        }
+        "/api/trend" => {
+            let monthly = parameter("by") == Some("month");
+            let periods: Vec<serde_json::Value> = snapshot_periods(&read_snapshots(), monthly)
+                .iter()
+                .map(|period| {
+                    json!({
+                        "label": period.label,
+                        "days": period.days,
+                        "commits": period.commits,
+                        "out_of_hours_commits": period.out_of_hours_commits,
+                        "gitnapped_percentage": period.gitnapped_percentage(),
+                    })
+                })
+                .collect();
+            Response::ok("application/json", json!(periods).to_string())
+        }
+        _ => Response::error("404 Not Found", "No such page"),
+    }
+}
@@ -0,0 +1,254 @@
+use crate::models::RepoStats;
+use crate::report::{gitnapped_percentage, Report, SnapshotComparison, TrendMetric};
+use crate::utils::{debug, is_repo_active, user_dir};
+use chrono::{Local, NaiveDate};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+use std::path::PathBuf;
+
+/// The aggregated statistics of a day, as recorded by `gitnapped record`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Snapshot {
+    /// The recorded day (YYYY-MM-DD)
+    pub date: String,
+    /// When the snapshot was recorded, ISO 8601
+    pub recorded_at: String,
+    /// Number of commits of the day
+    pub commits: usize,
+    /// Number of commits made outside working hours
+    pub out_of_hours_commits: usize,
+    /// Percentage of commits made outside working hours
+    pub gitnapped_percentage: f64,
+    /// Estimated minutes worked outside working hours
+    pub out_of_hours_minutes: usize,
+    /// Names of the repositories with commits on that day
+    pub active_repos: Vec<String>,
+    /// Statistics of every project of the config
+    pub projects: Vec<ProjectSnapshot>,
+}
+
+/// The statistics of a project in a snapshot.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ProjectSnapshot {
+    /// Name of the project
+    pub name: String,
+    /// Number of commits
+    pub commits: usize,
+    /// Number of commits made outside working hours
+    pub out_of_hours_commits: usize,
+    /// Percentage of commits made outside working hours
+    pub gitnapped_percentage: f64,
+}
+
+impl Snapshot {
+    /// Builds the snapshot of a day out of the report analyzing it.
+    pub fn of(date: NaiveDate, report: &Report) -> Snapshot {
+        let stats = &report.totals.stats;
+        Snapshot {
+            date: date.format("%Y-%m-%d").to_string(),
+            recorded_at: Local::now().to_rfc3339(),
+            commits: stats.commit_count,
+            out_of_hours_commits: stats.out_of_hours_commits,
+            gitnapped_percentage: report.totals.gitnapped_percentage,
+            out_of_hours_minutes: stats.out_of_hours_minutes,
+            active_repos: report
+                .repos
+                .iter()
+                .filter(|repo| is_repo_active(&repo.stats))
+                .map(|repo| repo.name.clone())
+                .collect(),
+            projects: report
+                .projects
+                .iter()
+                .map(|project| ProjectSnapshot {
+                    name: project.name.clone(),
+                    commits: project.stats.commit_count,
+                    out_of_hours_commits: project.stats.out_of_hours_commits,
+                    gitnapped_percentage: project.gitnapped_percentage,
+                })
+                .collect(),
+        }
+    }
+}
+
+/// Compares the snapshot of the analyzed period with an earlier one: the
+/// totals, which repositories started or stopped having commits, and the
+/// gitnapped percentage of the projects found in both.
+///
+/// # Arguments
+/// * `current` - The snapshot of the analyzed period
+/// * `last` - The snapshot it is compared with, usually the last recorded one
+///
+/// # Returns
+/// * `SnapshotComparison` - The changes
+pub fn compare_snapshots(current: &Snapshot, last: &Snapshot) -> SnapshotComparison {
+    let hours = |minutes: usize| (minutes as f64 / 6.0).round() / 10.0;
+    let projects: BTreeMap<String, TrendMetric> = current
+        .projects
+        .iter()
+        .filter_map(|project| {
+            let previous = last.projects.iter().find(|p| p.name == project.name)?;
+            Some((
+                project.name.clone(),
+                TrendMetric::new(
+                    "gitnapped",
+                    project.gitnapped_percentage,
+                    previous.gitnapped_percentage,
+                    true,
+                    true,
+                ),
+            ))
+        })
+        .collect();
+
+    SnapshotComparison {
+        date: last.date.clone(),
+        metrics: vec![
+            TrendMetric::new(
+                "commits",
+                current.commits as f64,
+                last.commits as f64,
+                false,
+                false,
+            ),
+            TrendMetric::new(
+                "gitnapped",
+                current.gitnapped_percentage,
+                last.gitnapped_percentage,
+                true,
+                true,
+            ),
+            TrendMetric::new(
+                "gitnapped hours",
+                hours(current.out_of_hours_minutes),
+                hours(last.out_of_hours_minutes),
+                false,
+                true,
+            ),
+        ],
+        new_active_repos: current
+            .active_repos
+            .iter()
+            .filter(|repo| !last.active_repos.contains(repo))
+            .cloned()
+            .collect(),
+        inactive_repos: last
+            .active_repos
+            .iter()
+            .filter(|repo| !current.active_repos.contains(repo))
+            .cloned()
+            .collect(),
+        projects,
+    }
+}
+
+/// The snapshots of a week or a month, summed.
+#[derive(Debug, Default)]
+pub struct SnapshotPeriod {
+    /// The week (`2025-W14`) or the month (`2025-04`)
+    pub label: String,
+    /// Number of recorded days
+    pub days: usize,
+    /// Number of commits
+    pub commits: usize,
+    /// Number of commits made outside working hours
+    pub out_of_hours_commits: usize,
+}
+
+impl SnapshotPeriod {
+    /// Computes the percentage of out-of-hours commits, as for a report.
+    pub fn gitnapped_percentage(&self) -> f64 {
+        gitnapped_percentage(&RepoStats {
+            commit_count: self.commits,
+            out_of_hours_commits: self.out_of_hours_commits,
+            ..RepoStats::default()
+        })
+    }
+}
+
+/// Returns the path of the snapshot history,
+/// `$XDG_STATE_HOME/gitnapped/snapshots.jsonl` (`~/.local/state/gitnapped/snapshots.jsonl`),
+/// one snapshot per line.
+pub fn snapshots_path() -> Option<PathBuf> {
+    user_dir("XDG_STATE_HOME", ".local/state").map(|dir| dir.join("snapshots.jsonl"))
+}
+
+/// Appends a snapshot to the history.
+///
+/// # Returns
+/// * `Result<PathBuf, String>` - The path of the history, or an error telling why it was not written
+pub fn append_snapshot(snapshot: &Snapshot) -> Result<PathBuf, String> {
+    let path = snapshots_path().ok_or("No directory to keep the snapshots in")?;
+    let line = serde_json::to_string(snapshot).map_err(|err| err.to_string())?;
+    if let Some(dir) = path.parent() {
+        let _ = fs::create_dir_all(dir);
+    }
+    OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(&path)
+        .and_then(|mut file| writeln!(file, "{}", line))
+        .map_err(|err| format!("Failed to write {}: {}", path.display(), err))?;
+    debug(&format!("Recorded the snapshot of {}", snapshot.date));
+    Ok(path)
+}
+
+/// Reads the snapshots of the history in the order they were recorded,
+/// skipping the lines that do not parse.
+pub fn read_snapshots() -> Vec<Snapshot> {
+    let Some(content) = snapshots_path().and_then(|path| fs::read_to_string(path).ok()) else {
+        return Vec::new();
+    };
+    content
+        .lines()
+        .filter_map(|line| match serde_json::from_str(line) {
+            Ok(snapshot) => Some(snapshot),
+            Err(err) => {
+                debug(&format!("Skipping snapshot line {}: {}", line, err));
+                None
+            }
+        })
+        .collect()
+}
+
+/// Sums the snapshots by week or by month, a day recorded several times
+/// counting with its last snapshot.
+///
+/// # Arguments
+/// * `snapshots` - The snapshots, in the order they were recorded
+/// * `monthly` - Whether the snapshots are summed by month rather than by ISO week
+///
+/// # Returns
+/// * `Vec<SnapshotPeriod>` - The weeks or months with a snapshot, oldest first
+pub fn snapshot_periods(snapshots: &[Snapshot], monthly: bool) -> Vec<SnapshotPeriod> {
+    let days: BTreeMap<NaiveDate, &Snapshot> = snapshots
+        .iter()
+        .filter_map(|snapshot| {
+            let date = NaiveDate::parse_from_str(&snapshot.date, "%Y-%m-%d").ok()?;
+            Some((date, snapshot))
+        })
+        .collect();
+
+    let mut periods: Vec<SnapshotPeriod> = Vec::new();
+    for (date, snapshot) in days {
+        let label = if monthly {
+            date.format("%Y-%m").to_string()
+        } else {
+            date.format("%G-W%V").to_string()
+        };
+        if periods.last().map(|period| &period.label) != Some(&label) {
+            periods.push(SnapshotPeriod {
+                label,
+                ..SnapshotPeriod::default()
+            });
+        }
+        if let Some(period) = periods.last_mut() {
+            period.days += 1;
+            period.commits += snapshot.commits;
+            period.out_of_hours_commits += snapshot.out_of_hours_commits;
+        }
+    }
+    periods
+}
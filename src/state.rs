@@ -0,0 +1,161 @@
+use crate::analyzer::{read_log as read_history, Backend, HistoryFilter, LogRange, LoggedCommit};
+use crate::authors::AuthorFilter;
+#[cfg(feature = "libgit2")]
+use crate::libgit2;
+use crate::utils::{bound_timestamp, debug, key_file_name, user_dir};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::Path;
+use std::process::Command;
+
+/// The commits of a repository kept from one `--incremental` run to the next.
+#[derive(Serialize, Deserialize)]
+struct RepoState {
+    /// The repository and the options the commits were read with
+    key: String,
+    /// The commit HEAD pointed to when the commits were last read
+    head: String,
+    /// Every commit of the history up to `head`, newest first
+    commits: Vec<LoggedCommit>,
+}
+
+/// Reads the commits of a repository in the period, reading from the history
+/// only the commits made since the previous run and keeping the others from
+/// the state saved in `$XDG_STATE_HOME/gitnapped` (`~/.local/state/gitnapped`).
+///
+/// The whole history is read again when the commits could not be kept, e.g.
+/// after a rebase or a switch to another branch.
+///
+/// # Arguments
+/// * `repo` - Path to the Git repository
+/// * `authors` - Authors whose commits are counted
+/// * `since` - Start of the period, as `YYYY-MM-DD HH:MM:SS` or ISO 8601
+/// * `until` - End of the period, as `YYYY-MM-DD HH:MM:SS` or ISO 8601
+/// * `history` - Which part of the history is analyzed
+///
+/// # Returns
+/// * `Option<Vec<LoggedCommit>>` - The commits of the period, None if the history could not be read
+pub fn read_log(
+    repo: &str,
+    authors: &AuthorFilter,
+    since: &str,
+    until: &str,
+    history: &HistoryFilter,
+) -> Option<Vec<LoggedCommit>> {
+    let period = LogRange::Period { since, until };
+    let (Some(head), Some(dir), Some(since), Some(until)) = (
+        head_commit(repo, history.backend),
+        user_dir("XDG_STATE_HOME", ".local/state"),
+        bound_timestamp(since),
+        bound_timestamp(until),
+    ) else {
+        return read_history(repo, authors, period, history);
+    };
+
+    let key = state_key(repo, authors, history);
+    let path = dir.join(key_file_name(&key));
+    let saved = fs::read_to_string(&path)
+        .ok()
+        .and_then(|content| serde_json::from_str::<RepoState>(&content).ok())
+        .filter(|state| state.key == key);
+
+    let commits = match saved {
+        Some(state) if state.head == head => {
+            debug(&format!("No new commits in {} since the last run", repo));
+            state.commits
+        }
+        Some(state) if is_ancestor(repo, &state.head, &head, history.backend) => {
+            let mut commits =
+                read_history(repo, authors, LogRange::After(Some(&state.head)), history)?;
+            debug(&format!(
+                "Read {} new commits of {} since the last run",
+                commits.len(),
+                repo
+            ));
+            commits.extend(state.commits);
+            save(&path, key, head, commits)
+        }
+        _ => {
+            debug(&format!("Reading the whole history of {}", repo));
+            let commits = read_history(repo, authors, LogRange::After(None), history)?;
+            save(&path, key, head, commits)
+        }
+    };
+
+    // Like git, the period applies to the committer date whatever the date source
+    Some(
+        commits
+            .into_iter()
+            .filter(|commit| (since..=until).contains(&commit.committed))
+            .collect(),
+    )
+}
+
+/// Builds the key the commits of a repository are kept under: everything
+/// deciding which commits are read and how their authors are named.
+fn state_key(repo: &str, authors: &AuthorFilter, history: &HistoryFilter) -> String {
+    let path = fs::canonicalize(repo)
+        .map(|path| path.to_string_lossy().into_owned())
+        .unwrap_or_else(|_| repo.to_string());
+    // A changed .mailmap names the authors of the kept commits differently
+    let mailmap = fs::read_to_string(Path::new(repo).join(".mailmap")).unwrap_or_default();
+
+    format!(
+        "{} {}\n{}\n{}\n{:?}",
+        env!("CARGO_PKG_NAME"),
+        env!("CARGO_PKG_VERSION"),
+        path,
+        key_file_name(&mailmap),
+        (
+            authors,
+            history.merges,
+            history.first_parent,
+            &history.pathspecs
+        )
+    )
+}
+
+/// Saves the commits of a repository, returning them. Failing to save them
+/// only costs the next run the time to read the whole history again.
+fn save(path: &Path, key: String, head: String, commits: Vec<LoggedCommit>) -> Vec<LoggedCommit> {
+    let state = RepoState { key, head, commits };
+    let result = path
+        .parent()
+        .map_or(Ok(()), fs::create_dir_all)
+        .map_err(|err| err.to_string())
+        .and_then(|_| serde_json::to_string(&state).map_err(|err| err.to_string()))
+        .and_then(|json| fs::write(path, json).map_err(|err| err.to_string()));
+    if let Err(err) = result {
+        debug(&format!("Failed to save the state in {:?}: {}", path, err));
+    }
+    state.commits
+}
+
+/// Reads the commit HEAD points to with the chosen backend.
+fn head_commit(repo: &str, backend: Backend) -> Option<String> {
+    match backend {
+        Backend::Git => {
+            let output = Command::new("git")
+                .args(["-C", repo, "rev-parse", "HEAD"])
+                .output()
+                .ok()?;
+            let head = String::from_utf8_lossy(&output.stdout).trim().to_string();
+            (output.status.success() && !head.is_empty()).then_some(head)
+        }
+        #[cfg(feature = "libgit2")]
+        Backend::Libgit2 => libgit2::head_commit(repo).ok(),
+    }
+}
+
+/// Checks if a commit is an ancestor of another one with the chosen backend, so
+/// that the commits read up to it are still part of the history.
+fn is_ancestor(repo: &str, ancestor: &str, commit: &str, backend: Backend) -> bool {
+    match backend {
+        Backend::Git => Command::new("git")
+            .args(["-C", repo, "merge-base", "--is-ancestor", ancestor, commit])
+            .output()
+            .is_ok_and(|output| output.status.success()),
+        #[cfg(feature = "libgit2")]
+        Backend::Libgit2 => libgit2::is_ancestor(repo, ancestor, commit).unwrap_or(false),
+    }
+}
@@ -0,0 +1,69 @@
+use regex::Regex;
+
+/// Ticket patterns used when the config has none: Jira-like keys such as
+/// `PROJ-123` and GitHub or GitLab issue numbers such as `#456`.
+pub const DEFAULT_TICKET_PATTERNS: [&str; 2] =
+    [r"\b([A-Z][A-Z0-9]+-[0-9]+)\b", r"(?:^|\W)(#[0-9]+)\b"];
+
+/// Regular expressions finding the IDs of issue tracker tickets in commit subjects.
+///
+/// A pattern with a capture group takes its first group as the ticket ID,
+/// e.g. `(?i)refs? ([0-9]+)`, and any other pattern the whole match.
+#[derive(Debug, Clone)]
+pub struct TicketPatterns {
+    patterns: Vec<Regex>,
+}
+
+impl TicketPatterns {
+    /// Compiles ticket patterns.
+    ///
+    /// # Arguments
+    /// * `patterns` - The regular expressions, e.g. `ACME-[0-9]+`
+    ///
+    /// # Returns
+    /// * `Result<TicketPatterns, String>` - The compiled patterns, or an error for an invalid one
+    pub fn new<S: AsRef<str>>(patterns: &[S]) -> Result<TicketPatterns, String> {
+        patterns
+            .iter()
+            .map(|pattern| {
+                let pattern = pattern.as_ref();
+                Regex::new(pattern)
+                    .map_err(|err| format!("Invalid ticket pattern '{}': {}", pattern, err))
+            })
+            .collect::<Result<Vec<Regex>, String>>()
+            .map(|patterns| TicketPatterns { patterns })
+    }
+
+    /// Finds the tickets a commit subject refers to.
+    ///
+    /// ```
+    /// use gitnapped::tickets::TicketPatterns;
+    ///
+    /// let tickets = TicketPatterns::default().tickets("PROJ-12: Fix the login (#34, PROJ-12)");
+    /// assert_eq!(tickets, ["PROJ-12", "#34"]);
+    /// ```
+    ///
+    /// # Returns
+    /// * `Vec<String>` - The ticket IDs in the order they appear, without duplicates
+    pub fn tickets(&self, subject: &str) -> Vec<String> {
+        let mut tickets: Vec<(usize, String)> = Vec::new();
+        for pattern in &self.patterns {
+            for captures in pattern.captures_iter(subject) {
+                let Some(ticket) = captures.get(1).or_else(|| captures.get(0)) else {
+                    continue;
+                };
+                if !tickets.iter().any(|(_, id)| id == ticket.as_str()) {
+                    tickets.push((ticket.start(), ticket.as_str().to_string()));
+                }
+            }
+        }
+        tickets.sort();
+        tickets.into_iter().map(|(_, ticket)| ticket).collect()
+    }
+}
+
+impl Default for TicketPatterns {
+    fn default() -> Self {
+        TicketPatterns::new(&DEFAULT_TICKET_PATTERNS).expect("default ticket patterns are valid")
+    }
+}
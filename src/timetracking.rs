@@ -0,0 +1,556 @@
+use crate::analyzer::{read_log, work_sessions, HistoryFilter, LogRange, LoggedCommit};
+use crate::authors::AuthorFilter;
+use crate::display::{csv_field, md_cell};
+use crate::models::RepoInfo;
+use crate::parser::group_repos_by_vanity;
+use crate::remote::{read_remote_log, RemoteRepo};
+use crate::schedule::Schedules;
+use crate::utils::debug;
+use base64::engine::general_purpose::STANDARD;
+use base64::Engine;
+use chrono::{
+    DateTime, Duration, FixedOffset, Local, NaiveDate, NaiveDateTime, SecondsFormat, Utc,
+};
+use serde::Deserialize;
+use serde_json::json;
+use std::collections::{BTreeMap, HashSet};
+use std::env;
+
+/// Environment variable holding the API token of Toggl Track.
+pub const TOGGL_TOKEN_VARIABLE: &str = "TOGGL_API_TOKEN";
+
+/// Environment variable holding the API key of Clockify.
+pub const CLOCKIFY_KEY_VARIABLE: &str = "CLOCKIFY_API_KEY";
+
+/// Environment variable holding the URL of the Clockify API, e.g. the one of
+/// a regional server such as `https://euc1.clockify.me/api`.
+pub const CLOCKIFY_API_VARIABLE: &str = "CLOCKIFY_API_URL";
+
+/// Environment variable holding the URL of the Toggl Track API, e.g. a proxy.
+pub const TOGGL_API_VARIABLE: &str = "TOGGL_API_URL";
+
+/// A work session on a project: commits less than the session gap apart,
+/// starting half a gap before the first one as for the hours gitnapped.
+#[derive(Debug, Clone)]
+pub struct ProjectSession {
+    /// Name of the project
+    pub project: String,
+    /// Category of the project, if any
+    pub category: Option<String>,
+    /// Start of the session
+    pub start: DateTime<Utc>,
+    /// End of the session, the time of its last commit
+    pub end: DateTime<Utc>,
+    /// Subjects of the commits of the session, oldest first
+    pub subjects: Vec<String>,
+}
+
+impl ProjectSession {
+    /// Describes the session by the subjects of its commits, each one once.
+    pub fn description(&self) -> String {
+        let mut subjects: Vec<&str> = Vec::new();
+        for subject in &self.subjects {
+            if !subjects.contains(&subject.as_str()) {
+                subjects.push(subject);
+            }
+        }
+        subjects.join("; ")
+    }
+
+    /// Returns the length of the session in seconds.
+    pub fn seconds(&self) -> i64 {
+        (self.end - self.start).num_seconds()
+    }
+}
+
+/// The commits of a project, each repository of it read once.
+struct ProjectCommits {
+    /// Name of the project
+    project: String,
+    /// Category of the project, if any
+    category: Option<String>,
+    /// Path of the first repository of the project
+    repo: String,
+    /// Time and subject of every commit, oldest first
+    commits: Vec<(DateTime<FixedOffset>, String)>,
+}
+
+/// Reads the commits of every project of the config, a repository listed in
+/// several projects counting for each of them.
+fn read_project_commits(
+    repos: &[RepoInfo],
+    authors: &AuthorFilter,
+    since: &str,
+    until: &str,
+    history: &HistoryFilter,
+) -> Vec<ProjectCommits> {
+    group_repos_by_vanity(repos)
+        .into_iter()
+        .map(|(project, project_repos)| {
+            // A repository listed twice in a project is read once
+            let mut seen = HashSet::new();
+            let mut commits: Vec<(DateTime<FixedOffset>, String)> = project_repos
+                .iter()
+                .filter(|repo| seen.insert(repo.canonical_key()))
+                .flat_map(|repo| {
+                    let history = history.for_repo(&repo.pathspecs);
+                    let log = match RemoteRepo::parse(&repo.path) {
+                        Some(remote) => read_remote_log(&remote, authors, since, until, &history),
+                        None => read_log(
+                            &repo.path,
+                            authors,
+                            LogRange::Period { since, until },
+                            &history,
+                        ),
+                    };
+                    log.unwrap_or_default()
+                })
+                .filter_map(|commit| commit_time_and_subject(&commit))
+                .collect();
+            commits.sort();
+            ProjectCommits {
+                project,
+                category: project_repos.iter().find_map(|repo| repo.group.clone()),
+                repo: project_repos
+                    .first()
+                    .map(|repo| repo.path.clone())
+                    .unwrap_or_default(),
+                commits,
+            }
+        })
+        .collect()
+}
+
+/// Groups the commits of every project of the config into work sessions, a
+/// repository listed in several projects counting for each of them.
+///
+/// # Arguments
+/// * `repos` - The repositories of the config
+/// * `authors` - Authors whose commits are counted
+/// * `since` - Start of the period
+/// * `until` - End of the period
+/// * `history` - Which part of the history is read
+/// * `session_gap` - Maximum gap in minutes between two commits of the same session
+///
+/// # Returns
+/// * `Vec<ProjectSession>` - The sessions, by start
+pub fn project_sessions(
+    repos: &[RepoInfo],
+    authors: &AuthorFilter,
+    since: &str,
+    until: &str,
+    history: &HistoryFilter,
+    session_gap: i64,
+) -> Vec<ProjectSession> {
+    let mut sessions = Vec::new();
+    for project in read_project_commits(repos, authors, since, until, history) {
+        let commits: Vec<(NaiveDateTime, &String)> = project
+            .commits
+            .iter()
+            .map(|(time, subject)| (time.naive_utc(), subject))
+            .collect();
+        let mut times: Vec<NaiveDateTime> = commits.iter().map(|(time, _)| *time).collect();
+        for (start, end) in work_sessions(&mut times, session_gap) {
+            let subjects: Vec<String> = commits
+                .iter()
+                .filter(|(time, _)| *time >= start && *time <= end)
+                .map(|(_, subject)| subject.to_string())
+                .collect();
+            sessions.push(ProjectSession {
+                project: project.project.clone(),
+                category: project.category.clone(),
+                start: start.and_utc(),
+                end: end.and_utc(),
+                subjects,
+            });
+        }
+    }
+    debug(&format!("Found {} work sessions", sessions.len()));
+    sessions.sort_by(|a, b| (a.start, &a.project).cmp(&(b.start, &b.project)));
+    sessions
+}
+
+/// Reads the time of a commit and its subject out of its line,
+/// `<hash> <date> <subject>`.
+fn commit_time_and_subject(commit: &LoggedCommit) -> Option<(DateTime<FixedOffset>, String)> {
+    let mut parts = commit.line.splitn(3, ' ');
+    let date = parts.nth(1)?;
+    let subject = parts.next().unwrap_or_default().trim().to_string();
+    let time = DateTime::parse_from_rfc3339(date).ok()?;
+    Some((time, subject))
+}
+
+/// The time estimated for a project on a day, split by the working hours.
+#[derive(Debug, Clone)]
+pub struct TimesheetDay {
+    /// The day
+    pub date: NaiveDate,
+    /// Name of the project
+    pub project: String,
+    /// Category of the project, if any
+    pub category: Option<String>,
+    /// Minutes of the work sessions inside the working hours
+    pub in_hours_minutes: usize,
+    /// Minutes of the work sessions outside the working hours
+    pub out_of_hours_minutes: usize,
+}
+
+impl TimesheetDay {
+    /// Returns the minutes of the day, in and out of hours.
+    pub fn minutes(&self) -> usize {
+        self.in_hours_minutes + self.out_of_hours_minutes
+    }
+}
+
+/// Estimates the time spent on every project day by day, out of the work
+/// sessions of its commits like the hours gitnapped: in the timezone and
+/// working hours of its first repository, a session counting for the day of
+/// its first commit.
+///
+/// # Arguments
+/// * `repos` - The repositories of the config
+/// * `authors` - Authors whose commits are counted
+/// * `since` - Start of the period
+/// * `until` - End of the period
+/// * `history` - Which part of the history is read
+/// * `session_gap` - Maximum gap in minutes between two commits of the same session
+/// * `schedules` - The working hours of the repositories
+///
+/// # Returns
+/// * `Vec<TimesheetDay>` - Every day of every project with commits, by day then project
+pub fn timesheet(
+    repos: &[RepoInfo],
+    authors: &AuthorFilter,
+    since: &str,
+    until: &str,
+    history: &HistoryFilter,
+    session_gap: i64,
+    schedules: &Schedules,
+) -> Vec<TimesheetDay> {
+    let mut days: BTreeMap<(NaiveDate, String), TimesheetDay> = BTreeMap::new();
+    for project in read_project_commits(repos, authors, since, until, history) {
+        let schedule = schedules.for_repo(&project.repo);
+        let mut times: Vec<NaiveDateTime> = project
+            .commits
+            .iter()
+            .map(|(time, _)| schedule.timezone.convert(time))
+            .collect();
+        for (start, end) in work_sessions(&mut times, session_gap) {
+            let first_commit = start + Duration::minutes(session_gap / 2);
+            let minutes = (end - start).num_minutes() as usize;
+            let outside = schedule.minutes_outside(start, end).min(minutes);
+            let day = days
+                .entry((first_commit.date(), project.project.clone()))
+                .or_insert_with(|| TimesheetDay {
+                    date: first_commit.date(),
+                    project: project.project.clone(),
+                    category: project.category.clone(),
+                    in_hours_minutes: 0,
+                    out_of_hours_minutes: 0,
+                });
+            day.in_hours_minutes += minutes - outside;
+            day.out_of_hours_minutes += outside;
+        }
+    }
+    days.into_values().collect()
+}
+
+/// Formats minutes as decimal hours, e.g. `1.25` for 75 minutes.
+fn decimal_hours(minutes: usize) -> String {
+    format!("{:.2}", minutes as f64 / 60.0)
+}
+
+/// Formats a timesheet as a CSV, one line per day and project.
+///
+/// # Arguments
+/// * `days` - The days of the timesheet
+///
+/// # Returns
+/// * `String` - The CSV, hours in decimal
+pub fn timesheet_csv(days: &[TimesheetDay]) -> String {
+    let mut lines = vec![String::from(
+        "date,project,category,hours,in_hours,out_of_hours",
+    )];
+    for day in days {
+        lines.push(format!(
+            "{},{},{},{},{},{}",
+            day.date.format("%Y-%m-%d"),
+            csv_field(&day.project),
+            csv_field(day.category.as_deref().unwrap_or_default()),
+            decimal_hours(day.minutes()),
+            decimal_hours(day.in_hours_minutes),
+            decimal_hours(day.out_of_hours_minutes)
+        ));
+    }
+    lines.join("\n")
+}
+
+/// Formats a timesheet as Markdown: a table of the days of every project,
+/// then the totals of every project over the period.
+///
+/// # Arguments
+/// * `days` - The days of the timesheet
+/// * `since` - Start of the period
+/// * `until` - End of the period
+///
+/// # Returns
+/// * `String` - The Markdown, hours in decimal
+pub fn timesheet_markdown(days: &[TimesheetDay], since: &str, until: &str) -> String {
+    let mut md = format!("# Timesheet\n\n- **Period:** {} – {}\n\n", since, until);
+    md.push_str("| Date | Project | Hours | In hours | Out of hours |\n| --- | --- | ---: | ---: | ---: |\n");
+    for day in days {
+        md.push_str(&format!(
+            "| {} | {} | {} | {} | {} |\n",
+            day.date.format("%Y-%m-%d"),
+            md_cell(&day.project),
+            decimal_hours(day.minutes()),
+            decimal_hours(day.in_hours_minutes),
+            decimal_hours(day.out_of_hours_minutes)
+        ));
+    }
+
+    let mut projects: BTreeMap<&str, (usize, usize)> = BTreeMap::new();
+    for day in days {
+        let (in_hours, out_of_hours) = projects.entry(&day.project).or_default();
+        *in_hours += day.in_hours_minutes;
+        *out_of_hours += day.out_of_hours_minutes;
+    }
+    md.push_str("\n## Totals\n\n| Project | Hours | In hours | Out of hours |\n| --- | ---: | ---: | ---: |\n");
+    for (project, (in_hours, out_of_hours)) in &projects {
+        md.push_str(&format!(
+            "| {} | {} | {} | {} |\n",
+            md_cell(project),
+            decimal_hours(in_hours + out_of_hours),
+            decimal_hours(*in_hours),
+            decimal_hours(*out_of_hours)
+        ));
+    }
+    let in_hours: usize = days.iter().map(|day| day.in_hours_minutes).sum();
+    let out_of_hours: usize = days.iter().map(|day| day.out_of_hours_minutes).sum();
+    md.push_str(&format!(
+        "| **Total** | **{}** | **{}** | **{}** |\n",
+        decimal_hours(in_hours + out_of_hours),
+        decimal_hours(in_hours),
+        decimal_hours(out_of_hours)
+    ));
+    md
+}
+
+/// Formats work sessions in a CSV format time trackers import.
+///
+/// # Arguments
+/// * `sessions` - The sessions
+/// * `format` - `toggl` or `clockify`
+/// * `email` - The email of the user the entries are of, which both formats ask for
+///
+/// # Returns
+/// * `Result<String, String>` - The CSV, or an error for an unknown format
+pub fn time_entries_csv(
+    sessions: &[ProjectSession],
+    format: &str,
+    email: &str,
+) -> Result<String, String> {
+    let header = match format {
+        "toggl" => "Email,Project,Client,Description,Start date,Start time,Duration",
+        "clockify" => {
+            "Project,Client,Description,Email,Start Date,Start Time,End Date,End Time,Duration (h)"
+        }
+        _ => return Err(format!("Unknown time entry format '{}'", format)),
+    };
+
+    let mut lines = vec![header.to_string()];
+    for session in sessions {
+        let start = session.start.with_timezone(&Local);
+        let end = session.end.with_timezone(&Local);
+        let category = session.category.as_deref().unwrap_or_default();
+        let fields = if format == "toggl" {
+            let seconds = session.seconds();
+            vec![
+                email.to_string(),
+                session.project.clone(),
+                category.to_string(),
+                session.description(),
+                start.format("%Y-%m-%d").to_string(),
+                start.format("%H:%M:%S").to_string(),
+                format!(
+                    "{:02}:{:02}:{:02}",
+                    seconds / 3600,
+                    seconds / 60 % 60,
+                    seconds % 60
+                ),
+            ]
+        } else {
+            vec![
+                session.project.clone(),
+                category.to_string(),
+                session.description(),
+                email.to_string(),
+                start.format("%Y-%m-%d").to_string(),
+                start.format("%H:%M:%S").to_string(),
+                end.format("%Y-%m-%d").to_string(),
+                end.format("%H:%M:%S").to_string(),
+                format!("{:.2}", session.seconds() as f64 / 3600.0),
+            ]
+        };
+        let fields: Vec<String> = fields.iter().map(|field| csv_field(field)).collect();
+        lines.push(fields.join(","));
+    }
+    Ok(lines.join("\n"))
+}
+
+/// Picks the email the time entries are of out of the author filter, e.g.
+/// `john@example.com` out of `John Doe <john@example.com>`.
+///
+/// # Returns
+/// * `Option<String>` - The first email of the authors, None if they have none
+pub fn author_email(authors: &[String]) -> Option<String> {
+    authors.iter().find_map(|author| {
+        let email = match (author.find('<'), author.rfind('>')) {
+            (Some(start), Some(end)) if start < end => &author[start + 1..end],
+            _ => author.as_str(),
+        };
+        email.contains('@').then(|| email.trim().to_string())
+    })
+}
+
+/// A project of a workspace of Toggl Track or Clockify.
+#[derive(Deserialize)]
+struct TrackerProject {
+    id: serde_json::Value,
+    name: String,
+}
+
+/// Creates a time entry in a Toggl Track workspace for every work session,
+/// in the project of the same name when there is one, else with the name of
+/// the project before the description.
+///
+/// # Arguments
+/// * `sessions` - The sessions
+/// * `workspace` - The id of the workspace
+///
+/// # Returns
+/// * `Result<usize, String>` - The number of entries created, or an error telling why one was not
+pub fn push_to_toggl(sessions: &[ProjectSession], workspace: &str) -> Result<usize, String> {
+    let token = env::var(TOGGL_TOKEN_VARIABLE).map_err(|_| {
+        format!(
+            "Toggl Track needs an API token in ${}",
+            TOGGL_TOKEN_VARIABLE
+        )
+    })?;
+    let api = env::var(TOGGL_API_VARIABLE)
+        .unwrap_or_else(|_| String::from("https://api.track.toggl.com/api/v9"));
+    let workspace_url = format!("{}/workspaces/{}", api.trim_end_matches('/'), workspace);
+    let authorization = format!("Basic {}", STANDARD.encode(format!("{}:api_token", token)));
+    let workspace_id: i64 = workspace
+        .parse()
+        .map_err(|_| format!("Invalid Toggl workspace id '{}'", workspace))?;
+
+    let projects: Vec<TrackerProject> = tracker_request(
+        ureq::get(&format!("{}/projects", workspace_url)).header("Authorization", &authorization),
+    )?;
+    for session in sessions {
+        let project = find_project(&projects, &session.project);
+        let entry = json!({
+            "created_with": "gitnapped",
+            "workspace_id": workspace_id,
+            "project_id": project.map(|project| project.id.clone()),
+            "description": described(session, project.is_some()),
+            "start": session.start.to_rfc3339_opts(SecondsFormat::Secs, true),
+            "stop": session.end.to_rfc3339_opts(SecondsFormat::Secs, true),
+            "duration": session.seconds(),
+            "tags": ["gitnapped"],
+        });
+        send_entry(
+            ureq::post(&format!("{}/time_entries", workspace_url))
+                .header("Authorization", &authorization),
+            &entry,
+        )?;
+    }
+    Ok(sessions.len())
+}
+
+/// Creates a time entry in a Clockify workspace for every work session, in
+/// the project of the same name when there is one, else with the name of the
+/// project before the description.
+///
+/// # Arguments
+/// * `sessions` - The sessions
+/// * `workspace` - The id of the workspace
+///
+/// # Returns
+/// * `Result<usize, String>` - The number of entries created, or an error telling why one was not
+pub fn push_to_clockify(sessions: &[ProjectSession], workspace: &str) -> Result<usize, String> {
+    let key = env::var(CLOCKIFY_KEY_VARIABLE)
+        .map_err(|_| format!("Clockify needs an API key in ${}", CLOCKIFY_KEY_VARIABLE))?;
+    let api = env::var(CLOCKIFY_API_VARIABLE)
+        .unwrap_or_else(|_| String::from("https://api.clockify.me/api"));
+    let workspace_url = format!("{}/v1/workspaces/{}", api.trim_end_matches('/'), workspace);
+
+    let projects: Vec<TrackerProject> = tracker_request(
+        ureq::get(&format!("{}/projects?page-size=5000", workspace_url)).header("X-Api-Key", &key),
+    )?;
+    for session in sessions {
+        let project = find_project(&projects, &session.project);
+        let entry = json!({
+            "projectId": project.map(|project| project.id.clone()),
+            "description": described(session, project.is_some()),
+            "start": session.start.to_rfc3339_opts(SecondsFormat::Secs, true),
+            "end": session.end.to_rfc3339_opts(SecondsFormat::Secs, true),
+        });
+        send_entry(
+            ureq::post(&format!("{}/time-entries", workspace_url)).header("X-Api-Key", &key),
+            &entry,
+        )?;
+    }
+    Ok(sessions.len())
+}
+
+/// Finds the project of a time tracker named like a project of the config, whatever the case.
+fn find_project<'a>(projects: &'a [TrackerProject], name: &str) -> Option<&'a TrackerProject> {
+    projects
+        .iter()
+        .find(|project| project.name.eq_ignore_ascii_case(name))
+}
+
+/// Describes a session in a time entry, naming its project when the time
+/// tracker has no project of that name.
+fn described(session: &ProjectSession, in_project: bool) -> String {
+    if in_project {
+        session.description()
+    } else {
+        debug(&format!(
+            "No project named {} in the workspace",
+            session.project
+        ));
+        format!("[{}] {}", session.project, session.description())
+    }
+}
+
+/// Sends a GET request to the API of a time tracker and reads its JSON answer.
+fn tracker_request<T: serde::de::DeserializeOwned>(
+    request: ureq::RequestBuilder<ureq::typestate::WithoutBody>,
+) -> Result<T, String> {
+    let mut response = request
+        .header("User-Agent", "gitnapped")
+        .call()
+        .map_err(|err| err.to_string())?;
+    let body = response
+        .body_mut()
+        .read_to_string()
+        .map_err(|err| err.to_string())?;
+    serde_json::from_str(&body).map_err(|err| format!("Unexpected response: {}", err))
+}
+
+/// Posts a time entry to the API of a time tracker.
+fn send_entry(
+    request: ureq::RequestBuilder<ureq::typestate::WithBody>,
+    entry: &serde_json::Value,
+) -> Result<(), String> {
+    debug(&format!("Creating the time entry {}", entry));
+    request
+        .header("User-Agent", "gitnapped")
+        .header("Content-Type", "application/json")
+        .send(entry.to_string())
+        .map(|_| ())
+        .map_err(|err| format!("Failed to create a time entry: {}", err))
+}
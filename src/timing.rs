@@ -0,0 +1,162 @@
+use colored::*;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+/// Whether the phases are timed, as chosen by `--timing`.
+static ENABLED: AtomicBool = AtomicBool::new(false);
+
+/// When timing started, for the wall time of the whole run.
+static STARTED: Mutex<Option<Instant>> = Mutex::new(None);
+
+/// Time spent in every phase, per repository.
+static TIMINGS: Mutex<Vec<(String, [Duration; PHASES])>> = Mutex::new(Vec::new());
+
+/// Number of timed phases.
+const PHASES: usize = 4;
+
+/// A phase of the analysis of a repository.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Phase {
+    /// Reading the commits of the repository, tags included
+    Log,
+    /// Reading the commits of its submodules
+    Submodules,
+    /// Counting its files and lines
+    Files,
+    /// The whole analysis, including the phases above
+    Total,
+}
+
+impl Phase {
+    /// Column headers of the phases, in the order of their indexes.
+    const NAMES: [&'static str; PHASES] = ["git log", "submodules", "files", "total"];
+
+    fn index(&self) -> usize {
+        match self {
+            Phase::Log => 0,
+            Phase::Submodules => 1,
+            Phase::Files => 2,
+            Phase::Total => 3,
+        }
+    }
+}
+
+/// Starts timing the phases of the analysis, for `--timing`.
+pub fn enable() {
+    ENABLED.store(true, Ordering::Relaxed);
+    if let Ok(mut started) = STARTED.lock() {
+        *started = Some(Instant::now());
+    }
+}
+
+/// Runs a phase of the analysis of a repository, recording how long it took
+/// when timing is enabled.
+///
+/// # Arguments
+/// * `repo` - The repository being analyzed
+/// * `phase` - The phase run by `run`
+/// * `run` - The phase itself
+///
+/// # Returns
+/// * `T` - What the phase returned
+pub fn time<T>(repo: &str, phase: Phase, run: impl FnOnce() -> T) -> T {
+    if !ENABLED.load(Ordering::Relaxed) {
+        return run();
+    }
+
+    let started = Instant::now();
+    let result = run();
+    let elapsed = started.elapsed();
+
+    if let Ok(mut timings) = TIMINGS.lock() {
+        // A repository analyzed again, e.g. for --trend, adds up
+        let index = match timings.iter().position(|(name, _)| name == repo) {
+            Some(index) => index,
+            None => {
+                timings.push((repo.to_string(), [Duration::ZERO; PHASES]));
+                timings.len() - 1
+            }
+        };
+        timings[index].1[phase.index()] += elapsed;
+    }
+    result
+}
+
+/// Prints how long every phase took per repository, the slowest repositories
+/// first, and the totals, on standard error so machine readable output is
+/// left untouched. Nothing is printed when timing is not enabled.
+pub fn print_timings() {
+    if !ENABLED.load(Ordering::Relaxed) {
+        return;
+    }
+    let Ok(mut timings) = TIMINGS.lock() else {
+        return;
+    };
+    timings.sort_by(|a, b| b.1[Phase::Total.index()].cmp(&a.1[Phase::Total.index()]));
+
+    let mut totals = [Duration::ZERO; PHASES];
+    for (_, durations) in timings.iter() {
+        for (total, duration) in totals.iter_mut().zip(durations) {
+            *total += *duration;
+        }
+    }
+
+    let width = timings
+        .iter()
+        .map(|(repo, _)| repo.chars().count())
+        .chain(std::iter::once("Repository".len()))
+        .max()
+        .unwrap_or_default();
+    let row = |name: &str, durations: Vec<String>| {
+        let columns: Vec<String> = durations
+            .iter()
+            .map(|duration| format!("{:>10}", duration))
+            .collect();
+        format!("  {:<width$} {}", name, columns.join(" "), width = width)
+    };
+
+    eprintln!(
+        "\n{}",
+        "Timing (slowest repositories first):".bright_magenta()
+    );
+    eprintln!(
+        "{}",
+        row(
+            "Repository",
+            Phase::NAMES.iter().map(|name| name.to_string()).collect()
+        )
+        .yellow()
+    );
+    for (repo, durations) in timings.iter() {
+        eprintln!(
+            "{}",
+            row(repo, durations.iter().map(format_duration).collect())
+        );
+    }
+    eprintln!(
+        "{}",
+        row("Total", totals.iter().map(format_duration).collect()).bold()
+    );
+
+    let wall = STARTED
+        .lock()
+        .ok()
+        .and_then(|started| started.map(|started| started.elapsed()));
+    if let Some(wall) = wall {
+        eprintln!(
+            "{}: {}",
+            "Wall time".yellow(),
+            format_duration(&wall).cyan()
+        );
+    }
+}
+
+/// Formats a duration in milliseconds, or in seconds from one second on.
+fn format_duration(duration: &Duration) -> String {
+    if *duration >= Duration::from_secs(1) {
+        format!("{:.2} s", duration.as_secs_f64())
+    } else {
+        format!("{:.1} ms", duration.as_secs_f64() * 1000.0)
+    }
+}
@@ -1,9 +1,24 @@
-use crate::models::RepoStats;
-use chrono::{DateTime, Duration, Local};
+use crate::analyzer::Backend;
+use crate::language::{detect_language, FileTypeOrder, TestPatterns};
+#[cfg(feature = "libgit2")]
+use crate::libgit2;
+use crate::lines::{classify_lines, comment_syntax, LineCounts};
+use crate::models::{FileCounts, RepoStats};
+use crate::progress;
+use chrono::{
+    DateTime, Datelike, Duration, Local, Months, NaiveDate, NaiveDateTime, TimeZone, Weekday,
+};
+use colored::*;
 use regex::Regex;
-use std::collections::HashMap;
+use std::cmp::Reverse;
+use std::collections::hash_map::DefaultHasher;
+use std::collections::{BTreeMap, BTreeSet, HashSet};
+use std::env;
 use std::fs;
-use std::process::Command;
+use std::hash::{Hash, Hasher};
+use std::io::{Read, Write};
+use std::path::PathBuf;
+use std::process::{Command, Stdio};
 
 static mut DEBUG_MODE: bool = false;
 static mut SILENT_MODE: bool = false;
@@ -37,7 +52,7 @@ pub fn init_silent_mode(silent: bool) {
 pub fn debug(message: &str) {
     unsafe {
         if DEBUG_MODE {
-            println!("DEBUG: {}", message);
+            progress::suspend(|| println!("DEBUG: {}", message));
         }
     }
 }
@@ -49,12 +64,14 @@ pub fn debug(message: &str) {
 pub fn log(message: &str) {
     unsafe {
         if !SILENT_MODE {
-            println!("{}", message);
+            progress::suspend(|| println!("{}", message));
         }
     }
 }
 
 /// Parses a relative time period string and returns a DateTime object.
+/// Months and years follow the calendar (6M from July 15 is January 15),
+/// clamping to the last day of shorter months.
 /// Supports the following formats:
 /// - Y: Years (e.g., "2Y" for 2 years)
 /// - M: Months (e.g., "6M" for 6 months)
@@ -70,6 +87,7 @@ pub fn log(message: &str) {
 ///
 /// # Examples
 /// ```
+/// # use gitnapped::utils::parse_period;
 /// let six_months_ago = parse_period("6M");
 /// let two_years_ago = parse_period("2Y");
 /// let five_days_ago = parse_period("5D");
@@ -84,11 +102,13 @@ pub fn parse_period(period: &str) -> Option<DateTime<Local>> {
         let now = Local::now();
 
         match unit {
-            "Y" => Some((now - Duration::days(amount * 365)).into()),
-            "M" => Some((now - Duration::days(amount * 30)).into()),
-            "W" => Some((now - Duration::days(amount * 7)).into()),
-            "D" => Some((now - Duration::days(amount)).into()),
-            "H" => Some((now - Duration::hours(amount)).into()),
+            "Y" => {
+                now.checked_sub_months(Months::new(u32::try_from(amount).ok()?.checked_mul(12)?))
+            }
+            "M" => now.checked_sub_months(Months::new(u32::try_from(amount).ok()?)),
+            "W" => Some(now - Duration::days(amount * 7)),
+            "D" => Some(now - Duration::days(amount)),
+            "H" => Some(now - Duration::hours(amount)),
             _ => None,
         }
     } else {
@@ -96,84 +116,523 @@ pub fn parse_period(period: &str) -> Option<DateTime<Local>> {
     }
 }
 
-/// Gets the file extension from a file path.
+/// Named periods accepted by `--period` and as flags of their own, with their help text.
+pub const PERIOD_PRESETS: [(&str, &str); 7] = [
+    ("today", "Analyze today's commits"),
+    ("yesterday", "Analyze yesterday's commits"),
+    ("this-week", "Analyze the commits of the current week"),
+    ("last-week", "Analyze the commits of the previous week"),
+    ("this-month", "Analyze the commits of the current month"),
+    ("last-month", "Analyze the commits of the previous month"),
+    ("this-year", "Analyze the commits of the current year"),
+];
+
+/// Computes the first and last day of the week containing a date.
+///
+/// # Arguments
+/// * `date` - Any day of the week
+/// * `week_start` - The first day of the week
+///
+/// # Returns
+/// * `(NaiveDate, NaiveDate)` - The first and last day of the week
+pub fn week_range(date: NaiveDate, week_start: Weekday) -> (NaiveDate, NaiveDate) {
+    let first_day = date - Duration::days(date.weekday().days_since(week_start) as i64);
+    (first_day, first_day + Duration::days(6))
+}
+
+/// Computes the first and last day of a month given as YYYY-MM.
+///
+/// # Arguments
+/// * `month` - The month in YYYY-MM format
+///
+/// # Returns
+/// * `Option<(NaiveDate, NaiveDate)>` - The first and last day of the month, None if invalid
+pub fn month_range(month: &str) -> Option<(NaiveDate, NaiveDate)> {
+    let first_day = NaiveDate::parse_from_str(&format!("{}-01", month), "%Y-%m-%d").ok()?;
+    let last_day = first_day.checked_add_months(Months::new(1))?.pred_opt()?;
+    Some((first_day, last_day))
+}
+
+/// Parses a period given as a month (YYYY-MM), a day (YYYY-MM-DD) or a range
+/// of days (YYYY-MM-DD..YYYY-MM-DD).
+///
+/// # Arguments
+/// * `value` - The period
+///
+/// # Returns
+/// * `Option<(NaiveDate, NaiveDate)>` - The first and last day of the period, None if invalid
+pub fn parse_day_range(value: &str) -> Option<(NaiveDate, NaiveDate)> {
+    let day = |value: &str| NaiveDate::parse_from_str(value.trim(), "%Y-%m-%d").ok();
+    if let Some((first_day, last_day)) = value.split_once("..") {
+        let (first_day, last_day) = (day(first_day)?, day(last_day)?);
+        return (first_day <= last_day).then_some((first_day, last_day));
+    }
+    day(value)
+        .map(|date| (date, date))
+        .or_else(|| month_range(value.trim()))
+}
+
+/// Computes the first and last day of a named period such as `this-week` or `last-month`.
+///
+/// # Arguments
+/// * `preset` - The name of the period, one of `PERIOD_PRESETS`
+/// * `today` - The current date
+/// * `week_start` - The first day of the week
+///
+/// # Returns
+/// * `Option<(NaiveDate, NaiveDate)>` - The first and last day of the period, None for unknown names
+pub fn period_preset_range(
+    preset: &str,
+    today: NaiveDate,
+    week_start: Weekday,
+) -> Option<(NaiveDate, NaiveDate)> {
+    let (week_start_date, _) = week_range(today, week_start);
+    let month_start_date = today.with_day(1)?;
+
+    match preset {
+        "today" => Some((today, today)),
+        "yesterday" => {
+            let yesterday = today.pred_opt()?;
+            Some((yesterday, yesterday))
+        }
+        "this-week" => Some((week_start_date, today)),
+        "last-week" => Some(week_range(week_start_date - Duration::days(7), week_start)),
+        "this-month" => Some((month_start_date, today)),
+        "last-month" => month_range(
+            &month_start_date
+                .checked_sub_months(Months::new(1))?
+                .format("%Y-%m")
+                .to_string(),
+        ),
+        "this-year" => Some((today.with_ordinal(1)?, today)),
+        _ => None,
+    }
+}
+
+/// Parses the first day of the fiscal year given as MM-DD (e.g. `04-01`).
+///
+/// # Returns
+/// * `Option<(u32, u32)>` - The month and day, None if invalid
+pub fn parse_fiscal_year_start(value: &str) -> Option<(u32, u32)> {
+    let (month, day) = value.trim().split_once('-')?;
+    let month: u32 = month.parse().ok()?;
+    let day: u32 = day.parse().ok()?;
+
+    // Validate against a non-leap year, so the start exists every year
+    NaiveDate::from_ymd_opt(2001, month, day)?;
+    Some((month, day))
+}
+
+/// Computes the first and last day of the current fiscal year (up to today)
+/// or of the previous, complete one.
 ///
 /// # Arguments
-/// * `file_path` - The path to the file
+/// * `today` - The current date
+/// * `start` - Month and day the fiscal year starts on
+/// * `previous` - Whether to return the previous fiscal year
 ///
 /// # Returns
-/// * `String` - The file extension or "none" if no extension is found
-pub fn get_file_extension(file_path: &str) -> String {
-    let parts: Vec<&str> = file_path.split('.').collect();
-    if parts.len() > 1 {
-        parts.last().unwrap().to_string()
+/// * `Option<(NaiveDate, NaiveDate)>` - The first and last day of the fiscal year
+pub fn fiscal_year_range(
+    today: NaiveDate,
+    (month, day): (u32, u32),
+    previous: bool,
+) -> Option<(NaiveDate, NaiveDate)> {
+    let mut first_day = NaiveDate::from_ymd_opt(today.year(), month, day)?;
+    if first_day > today {
+        first_day = NaiveDate::from_ymd_opt(today.year() - 1, month, day)?;
+    }
+
+    if previous {
+        let previous_first_day = NaiveDate::from_ymd_opt(first_day.year() - 1, month, day)?;
+        Some((previous_first_day, first_day.pred_opt()?))
     } else {
-        "none".to_string()
+        Some((first_day, today))
+    }
+}
+
+/// Appends a time of day to a bare date (YYYY-MM-DD), leaving any other value untouched.
+///
+/// # Arguments
+/// * `value` - A date, date and time or any other date expression git understands
+/// * `time` - The time of day to append, in HH:MM:SS format
+///
+/// # Returns
+/// * `String` - The value with an explicit time of day when it was a bare date
+pub fn with_time_of_day(value: &str, time: &str) -> String {
+    match NaiveDate::parse_from_str(value, "%Y-%m-%d") {
+        Ok(date) => format!("{} {}", date.format("%Y-%m-%d"), time),
+        Err(_) => value.to_string(),
+    }
+}
+
+/// Date formats accepted for the bounds of the analyzed period.
+const RANGE_DATE_FORMATS: [&str; 3] = ["%Y-%m-%d %H:%M:%S", "%Y-%m-%d %H:%M", "%Y-%m-%dT%H:%M:%S"];
+
+/// Parses a bound of the analyzed period, after `with_time_of_day` gave bare dates a time.
+///
+/// # Arguments
+/// * `value` - The date and time, e.g. `2025-04-29 23:59:59`
+///
+/// # Returns
+/// * `Option<NaiveDateTime>` - The parsed date and time, None if the format is not supported
+pub fn parse_range_bound(value: &str) -> Option<NaiveDateTime> {
+    RANGE_DATE_FORMATS
+        .iter()
+        .find_map(|format| NaiveDateTime::parse_from_str(value.trim(), format).ok())
+}
+
+/// Finds a directory of gitnapped's own under the user's home, e.g. for the cache.
+///
+/// # Arguments
+/// * `xdg_variable` - The XDG variable naming the base directory, e.g. `XDG_CACHE_HOME`
+/// * `fallback` - The base directory relative to the home when the variable is not set, e.g. `.cache`
+///
+/// # Returns
+/// * `Option<PathBuf>` - The `gitnapped` directory in the base directory, None without a home
+pub fn user_dir(xdg_variable: &str, fallback: &str) -> Option<PathBuf> {
+    let base = env::var_os(xdg_variable)
+        .map(PathBuf::from)
+        .or_else(|| env::var_os("HOME").map(|home| PathBuf::from(home).join(fallback)))
+        .or_else(|| env::var_os("LOCALAPPDATA").map(PathBuf::from))?;
+    Some(base.join("gitnapped"))
+}
+
+/// Hashes a key into the name of the file it is saved under.
+pub fn key_file_name(key: &str) -> String {
+    let mut hasher = DefaultHasher::new();
+    key.hash(&mut hasher);
+    format!("{:016x}.json", hasher.finish())
+}
+
+/// Converts a bound of the analyzed period into a Unix timestamp, dates
+/// without a timezone being local as for git's `--since` and `--until`.
+///
+/// # Arguments
+/// * `value` - The date and time, e.g. `2025-04-29 23:59:59` or `2025-04-29T23:59:59+02:00`
+///
+/// # Returns
+/// * `Option<i64>` - The timestamp, None if the format is not supported
+pub fn bound_timestamp(value: &str) -> Option<i64> {
+    if let Ok(date) = DateTime::parse_from_rfc3339(value) {
+        return Some(date.timestamp());
     }
+    let date = parse_range_bound(value)?;
+    Local
+        .from_local_datetime(&date)
+        .earliest()
+        .map(|date| date.timestamp())
+}
+
+/// Computes the window of the same length right before a period, used to compare
+/// a period with the previous one.
+///
+/// # Arguments
+/// * `since` - Start of the period (YYYY-MM-DD HH:MM:SS)
+/// * `until` - End of the period (YYYY-MM-DD HH:MM:SS)
+///
+/// # Returns
+/// * `Option<(String, String)>` - Start and end of the previous period, None if the bounds do not parse
+pub fn previous_period(since: &str, until: &str) -> Option<(String, String)> {
+    let start = parse_range_bound(since)?;
+    let end = parse_range_bound(until)?;
+    let previous_end = start - Duration::seconds(1);
+    let previous_start = previous_end - (end - start);
+    Some((
+        previous_start.format("%Y-%m-%d %H:%M:%S").to_string(),
+        previous_end.format("%Y-%m-%d %H:%M:%S").to_string(),
+    ))
+}
+
+/// Resolves a date in any format git understands (e.g. `2.weeks.ago`, `noon yesterday`)
+/// by asking git itself. The expression is read as the default value of a
+/// config key no one sets, which git parses outside of any repository.
+///
+/// # Arguments
+/// * `value` - The date expression
+///
+/// # Returns
+/// * `Option<NaiveDateTime>` - The local date and time, None if git could not be run
+///   or does not understand the expression
+pub fn resolve_git_date(value: &str) -> Option<NaiveDateTime> {
+    let output = Command::new("git")
+        .args([
+            "config",
+            "--type=expiry-date",
+            "--default",
+            value,
+            "--get",
+            "gitnapped.unset-date",
+        ])
+        .output()
+        .ok()?;
+    if !output.status.success() {
+        return None;
+    }
+
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let timestamp: i64 = stdout.trim().parse().ok()?;
+    debug(&format!(
+        "git resolved '{}' to timestamp {}",
+        value, timestamp
+    ));
+
+    Some(
+        DateTime::from_timestamp(timestamp, 0)?
+            .with_timezone(&Local)
+            .naive_local(),
+    )
+}
+
+/// Looks up the committer date of the commit a tag points to.
+///
+/// # Arguments
+/// * `repo` - The path to the Git repository
+/// * `tag` - The tag name, e.g. `v2.3.0`
+/// * `after` - Whether to return the second after the commit, so that a period
+///   starting at a release leaves the release commit out
+///
+/// # Returns
+/// * `Option<String>` - The date in ISO 8601 format, None if the tag does not exist
+pub fn tag_commit_date(repo: &str, tag: &str, after: bool) -> Option<String> {
+    let output = Command::new("git")
+        .args([
+            "-C",
+            repo,
+            "log",
+            "-1",
+            "--format=%cI",
+            &format!("refs/tags/{}^{{commit}}", tag),
+            "--",
+        ])
+        .output()
+        .ok()?;
+    if !output.status.success() {
+        return None;
+    }
+
+    let date = DateTime::parse_from_rfc3339(String::from_utf8_lossy(&output.stdout).trim()).ok()?;
+    let date = if after {
+        date + Duration::seconds(1)
+    } else {
+        date
+    };
+    debug(&format!("Tag {} of {} resolved to {}", tag, repo, date));
+
+    Some(date.to_rfc3339())
+}
+
+/// Git attributes marking files as generated or vendored, which GitHub leaves
+/// out of the language statistics.
+const LINGUIST_ATTRIBUTES: [&str; 2] = ["linguist-generated", "linguist-vendored"];
+
+/// Finds the files marked as generated or vendored in `.gitattributes`.
+///
+/// # Arguments
+/// * `repo` - The path to the Git repository
+/// * `files` - The files to check, relative to the repository
+///
+/// # Returns
+/// * `HashSet<String>` - The files with `linguist-generated` or `linguist-vendored` set
+pub fn linguist_excluded_files(repo: &str, files: &[&str]) -> HashSet<String> {
+    let child = Command::new("git")
+        .args(["-C", repo, "check-attr", "-z", "--stdin"])
+        .args(LINGUIST_ATTRIBUTES)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::null())
+        .spawn();
+    let Ok(mut child) = child else {
+        return HashSet::new();
+    };
+
+    if let Some(mut stdin) = child.stdin.take() {
+        let paths: Vec<u8> = files
+            .iter()
+            .flat_map(|file| file.bytes().chain(std::iter::once(0)))
+            .collect();
+        // A failed write shows up as a failed git run below
+        let _ = stdin.write_all(&paths);
+    }
+    let Ok(output) = child.wait_with_output() else {
+        return HashSet::new();
+    };
+
+    // The output is a NUL separated list of path, attribute and value triples
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let fields: Vec<&str> = stdout.split('\0').collect();
+    fields
+        .chunks_exact(3)
+        .filter(|triple| matches!(triple[2], "set" | "true"))
+        .map(|triple| triple[0].to_string())
+        .collect()
+}
+
+/// Number of bytes looked at to tell binary files apart, as git does.
+const BINARY_SNIFF_BYTES: usize = 8000;
+
+/// Reads a file as text, unless it is a binary file.
+///
+/// Like git, a file is considered binary when it has a NUL byte in its first
+/// 8000 bytes, so binary files are never read in full.
+///
+/// # Arguments
+/// * `path` - The path to the file
+///
+/// # Returns
+/// * `Result<Option<String>, String>` - The content of a text file, None for a binary file
+fn read_text_file(path: &str) -> Result<Option<String>, String> {
+    let mut file = fs::File::open(path).map_err(|err| err.to_string())?;
+    let mut head = vec![0; BINARY_SNIFF_BYTES];
+    let mut read = 0;
+    while read < head.len() {
+        match file.read(&mut head[read..]) {
+            Ok(0) => break,
+            Ok(count) => read += count,
+            Err(err) => return Err(err.to_string()),
+        }
+    }
+    head.truncate(read);
+    if head.contains(&0) {
+        return Ok(None);
+    }
+
+    file.read_to_end(&mut head).map_err(|err| err.to_string())?;
+    String::from_utf8(head)
+        .map(Some)
+        .map_err(|err| err.to_string())
 }
 
 /// Counts the number of files and lines in a Git repository.
 ///
+/// Files marked as `linguist-generated` or `linguist-vendored` in `.gitattributes`
+/// are left out of the line count and the file types, like GitHub does.
+///
 /// # Arguments
 /// * `repo` - The path to the Git repository
+/// * `only` - Optional paths to restrict the count to, e.g. the files touched in a period
+/// * `pathspecs` - Pathspecs restricting the count to parts of the repository, empty for all of it
+/// * `test_patterns` - Path patterns of the test files, counted apart too
+/// * `backend` - How the tracked files and their attributes are read
 ///
 /// # Returns
-/// * `(usize, usize, HashMap<String, usize>)` - A tuple containing:
-///   - Number of files
-///   - Total number of lines
-///   - Map of file extensions to their counts
-pub fn count_files_and_lines(repo: &str) -> (usize, usize, HashMap<String, usize>) {
+/// * `FileCounts` - Files, lines and languages, with binary files counted apart
+pub fn count_files_and_lines(
+    repo: &str,
+    only: Option<&BTreeSet<String>>,
+    pathspecs: &[String],
+    test_patterns: &TestPatterns,
+    backend: Backend,
+) -> FileCounts {
     // Get all files tracked by git
     debug(&format!("Counting files and lines in repo: {}", repo));
 
-    let output = Command::new("git")
-        .args(["-C", repo, "ls-files"])
-        .output()
-        .expect("Failed to run git ls-files");
+    let tracked: Vec<String> = match backend {
+        Backend::Git => {
+            let output = Command::new("git")
+                .args(["-C", repo, "ls-files", "--"])
+                .args(pathspecs)
+                .output()
+                .expect("Failed to run git ls-files");
+            String::from_utf8_lossy(&output.stdout)
+                .lines()
+                .map(|file| file.to_string())
+                .collect()
+        }
+        #[cfg(feature = "libgit2")]
+        Backend::Libgit2 => libgit2::tracked_files(repo, pathspecs).unwrap_or_else(|err| {
+            debug(&format!("Error listing tracked files: {}", err));
+            Vec::new()
+        }),
+    };
 
-    let files_output = String::from_utf8_lossy(&output.stdout);
-    let files: Vec<&str> = files_output.lines().collect();
+    // Files deleted since they were touched are no longer tracked, so they are not counted
+    let files: Vec<&str> = tracked
+        .iter()
+        .map(|file| file.as_str())
+        .filter(|file| only.is_none_or(|only| only.contains(*file)))
+        .collect();
     let file_count = files.len();
 
     debug(&format!("Found {} tracked files in repo", file_count));
 
+    let excluded = match backend {
+        Backend::Git => linguist_excluded_files(repo, &files),
+        #[cfg(feature = "libgit2")]
+        Backend::Libgit2 => libgit2::linguist_excluded_files(repo, &files, &LINGUIST_ATTRIBUTES),
+    };
+    if !excluded.is_empty() {
+        debug(&format!(
+            "Skipping {} generated or vendored files",
+            excluded.len()
+        ));
+    }
+
     // Count lines in all tracked files and track file types
-    let mut total_lines = 0;
-    let mut file_types = HashMap::new();
+    let mut counts = FileCounts {
+        file_count,
+        ..FileCounts::default()
+    };
     let mut files_read = 0;
     let mut files_failed = 0;
 
     for file in files {
+        if excluded.contains(file) {
+            continue;
+        }
         let file_path = format!("{}/{}", repo, file);
-        let extension = get_file_extension(file);
-        *file_types.entry(extension).or_insert(0) += 1;
+        let content = read_text_file(&file_path);
+        let language = detect_language(file, content.as_ref().ok().and_then(|c| c.as_deref()));
+        *counts.file_types.entry(language.clone()).or_insert(0) += 1;
+        let is_test = test_patterns.is_test(file);
+        if is_test {
+            counts.test_files += 1;
+        }
 
-        if let Ok(content) = fs::read_to_string(&file_path) {
-            let line_count = content.lines().count();
-            total_lines += line_count;
-            files_read += 1;
-        } else {
-            files_failed += 1;
+        match content {
+            Ok(Some(content)) => {
+                let lines = classify_lines(&content, comment_syntax(&language));
+                counts.lines += lines;
+                if is_test {
+                    counts.test_lines += lines.total();
+                }
+                *counts.lines_by_type.entry(language).or_default() += lines;
+                files_read += 1;
+            }
+            Ok(None) => {
+                counts.binary_files += 1;
+                counts.binary_size += fs::metadata(&file_path).map_or(0, |meta| meta.len());
+            }
+            Err(err) => {
+                debug(&format!("Failed to read {}: {}", file_path, err));
+                files_failed += 1;
+            }
         }
     }
 
     debug(&format!(
-        "Successfully read {} files, failed to read {} files",
-        files_read, files_failed
+        "Successfully read {} files, skipped {} binary files, failed to read {} files",
+        files_read, counts.binary_files, files_failed
+    ));
+    debug(&format!(
+        "Total lines: {} ({} code, {} comments, {} blank)",
+        counts.lines.total(),
+        counts.lines.code,
+        counts.lines.comments,
+        counts.lines.blank
     ));
-    debug(&format!("Total lines: {}", total_lines));
 
-    (file_count, total_lines, file_types)
+    counts
 }
 
 /// Gets the day with the maximum number of commits from a commit history.
 ///
 /// # Arguments
-/// * `commits_by_date` - A HashMap mapping dates to commit counts
+/// * `commits_by_date` - A map of dates to commit counts
 ///
 /// # Returns
 /// * `Option<(String, usize)>` - The date and count of the most active day, if any
-pub fn get_max_commit_day(commits_by_date: &HashMap<String, usize>) -> Option<(String, usize)> {
+pub fn get_max_commit_day(commits_by_date: &BTreeMap<String, usize>) -> Option<(String, usize)> {
     if commits_by_date.is_empty() {
         return None;
     }
@@ -191,6 +650,376 @@ pub fn get_max_commit_day(commits_by_date: &HashMap<String, usize>) -> Option<(S
     Some((max_date, max_count))
 }
 
+/// Computes the commit streaks of a period: runs of consecutive days with commits,
+/// and the longest run of days without any.
+///
+/// The current streak ends on the last day of the period, or on the day before
+/// when nothing has been committed on the last day yet.
+///
+/// # Arguments
+/// * `commits_by_date` - A map of dates (YYYY-MM-DD) to commit counts
+/// * `since` - Start of the period, starting with a YYYY-MM-DD date
+/// * `until` - End of the period, starting with a YYYY-MM-DD date
+///
+/// # Returns
+/// * `(usize, usize, usize)` - Longest streak, current streak and longest gap, in days
+pub fn commit_streaks(
+    commits_by_date: &BTreeMap<String, usize>,
+    since: &str,
+    until: &str,
+) -> (usize, usize, usize) {
+    let parse = |value: &str| {
+        value
+            .get(..10)
+            .and_then(|date| NaiveDate::parse_from_str(date, "%Y-%m-%d").ok())
+    };
+    let (Some(first), Some(last)) = (parse(since), parse(until)) else {
+        return (0, 0, 0);
+    };
+
+    let active: Vec<bool> = first
+        .iter_days()
+        .take_while(|day| *day <= last)
+        .map(|day| {
+            commits_by_date
+                .get(&day.format("%Y-%m-%d").to_string())
+                .is_some_and(|count| *count > 0)
+        })
+        .collect();
+
+    let (mut longest_streak, mut longest_gap, mut streak, mut gap) = (0, 0, 0, 0);
+    for is_active in &active {
+        if *is_active {
+            streak += 1;
+            gap = 0;
+        } else {
+            gap += 1;
+            streak = 0;
+        }
+        longest_streak = longest_streak.max(streak);
+        longest_gap = longest_gap.max(gap);
+    }
+
+    let mut days = active.iter().rev().peekable();
+    if days.peek() == Some(&&false) {
+        days.next();
+    }
+    let current_streak = days.take_while(|is_active| **is_active).count();
+
+    (longest_streak, current_streak, longest_gap)
+}
+
+/// Finds the stretches of at least `min_days` consecutive days without commits,
+/// such as vacations, so they are not mistaken for low activity.
+///
+/// # Arguments
+/// * `commits_by_date` - A map of dates (YYYY-MM-DD) to commit counts
+/// * `since` - Start of the period, starting with a YYYY-MM-DD date
+/// * `until` - End of the period, starting with a YYYY-MM-DD date
+/// * `min_days` - Minimum length of a gap, in days
+///
+/// # Returns
+/// * `Vec<(NaiveDate, NaiveDate)>` - First and last day of every gap, in order
+pub fn inactivity_gaps(
+    commits_by_date: &BTreeMap<String, usize>,
+    since: &str,
+    until: &str,
+    min_days: usize,
+) -> Vec<(NaiveDate, NaiveDate)> {
+    let parse = |value: &str| {
+        value
+            .get(..10)
+            .and_then(|date| NaiveDate::parse_from_str(date, "%Y-%m-%d").ok())
+    };
+    let (Some(first), Some(last)) = (parse(since), parse(until)) else {
+        return Vec::new();
+    };
+
+    let mut gaps = Vec::new();
+    let mut gap_start: Option<NaiveDate> = None;
+    for day in first.iter_days().take_while(|day| *day <= last) {
+        let is_active = commits_by_date
+            .get(&day.format("%Y-%m-%d").to_string())
+            .is_some_and(|count| *count > 0);
+        match (is_active, gap_start) {
+            (false, None) => gap_start = Some(day),
+            (true, Some(start)) => {
+                gaps.push((start, day - Duration::days(1)));
+                gap_start = None;
+            }
+            _ => {}
+        }
+    }
+    if let Some(start) = gap_start {
+        gaps.push((start, last));
+    }
+
+    gaps.retain(|(start, end)| (*end - *start).num_days() as usize + 1 >= min_days);
+    gaps
+}
+
+/// Computes the average number of commits per day of a period.
+///
+/// # Arguments
+/// * `commit_count` - Number of commits in the period
+/// * `since` - Start of the period, starting with a YYYY-MM-DD date
+/// * `until` - End of the period, starting with a YYYY-MM-DD date
+/// * `excluded_days` - Days left out of the average, e.g. inactivity gaps
+///
+/// # Returns
+/// * `f64` - Commits per day, 0 when no day is left
+pub fn commits_per_day(commit_count: usize, since: &str, until: &str, excluded_days: usize) -> f64 {
+    let parse = |value: &str| {
+        value
+            .get(..10)
+            .and_then(|date| NaiveDate::parse_from_str(date, "%Y-%m-%d").ok())
+    };
+    let days = match (parse(since), parse(until)) {
+        (Some(first), Some(last)) => ((last - first).num_days() + 1).max(0) as usize,
+        _ => 0,
+    };
+    match days.saturating_sub(excluded_days) {
+        0 => 0.0,
+        days => (commit_count as f64 / days as f64 * 100.0).round() / 100.0,
+    }
+}
+
+/// Computes the typical working day: the average time of the first and of the
+/// last commit of every day with commits. Days whose first commit came after
+/// midnight, only worked late at night, leave the start of the day out, unless
+/// there are no others.
+///
+/// # Arguments
+/// * `stats` - The statistics holding the first and last commit of every day
+///
+/// # Returns
+/// * `Option<(String, String)>` - Average first and last commit time (HH:MM), None without commits
+pub fn typical_day(stats: &RepoStats) -> Option<(String, String)> {
+    let average = |minutes: Vec<u32>| {
+        if minutes.is_empty() {
+            return None;
+        }
+        let total: u64 = minutes.iter().map(|minute| *minute as u64).sum();
+        let average = (total as f64 / minutes.len() as f64).round() as u64;
+        // Late nights are past 24:00, shown as the time of the next morning
+        Some(format!("{:02}:{:02}", average / 60 % 24, average % 60))
+    };
+    let first_commits: Vec<u32> = stats.first_commit_by_date.values().copied().collect();
+    let started_before_midnight: Vec<u32> = first_commits
+        .iter()
+        .copied()
+        .filter(|minute| *minute < 24 * 60)
+        .collect();
+
+    Some((
+        average(if started_before_midnight.is_empty() {
+            first_commits
+        } else {
+            started_before_midnight
+        })?,
+        average(stats.last_commit_by_date.values().copied().collect())?,
+    ))
+}
+
+/// Finds the short-sleep days: nights whose last commit came after midnight
+/// while the first commit of the next day came before the given hour.
+///
+/// # Arguments
+/// * `stats` - The statistics holding the first and last commit of every day
+/// * `before_hour` - Hour before which a first commit after such a night counts as short sleep
+///
+/// # Returns
+/// * `Vec<String>` - The dates (YYYY-MM-DD) of the days that ended after midnight
+pub fn short_sleep_days(stats: &RepoStats, before_hour: u32) -> Vec<String> {
+    stats
+        .last_commit_by_date
+        .iter()
+        .filter(|(_, last_commit)| **last_commit >= 24 * 60)
+        .filter(|(date, _)| {
+            NaiveDate::parse_from_str(date, "%Y-%m-%d")
+                .ok()
+                .and_then(|date| date.succ_opt())
+                .and_then(|next| {
+                    stats
+                        .first_commit_by_date
+                        .get(&next.format("%Y-%m-%d").to_string())
+                })
+                .is_some_and(|first_commit| *first_commit < before_hour * 60)
+        })
+        .map(|(date, _)| date.clone())
+        .collect()
+}
+
+/// Computes the mean and median time between consecutive commits.
+///
+/// # Arguments
+/// * `commit_times` - Unix timestamps of the commits, in any order
+///
+/// # Returns
+/// * `Option<(u64, u64)>` - Mean and median interval in minutes, None with fewer than two commits
+pub fn commit_intervals(commit_times: &[i64]) -> Option<(u64, u64)> {
+    let mut times = commit_times.to_vec();
+    times.sort_unstable();
+
+    let mut intervals: Vec<u64> = times
+        .windows(2)
+        .map(|pair| (pair[1] - pair[0]) as u64 / 60)
+        .collect();
+    if intervals.is_empty() {
+        return None;
+    }
+    intervals.sort_unstable();
+
+    let mean = intervals.iter().sum::<u64>() / intervals.len() as u64;
+    let middle = intervals.len() / 2;
+    let median = if intervals.len().is_multiple_of(2) {
+        (intervals[middle - 1] + intervals[middle]) / 2
+    } else {
+        intervals[middle]
+    };
+
+    Some((mean, median))
+}
+
+/// Formats the binary files next to the number of files, e.g. ` (3 binary, 1.2 MiB)`,
+/// empty without binary files.
+pub fn binary_files_detail(stats: &RepoStats) -> String {
+    if stats.binary_files == 0 {
+        return String::new();
+    }
+    format!(
+        " ({} binary, {})",
+        stats.binary_files,
+        format_bytes(stats.binary_size)
+    )
+}
+
+/// Formats the share of test code and of commits touching tests, e.g.
+/// `12 files, 340 lines (18% of lines), in 40% of commits and 10% of gitnapped commits`.
+pub fn tests_detail(stats: &RepoStats) -> String {
+    let percent = |part: usize, whole: usize| {
+        if whole == 0 {
+            0
+        } else {
+            (part as f64 / whole as f64 * 100.0).round() as usize
+        }
+    };
+    format!(
+        "{} files, {} lines ({}% of lines), in {}% of commits and {}% of gitnapped commits",
+        stats.test_files,
+        stats.test_lines,
+        percent(stats.test_lines, stats.line_count),
+        percent(stats.test_commits, stats.commit_count),
+        percent(stats.out_of_hours_test_commits, stats.out_of_hours_commits)
+    )
+}
+
+/// Returns the languages of the statistics with their files and lines, most
+/// files or most lines first.
+///
+/// # Arguments
+/// * `stats` - The statistics holding the file types
+/// * `order` - Whether to order by files or by lines
+/// * `limit` - Maximum number of languages returned
+///
+/// # Returns
+/// * `Vec<(String, usize, usize)>` - Language, number of files and number of lines
+pub fn top_file_types(
+    stats: &RepoStats,
+    order: FileTypeOrder,
+    limit: usize,
+) -> Vec<(String, usize, usize)> {
+    let mut types: Vec<(String, usize, usize)> = stats
+        .file_types
+        .iter()
+        .map(|(language, files)| {
+            let lines = stats
+                .line_kinds_by_type
+                .get(language)
+                .map_or(0, |lines| lines.total());
+            (language.clone(), *files, lines)
+        })
+        .collect();
+
+    match order {
+        FileTypeOrder::Files => types.sort_by_key(|(_, files, lines)| Reverse((*files, *lines))),
+        FileTypeOrder::Lines => types.sort_by_key(|(_, files, lines)| Reverse((*lines, *files))),
+    }
+    types.truncate(limit);
+    types
+}
+
+/// Formats the files and lines of a language, e.g. `3 files, 120 lines`.
+pub fn file_type_detail(files: usize, lines: usize) -> String {
+    format!(
+        "{} {}, {} {}",
+        files,
+        "files".green(),
+        lines,
+        "lines".green()
+    )
+}
+
+/// Formats the comment and blank lines next to the lines of code, e.g.
+/// `(120 comments, 80 blank, 1400 lines in total)`.
+pub fn line_kinds_detail(lines: &LineCounts) -> String {
+    format!(
+        "({} comments, {} blank, {} lines in total)",
+        lines.comments,
+        lines.blank,
+        lines.total()
+    )
+}
+
+/// Formats a size in bytes with a binary unit, e.g. `512 B` or `3.4 MiB`.
+pub fn format_bytes(bytes: u64) -> String {
+    const UNITS: [&str; 4] = ["KiB", "MiB", "GiB", "TiB"];
+    if bytes < 1024 {
+        return format!("{} B", bytes);
+    }
+    let mut size = bytes as f64 / 1024.0;
+    let mut unit = 0;
+    while size >= 1024.0 && unit < UNITS.len() - 1 {
+        size /= 1024.0;
+        unit += 1;
+    }
+    format!("{:.1} {}", size, UNITS[unit])
+}
+
+/// Formats a number of minutes as days, hours and minutes, e.g. `2h 10m` or `3d 4h`.
+pub fn format_minutes(minutes: u64) -> String {
+    let (days, hours, minutes) = (minutes / 1440, minutes / 60 % 24, minutes % 60);
+    match (days, hours) {
+        (0, 0) => format!("{}m", minutes),
+        (0, _) => format!("{}h {}m", hours, minutes),
+        _ => format!("{}d {}h", days, hours),
+    }
+}
+
+/// Summarizes the sizes of a set of commits.
+///
+/// # Arguments
+/// * `sizes` - Lines changed by every commit
+///
+/// # Returns
+/// * `Option<(usize, usize, f64, usize)>` - Minimum, median, mean and maximum, None without commits
+pub fn commit_size_summary(sizes: &[usize]) -> Option<(usize, usize, f64, usize)> {
+    let mut sorted = sizes.to_vec();
+    sorted.sort_unstable();
+
+    let min = *sorted.first()?;
+    let max = *sorted.last()?;
+    let middle = sorted.len() / 2;
+    let median = if sorted.len().is_multiple_of(2) {
+        (sorted[middle - 1] + sorted[middle]) / 2
+    } else {
+        sorted[middle]
+    };
+    let mean = sorted.iter().sum::<usize>() as f64 / sorted.len() as f64;
+
+    Some((min, median, mean, max))
+}
+
 /// Aggregates multiple RepoStats into a single RepoStats object.
 ///
 /// # Arguments
@@ -204,23 +1033,120 @@ pub fn aggregate_stats(stats_vec: &[RepoStats]) -> RepoStats {
     for stats in stats_vec {
         aggregated.commit_count += stats.commit_count;
         aggregated.out_of_hours_commits += stats.out_of_hours_commits;
+        aggregated.out_of_hours_minutes += stats.out_of_hours_minutes;
+        aggregated.merge_commits += stats.merge_commits;
+        aggregated.files_counted |= stats.files_counted;
         aggregated.file_count += stats.file_count;
         aggregated.line_count += stats.line_count;
+        aggregated.line_kinds += stats.line_kinds;
+        aggregated.binary_files += stats.binary_files;
+        aggregated.binary_size += stats.binary_size;
+        aggregated.test_files += stats.test_files;
+        aggregated.test_lines += stats.test_lines;
+        aggregated.test_commits += stats.test_commits;
+        aggregated.out_of_hours_test_commits += stats.out_of_hours_test_commits;
+        for (ext, lines) in &stats.line_kinds_by_type {
+            *aggregated
+                .line_kinds_by_type
+                .entry(ext.clone())
+                .or_default() += *lines;
+        }
+        aggregated.lines_added += stats.lines_added;
+        aggregated.lines_removed += stats.lines_removed;
 
         // Merge commits by date
         for (date, count) in &stats.commits_by_date {
             *aggregated.commits_by_date.entry(date.clone()).or_insert(0) += count;
         }
 
+        for (date, count) in &stats.out_of_hours_by_date {
+            *aggregated
+                .out_of_hours_by_date
+                .entry(date.clone())
+                .or_insert(0) += count;
+        }
+
+        for (date, count) in &stats.lines_added_by_date {
+            *aggregated
+                .lines_added_by_date
+                .entry(date.clone())
+                .or_insert(0) += count;
+        }
+
+        for (date, count) in &stats.lines_removed_by_date {
+            *aggregated
+                .lines_removed_by_date
+                .entry(date.clone())
+                .or_insert(0) += count;
+        }
+
+        for (date, minute) in &stats.first_commit_by_date {
+            let first = aggregated
+                .first_commit_by_date
+                .entry(date.clone())
+                .or_insert(*minute);
+            *first = (*first).min(*minute);
+        }
+
+        for (date, minute) in &stats.last_commit_by_date {
+            let last = aggregated
+                .last_commit_by_date
+                .entry(date.clone())
+                .or_insert(*minute);
+            *last = (*last).max(*minute);
+        }
+
+        aggregated.commit_times.extend(&stats.commit_times);
+        aggregated.commit_sizes.extend(&stats.commit_sizes);
+        aggregated
+            .out_of_hours_commit_sizes
+            .extend(&stats.out_of_hours_commit_sizes);
+
+        for (hour, count) in stats.commits_by_hour.iter().enumerate() {
+            aggregated.commits_by_hour[hour] += count;
+        }
+
+        for (weekday, hours) in stats.punch_card.iter().enumerate() {
+            for (hour, count) in hours.iter().enumerate() {
+                aggregated.punch_card[weekday][hour] += count;
+            }
+        }
+
         // Merge file types
         for (ext, count) in &stats.file_types {
             *aggregated.file_types.entry(ext.clone()).or_insert(0) += count;
         }
+
+        for (author, author_stats) in &stats.authors {
+            let aggregated_author = aggregated.authors.entry(author.clone()).or_default();
+            aggregated_author.commit_count += author_stats.commit_count;
+            aggregated_author.out_of_hours_commits += author_stats.out_of_hours_commits;
+            aggregated_author.out_of_hours_minutes += author_stats.out_of_hours_minutes;
+        }
     }
 
     aggregated
 }
 
+/// Reads the identity git would commit with in a repository, to be used as
+/// the default author filter.
+///
+/// # Arguments
+/// * `repo` - The path to the Git repository
+///
+/// # Returns
+/// * `Option<String>` - `user.email`, or `user.name` when no email is set, None if neither is configured
+pub fn git_config_author(repo: &str) -> Option<String> {
+    ["user.email", "user.name"].iter().find_map(|key| {
+        let output = Command::new("git")
+            .args(["-C", repo, "config", key])
+            .output()
+            .ok()?;
+        let value = String::from_utf8_lossy(&output.stdout).trim().to_string();
+        (output.status.success() && !value.is_empty()).then_some(value)
+    })
+}
+
 /// Prints debug information about a Git command execution.
 ///
 /// # Arguments
@@ -268,98 +1194,3 @@ pub fn debug_git_command(repo: &str, cmd: &Command, output: &std::process::Outpu
 pub fn is_repo_active(stats: &RepoStats) -> bool {
     stats.commit_count > 0
 }
-
-/// Parses a working time string in either 24-hour (HH:MM-HH:MM) or 12-hour (HAM-PM) format.
-/// Returns a tuple of (start_hour, start_minute, end_hour, end_minute) in 24-hour format.
-///
-/// # Arguments
-/// * `time_str` - The working time string to parse
-///
-/// # Returns
-/// * `Option<(u32, u32, u32, u32)>` - The parsed times if successful, None if invalid
-///
-/// # Examples
-/// ```
-/// // 24-hour format
-/// let times = parse_working_time("09:00-17:00");
-/// assert_eq!(times, Some((9, 0, 17, 0)));
-///
-/// // 12-hour format
-/// let times = parse_working_time("9AM-5PM");
-/// assert_eq!(times, Some((9, 0, 17, 0)));
-/// ```
-pub fn parse_working_time(time_str: &str) -> Option<(u32, u32, u32, u32)> {
-    // Try 24-hour format first (HH:MM-HH:MM)
-    if let Some((start, end)) = time_str.split_once('-') {
-        let start = start.trim();
-        let end = end.trim();
-
-        // Parse 24-hour format
-        if let (Some((start_hour, start_min)), Some((end_hour, end_min))) =
-            (parse_24h_time(start), parse_24h_time(end))
-        {
-            return Some((start_hour, start_min, end_hour, end_min));
-        }
-
-        // Try 12-hour format
-        if let (Some((start_hour, start_min)), Some((end_hour, end_min))) =
-            (parse_12h_time(start), parse_12h_time(end))
-        {
-            return Some((start_hour, start_min, end_hour, end_min));
-        }
-    }
-
-    None
-}
-
-/// Parses a time string in 24-hour format (HH:MM)
-fn parse_24h_time(time: &str) -> Option<(u32, u32)> {
-    let parts: Vec<&str> = time.split(':').collect();
-    if parts.len() != 2 {
-        return None;
-    }
-
-    let hour: u32 = parts[0].trim().parse().ok()?;
-    let minute: u32 = parts[1].trim().parse().ok()?;
-
-    if hour > 23 || minute > 59 {
-        return None;
-    }
-
-    Some((hour, minute))
-}
-
-/// Parses a time string in 12-hour format (HAM or H:MMAM)
-fn parse_12h_time(time: &str) -> Option<(u32, u32)> {
-    let time = time.to_uppercase();
-    let is_pm = time.contains("PM");
-    let time = time.replace("AM", "").replace("PM", "").trim().to_string();
-
-    let parts: Vec<&str> = time.split(':').collect();
-    let hour: u32 = parts[0].trim().parse().ok()?;
-    let minute: u32 = if parts.len() > 1 {
-        parts[1].trim().parse().ok()?
-    } else {
-        0
-    };
-
-    if hour > 12 || minute > 59 {
-        return None;
-    }
-
-    let hour_24 = if is_pm {
-        if hour == 12 {
-            12
-        } else {
-            hour + 12
-        }
-    } else {
-        if hour == 12 {
-            0
-        } else {
-            hour
-        }
-    };
-
-    Some((hour_24, minute))
-}
@@ -0,0 +1,563 @@
+use crate::clones::is_clone_url;
+use crate::config::{include_path, is_git_repository, read_config, schedules_from_config};
+use crate::discovery::expand_repo_path;
+use crate::language::TestPatterns;
+use crate::models::{Config, RepoEntry};
+use crate::parser::{parse_repo_entry, repo_entry_path, with_repo_entry_path};
+use crate::remote::{remote_prefix_length, RemoteRepo};
+use crate::tickets::TicketPatterns;
+use crate::utils::parse_fiscal_year_start;
+use chrono::Weekday;
+use regex::Regex;
+use serde::de::DeserializeOwned;
+use serde_yaml::{Mapping, Value};
+use std::collections::HashMap;
+use std::fmt;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Settings of the config file, as read into `Config`.
+const CONFIG_KEYS: [&str; 18] = [
+    "include",
+    "author",
+    "exclude_authors",
+    "repos",
+    "working_time",
+    "working_time_by_day",
+    "workdays",
+    "working_time_by_category",
+    "working_time_by_repo",
+    "week_start",
+    "fiscal_year_start",
+    "team",
+    "test_patterns",
+    "ticket_patterns",
+    "profiles",
+    "defaults",
+    "email",
+    "forges",
+];
+
+/// Settings of a team member, as read into `TeamMemberConfig`.
+const TEAM_KEYS: [&str; 4] = ["author", "working_time", "timezone", "workdays"];
+
+/// Settings of a structured repository, as read into `RepoConfig`.
+const REPO_KEYS: [&str; 6] = [
+    "path",
+    "name",
+    "category",
+    "tags",
+    "working_hours",
+    "exclude",
+];
+
+/// Settings of the email section, as read into `EmailConfig`.
+const EMAIL_KEYS: [&str; 8] = [
+    "smtp_host",
+    "smtp_port",
+    "tls",
+    "username",
+    "password_env",
+    "from",
+    "to",
+    "subject",
+];
+
+/// Settings of a forge, as read into `ForgeConfig`.
+const FORGE_KEYS: [&str; 2] = ["url", "token_env"];
+
+/// Settings of a scan entry, as read into `ScanConfig`.
+const SCAN_KEYS: [&str; 2] = ["scan", "depth"];
+
+/// A problem found in a config file by `validate_config`.
+pub struct Issue {
+    /// The config file, or the included file the problem is in
+    pub file: String,
+    /// The line of the problem, when it can be told
+    pub line: Option<usize>,
+    /// What is wrong
+    pub message: String,
+}
+
+impl fmt::Display for Issue {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self.line {
+            Some(line) => write!(f, "{}:{}: {}", self.file, line, self.message),
+            None => write!(f, "{}: {}", self.file, self.message),
+        }
+    }
+}
+
+/// The text of a config file, to tell the lines of what is found in it.
+struct Source {
+    /// Path of the file
+    path: String,
+    /// Lines of the file
+    lines: Vec<String>,
+    /// Index of the line after the last one found, as the file is read in order
+    cursor: usize,
+}
+
+impl Source {
+    /// Returns the line (from 1) of a text, looked for after the last line
+    /// found, so repeated texts point to their own lines, then from the start.
+    fn line_of(&mut self, text: &str) -> Option<usize> {
+        let find = |from: usize| {
+            self.lines
+                .iter()
+                .skip(from)
+                .position(|line| line.contains(text))
+                .map(|index| from + index + 1)
+        };
+        let line = find(self.cursor).or_else(|| find(0));
+        if let Some(line) = line {
+            self.cursor = line;
+        }
+        line
+    }
+
+    /// Returns the line (from 1) of the first `key:` or `key =` of the file.
+    fn line_of_key(&self, key: &str) -> Option<usize> {
+        self.lines
+            .iter()
+            .position(|line| {
+                let line = line.trim_start().trim_start_matches(['-', '[', ' ']);
+                let line = line.trim_start_matches(['"', '\'']);
+                line.strip_prefix(key).is_some_and(|rest| {
+                    rest.trim_start_matches(['"', '\'', ' '])
+                        .starts_with([':', '=', ']', '.'])
+                })
+            })
+            .map(|index| index + 1)
+    }
+
+    /// Builds a problem of the file.
+    fn issue(&self, line: Option<usize>, message: String) -> Issue {
+        Issue {
+            file: self.path.clone(),
+            line,
+            message,
+        }
+    }
+}
+
+/// Checks a config file without analyzing anything, so mistakes are found
+/// before a long run: syntax errors, unknown keys, malformed `[Category][Name]`
+/// labels, repositories that do not exist or are not Git repositories,
+/// repositories listed twice and invalid settings. Included files are checked
+/// too, and so are the profiles.
+///
+/// # Arguments
+/// * `path` - The config file
+///
+/// # Returns
+/// * `Vec<Issue>` - The problems found, none for a valid config
+pub fn validate_config(path: &str) -> Vec<Issue> {
+    let mut issues = Vec::new();
+    if !Path::new(path).exists() {
+        issues.push(Issue {
+            file: path.to_string(),
+            line: None,
+            message: "Config file not found".to_string(),
+        });
+        return issues;
+    }
+
+    let mut listed = HashMap::new();
+    check_file(Path::new(path), &mut issues, &mut listed, &mut Vec::new());
+    // The settings are only read once the files themselves are right
+    if issues.is_empty() {
+        check_settings(path, &mut issues);
+    }
+
+    issues
+}
+
+/// Checks a config file and the files it includes.
+///
+/// # Arguments
+/// * `path` - The config file
+/// * `issues` - The problems found so far
+/// * `listed` - The repositories found so far by canonical key, with where they are listed
+/// * `checked` - The files checked so far, so every file is only checked once
+fn check_file(
+    path: &Path,
+    issues: &mut Vec<Issue>,
+    listed: &mut HashMap<String, String>,
+    checked: &mut Vec<PathBuf>,
+) {
+    let canonical = fs::canonicalize(path).unwrap_or_else(|_| path.to_path_buf());
+    if checked.contains(&canonical) {
+        return;
+    }
+    checked.push(canonical);
+
+    let text = match fs::read_to_string(path) {
+        Ok(text) => text,
+        Err(err) => {
+            issues.push(Issue {
+                file: path.display().to_string(),
+                line: None,
+                message: format!("Cannot read the file: {}", err),
+            });
+            return;
+        }
+    };
+    let mut source = Source {
+        path: path.display().to_string(),
+        lines: text.lines().map(str::to_string).collect(),
+        cursor: 0,
+    };
+
+    let is_toml = path
+        .extension()
+        .is_some_and(|extension| extension.eq_ignore_ascii_case("toml"));
+    let value = if is_toml {
+        parse::<toml::Table>(&text, true)
+            .and_then(|table| serde_yaml::to_value(table).map_err(|err| (None, err.to_string())))
+    } else {
+        parse::<Value>(&text, false)
+    };
+    let value = match value {
+        Ok(value) => value,
+        Err((line, message)) => {
+            issues.push(source.issue(line, message));
+            return;
+        }
+    };
+    let Some(settings) = value.as_mapping() else {
+        issues.push(source.issue(None, "The config is not a map of settings".to_string()));
+        return;
+    };
+
+    let found = issues.len();
+    check_keys(&source, settings, &CONFIG_KEYS, "setting", issues);
+    if let Some(repos) = settings.get("repos") {
+        check_repos(&mut source, repos, issues, listed);
+    }
+    if let Some(team) = settings.get("team").and_then(Value::as_sequence) {
+        for member in team.iter().filter_map(Value::as_mapping) {
+            check_keys(&source, member, &TEAM_KEYS, "team member setting", issues);
+        }
+    }
+    if let Some(email) = settings.get("email").and_then(Value::as_mapping) {
+        check_keys(&source, email, &EMAIL_KEYS, "email setting", issues);
+    }
+    if let Some(forges) = settings.get("forges").and_then(Value::as_mapping) {
+        for forge in forges.values().filter_map(Value::as_mapping) {
+            check_keys(&source, forge, &FORGE_KEYS, "forge setting", issues);
+        }
+    }
+    if let Some(profiles) = settings.get("profiles").and_then(Value::as_mapping) {
+        for profile in profiles.values().filter_map(Value::as_mapping) {
+            let known: Vec<&str> = CONFIG_KEYS.iter().copied().chain(["categories"]).collect();
+            check_keys(&source, profile, &known, "profile setting", issues);
+            // The repositories of a profile are added to the others, so they are not duplicates of them
+            if let Some(repos) = profile.get("repos") {
+                check_repos(&mut source, repos, issues, &mut HashMap::new());
+            }
+        }
+    }
+    // Type errors are told better by the checks above, when they find them
+    if issues.len() == found {
+        if let Err((line, message)) = parse::<Config>(&text, is_toml) {
+            issues.push(source.issue(line, message));
+        }
+    }
+
+    let includes = settings.get("include").and_then(Value::as_sequence);
+    for include in includes.into_iter().flatten().filter_map(Value::as_str) {
+        let included = include_path(path, include);
+        if included.exists() {
+            check_file(&included, issues, listed, checked);
+        } else {
+            let line = source.line_of(include);
+            issues.push(source.issue(
+                line,
+                format!("Included file '{}' not found", included.display()),
+            ));
+        }
+    }
+}
+
+/// Parses a config file, returning the line and message of its error.
+fn parse<T: DeserializeOwned>(text: &str, is_toml: bool) -> Result<T, (Option<usize>, String)> {
+    if is_toml {
+        toml::from_str(text).map_err(|err| {
+            let line = err
+                .span()
+                .map(|span| text[..span.start].matches('\n').count() + 1);
+            (line, err.message().to_string())
+        })
+    } else {
+        serde_yaml::from_str(text).map_err(|err| {
+            (
+                err.location().map(|location| location.line()),
+                err.to_string(),
+            )
+        })
+    }
+}
+
+/// Reports the keys of a map of settings that are not known.
+fn check_keys(
+    source: &Source,
+    settings: &Mapping,
+    known: &[&str],
+    kind: &str,
+    issues: &mut Vec<Issue>,
+) {
+    for key in settings.keys() {
+        let key = key.as_str().unwrap_or_default();
+        if !known.contains(&key) {
+            issues.push(source.issue(
+                source.line_of_key(key),
+                format!("Unknown {} '{}'", kind, key),
+            ));
+        }
+    }
+}
+
+/// Checks the `repos` section of a config file or profile: every category
+/// must list repositories, written as strings with well-formed labels or as
+/// objects with known settings, that exist and are listed once.
+fn check_repos(
+    source: &mut Source,
+    repos: &Value,
+    issues: &mut Vec<Issue>,
+    listed: &mut HashMap<String, String>,
+) {
+    let Some(categories) = repos.as_mapping() else {
+        let line = source.line_of_key("repos");
+        issues.push(source.issue(line, "repos is not a map of categories".to_string()));
+        return;
+    };
+
+    for (category, entries) in categories {
+        let category = category.as_str().unwrap_or_default();
+        let Some(entries) = entries.as_sequence() else {
+            issues.push(source.issue(
+                source.line_of_key(category),
+                format!("The repositories of '{}' are not a list", category),
+            ));
+            continue;
+        };
+
+        for value in entries {
+            let (entry, line) =
+                match value {
+                    Value::String(text) => (RepoEntry::Line(text.clone()), source.line_of(text)),
+                    Value::Mapping(settings) => {
+                        let (known, path) = if settings.contains_key("scan") {
+                            (&SCAN_KEYS[..], "scan")
+                        } else {
+                            (&REPO_KEYS[..], "path")
+                        };
+                        let Some(written) = settings.get(path).and_then(Value::as_str) else {
+                            issues.push(source.issue(
+                                source.line_of_key(category),
+                                format!("A repository of '{}' has no {}", category, path),
+                            ));
+                            continue;
+                        };
+                        let line = source.line_of(written);
+                        let unknown: Vec<&str> = settings
+                            .keys()
+                            .filter_map(Value::as_str)
+                            .filter(|key| !known.contains(key))
+                            .collect();
+                        for key in &unknown {
+                            issues.push(source.issue(
+                                line,
+                                format!("Unknown setting '{}' of repository '{}'", key, written),
+                            ));
+                        }
+                        // Unknown settings are why the entry cannot be read, already told
+                        if !unknown.is_empty() {
+                            continue;
+                        }
+                        match serde_yaml::from_value(value.clone()) {
+                            Ok(entry) => (entry, line),
+                            Err(err) => {
+                                issues.push(source.issue(
+                                    line,
+                                    format!("Invalid repository '{}': {}", written, err),
+                                ));
+                                continue;
+                            }
+                        }
+                    }
+                    _ => {
+                        issues.push(source.issue(
+                            source.line_of_key(category),
+                            format!(
+                                "A repository of '{}' is neither a string nor an object",
+                                category
+                            ),
+                        ));
+                        continue;
+                    }
+                };
+            check_repo(source, &entry, line, issues, listed);
+        }
+    }
+}
+
+/// Checks a repository of the config, written on a line of the file: its
+/// labels, and that its path, once expanded, is a Git repository listed once.
+fn check_repo(
+    source: &Source,
+    entry: &RepoEntry,
+    line: Option<usize>,
+    issues: &mut Vec<Issue>,
+    listed: &mut HashMap<String, String>,
+) {
+    let written = repo_entry_path(entry);
+    if let RepoEntry::Line(text) = entry {
+        if let Some(problem) = label_problem(text) {
+            issues.push(source.issue(line, format!("{} in '{}'", problem, text)));
+        }
+    }
+
+    let paths = expand_repo_path(written);
+    if paths.is_empty() {
+        issues.push(source.issue(line, format!("'{}' matches no directory", written)));
+    }
+    for path in paths {
+        let entry = with_repo_entry_path(entry, &path);
+        if let RepoEntry::Scan(_) = entry {
+            if !Path::new(&path).is_dir() {
+                issues.push(
+                    source.issue(line, format!("Directory '{}' to scan does not exist", path)),
+                );
+            }
+            continue;
+        }
+
+        let repo = parse_repo_entry(&entry);
+        // Clone URLs are only cloned when analyzing
+        let url = is_clone_url(&repo.path);
+        let remote = url || remote_prefix_length(&repo.path) > 0;
+        if remote && !url && RemoteRepo::parse(&repo.path).is_none() {
+            issues.push(source.issue(
+                line,
+                format!(
+                    "'{}' is not a remote repository, e.g. github:org/repo, gitlab:group/project, bitbucket:workspace/repo or gitea:host/owner/repo",
+                    repo.path
+                ),
+            ));
+        } else if !remote && !Path::new(&repo.path).exists() {
+            issues.push(source.issue(line, format!("Repository '{}' does not exist", repo.path)));
+        } else if !remote && !is_git_repository(&repo.path) {
+            issues.push(source.issue(line, format!("'{}' is not a Git repository", repo.path)));
+        } else {
+            let here = match line {
+                Some(line) => format!("{}:{}", source.path, line),
+                None => source.path.clone(),
+            };
+            match listed.get(&repo.canonical_key()) {
+                Some(first) => issues.push(source.issue(
+                    line,
+                    format!("'{}' is already listed at {}", repo.key(), first),
+                )),
+                None => {
+                    listed.insert(repo.canonical_key(), here);
+                }
+            }
+        }
+    }
+}
+
+/// Tells what is wrong with the labels of a `path [Category][Name]` string:
+/// unbalanced or nested brackets, empty labels, more than two of them, or text
+/// after them. None when they are right, or missing.
+fn label_problem(line: &str) -> Option<&'static str> {
+    let start = line.find(['[', ']'])?;
+    let labels = line[start..].trim_end();
+    let well_formed = Regex::new(r"^(\[[^\[\]]*\]\s*)+$").expect("valid regex");
+    if !well_formed.is_match(labels) {
+        return Some("Malformed labels, expected [Category][Name] or [Name]");
+    }
+
+    let names: Vec<&str> = labels
+        .split('[')
+        .skip(1)
+        .map(|label| label.split(']').next().unwrap_or_default().trim())
+        .collect();
+    if names.len() > 2 {
+        Some("Too many labels, expected [Category][Name] or [Name]")
+    } else if names.iter().any(|name| name.is_empty()) {
+        Some("Empty label")
+    } else {
+        None
+    }
+}
+
+/// Checks the settings of the config once its files are merged, for the
+/// config itself and every profile of it.
+fn check_settings(path: &str, issues: &mut Vec<Issue>) {
+    let config = match read_config(path, None) {
+        Ok(config) => config,
+        Err(err) => {
+            issues.push(Issue {
+                file: path.to_string(),
+                line: None,
+                message: err,
+            });
+            return;
+        }
+    };
+    let error = check_schedules(&config).err();
+    if let Some(err) = &error {
+        issues.push(Issue {
+            file: path.to_string(),
+            line: None,
+            message: err.clone(),
+        });
+    }
+
+    let mut profiles: Vec<&String> = config.profiles.keys().collect();
+    profiles.sort();
+    for profile in profiles {
+        let checked = read_config(path, Some(profile)).and_then(|config| {
+            match check_schedules(&config) {
+                // The profiles inherit the errors of the config, told once
+                Err(err) if Some(&err) != error.as_ref() => {
+                    Err(format!("In profile '{}': {}", profile, err))
+                }
+                _ => Ok(()),
+            }
+        });
+        if let Err(err) = checked {
+            issues.push(Issue {
+                file: path.to_string(),
+                line: None,
+                message: err,
+            });
+        }
+    }
+}
+
+/// Builds the schedules and reads the other settings of a config the way an
+/// analysis does, for the errors it would stop on.
+fn check_schedules(config: &Config) -> Result<(), String> {
+    schedules_from_config(config)?;
+
+    if let Some(week_start) = &config.week_start {
+        week_start
+            .parse::<Weekday>()
+            .map_err(|_| format!("Unknown weekday '{}' in week_start", week_start))?;
+    }
+    if let Some(start) = &config.fiscal_year_start {
+        parse_fiscal_year_start(start)
+            .ok_or_else(|| format!("Invalid fiscal_year_start '{}', expected MM-DD", start))?;
+    }
+    if let Some(patterns) = &config.test_patterns {
+        TestPatterns::new(patterns)?;
+    }
+    if let Some(patterns) = &config.ticket_patterns {
+        TicketPatterns::new(patterns)?;
+    }
+
+    Ok(())
+}
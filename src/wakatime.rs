@@ -0,0 +1,72 @@
+use crate::utils::debug;
+use chrono::NaiveDate;
+use serde::Deserialize;
+use std::collections::BTreeMap;
+use std::fs;
+
+/// A file of WakaTime summaries: the response of the summaries API, with a
+/// `data` list, or the export of the dashboard, with a `days` list.
+#[derive(Deserialize)]
+struct SummariesFile {
+    #[serde(default)]
+    data: Vec<Summary>,
+    #[serde(default)]
+    days: Vec<Summary>,
+}
+
+/// The summary of a day, dated by its `range` in the API and by `date` in the export.
+#[derive(Deserialize)]
+struct Summary {
+    date: Option<String>,
+    range: Option<SummaryRange>,
+    grand_total: GrandTotal,
+}
+
+#[derive(Deserialize)]
+struct SummaryRange {
+    date: String,
+}
+
+#[derive(Deserialize)]
+struct GrandTotal {
+    total_seconds: f64,
+}
+
+/// Reads the time spent in editors every day from a file of WakaTime
+/// summaries, e.g. saved from
+/// `https://wakatime.com/api/v1/users/current/summaries?start=...&end=...`.
+///
+/// # Arguments
+/// * `path` - The JSON file
+///
+/// # Returns
+/// * `Result<BTreeMap<NaiveDate, usize>, String>` - The minutes of every summarized day, or an error telling why the file was not read
+pub fn read_wakatime_summaries(path: &str) -> Result<BTreeMap<NaiveDate, usize>, String> {
+    let content =
+        fs::read_to_string(path).map_err(|err| format!("Failed to read {}: {}", path, err))?;
+    let file: SummariesFile = serde_json::from_str(&content)
+        .map_err(|err| format!("{} is not a file of WakaTime summaries: {}", path, err))?;
+
+    let mut minutes = BTreeMap::new();
+    for summary in file.data.into_iter().chain(file.days) {
+        let Some(date) = summary.date.or(summary.range.map(|range| range.date)) else {
+            debug("Skipping a WakaTime summary without a date");
+            continue;
+        };
+        match NaiveDate::parse_from_str(&date, "%Y-%m-%d") {
+            Ok(day) => {
+                *minutes.entry(day).or_default() +=
+                    (summary.grand_total.total_seconds / 60.0).round() as usize;
+            }
+            Err(err) => debug(&format!(
+                "Skipping the WakaTime summary of {}: {}",
+                date, err
+            )),
+        }
+    }
+    debug(&format!(
+        "Read {} days of WakaTime summaries",
+        minutes.len()
+    ));
+    Ok(minutes)
+}